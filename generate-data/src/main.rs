@@ -2,7 +2,7 @@ use anyhow::Context;
 use futures::StreamExt;
 use indexmap::IndexSet;
 use itertools::Itertools;
-use language_utils::{COURSES, NlpAnalyzedSentence, SentenceInfo, strip_punctuation};
+use language_utils::{COURSES, Lexeme, NlpAnalyzedSentence, SentenceInfo, strip_punctuation};
 use std::collections::{BTreeMap, BTreeSet};
 use std::fs::File;
 use std::io::{BufRead, BufReader, BufWriter, Write};
@@ -715,6 +715,49 @@ async fn main() -> anyhow::Result<()> {
             (pronunciation_to_words, word_to_pronunciation)
         };
 
+        // Themed word packs: one lemma or multiword term per line, matched against the frequency list
+        let word_packs = {
+            let word_packs_dir = source_data_path.join("word_packs");
+            let mut word_packs = Vec::new();
+            if word_packs_dir.exists() {
+                let mut files = std::fs::read_dir(&word_packs_dir)?
+                    .map(|entry| entry.map(|entry| entry.path()))
+                    .collect::<Result<Vec<_>, _>>()?;
+                files.sort();
+                for file in files {
+                    if file.extension().is_none_or(|extension| extension != "txt") {
+                        continue;
+                    }
+                    let name = file
+                        .file_stem()
+                        .context("Word pack file has no name")?
+                        .to_string_lossy()
+                        .to_string();
+                    let content = std::fs::read_to_string(&file)
+                        .with_context(|| format!("Failed to read word pack {name}"))?;
+                    let terms = content
+                        .lines()
+                        .map(|line| line.trim())
+                        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+                        .collect::<std::collections::HashSet<_>>();
+                    let lexemes = frequencies
+                        .iter()
+                        .map(|frequency| &frequency.lexeme)
+                        .filter(|lexeme| match lexeme {
+                            Lexeme::Heteronym(heteronym) => {
+                                terms.contains(heteronym.lemma.as_str())
+                            }
+                            Lexeme::Multiword(term) => terms.contains(term.as_str()),
+                        })
+                        .cloned()
+                        .collect::<Vec<_>>();
+                    println!("Loaded word pack {name} with {} lexemes", lexemes.len());
+                    word_packs.push((name, lexemes));
+                }
+            }
+            word_packs
+        };
+
         // Create consolidated data structure
         let consolidated_data = language_utils::ConsolidatedLanguageData {
             target_language_sentences,
@@ -725,6 +768,7 @@ async fn main() -> anyhow::Result<()> {
             frequencies,
             word_to_pronunciation,
             pronunciation_to_words,
+            word_packs,
         };
 
         let mut rodeo = lasso::Rodeo::new();
@@ -892,6 +892,20 @@ pub mod autograde {
         pub expressions_forgot: Vec<Lexeme<String>>,
     }
 
+    /// Grading a translation from the user's native language into the target language. The response is an
+    /// `AutoGradeTranslationResponse`, with the expressions being the ones the user needed to produce.
+    #[derive(Clone, Debug, serde::Serialize, serde::Deserialize, tsify::Tsify)]
+    #[tsify(into_wasm_abi, from_wasm_abi)]
+    pub struct AutoGradeProductionRequest {
+        pub language: Language,
+        pub challenge_sentence: String,
+        /// A correct translation. Other translations may be just as good.
+        pub reference_sentence: String,
+        pub user_sentence: String,
+        pub primary_expression: Lexeme<String>,
+        pub lexemes: Vec<Lexeme<String>>,
+    }
+
     #[derive(Clone, Debug, serde::Serialize, serde::Deserialize, tsify::Tsify)]
     #[tsify(into_wasm_abi, from_wasm_abi)]
     pub struct AutoGradeTranscriptionRequest {
@@ -1051,6 +1065,8 @@ pub struct ConsolidatedLanguageData {
     pub word_to_pronunciation: Vec<(String, Pronunciation)>,
     /// Mapping from IPA pronunciations to lists of words
     pub pronunciation_to_words: Vec<(Pronunciation, Vec<String>)>,
+    /// Themed word lists (travel, food, ...) by name, each in frequency order
+    pub word_packs: Vec<(String, Vec<Lexeme<String>>)>,
 }
 
 #[derive(
@@ -1226,6 +1242,39 @@ pub struct TtsRequest {
     pub language: Language,
 }
 
+/// Speech recognition: what did the user say in `language`?
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, tsify::Tsify)]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+pub struct AsrRequest {
+    /// The recording, base64 encoded. Any format the browser's `MediaRecorder` produces is fine.
+    pub audio: String,
+    pub language: Language,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, tsify::Tsify)]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+pub struct AsrResponse {
+    pub transcript: String,
+}
+
+/// A memory aid for a word the user keeps forgetting.
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, tsify::Tsify)]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+pub struct MnemonicRequest {
+    pub language: Language,
+    pub word: String,
+    /// What the word means, in English.
+    pub meanings: Vec<String>,
+}
+
+#[derive(
+    serde::Serialize, serde::Deserialize, Debug, Clone, schemars::JsonSchema, tsify::Tsify,
+)]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+pub struct MnemonicResponse {
+    pub mnemonic: String,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
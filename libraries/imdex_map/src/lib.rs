@@ -95,6 +95,11 @@ impl<K: Clone + Hash + Eq, V: Clone> IndexMap<K, V> {
         Some(old_value)
     }
 
+    /// Returns the index of the given key, if it is in the map
+    pub fn get_index_of(&self, key: &K) -> Option<usize> {
+        self.indices.get(key).copied()
+    }
+
     /// Gets the key-value pair at the given index
     pub fn get_index(&self, index: usize) -> Option<(&K, &V)> {
         self.order.get(index).map(|(k, v)| (k, v))
@@ -120,6 +125,11 @@ impl<K: Clone + Hash + Eq, V: Clone> IndexMap<K, V> {
         self.order.iter().map(|(_, v)| v)
     }
 
+    /// Returns a mutable iterator over the values in insertion order
+    pub fn values_mut(&mut self) -> impl Iterator<Item = &mut V> {
+        self.order.iter_mut().map(|(_, v)| v)
+    }
+
     /// Clears the map, removing all key-value pairs
     pub fn clear(&mut self) {
         self.indices = HashMap::new();
@@ -0,0 +1,97 @@
+//! Speech recognition, behind a trait so the provider can be swapped out. Set `ASR_PROVIDER=stub` to run without
+//! calling out to anyone, e.g. when testing the frontend against a local server.
+
+use axum::http::StatusCode;
+use language_utils::Language;
+use serde::Deserialize;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::LazyLock;
+
+pub type TranscriptFuture<'a> =
+    Pin<Box<dyn Future<Output = Result<String, StatusCode>> + Send + 'a>>;
+
+pub trait SpeechRecognizer: Send + Sync {
+    /// Transcribe `audio`, which is in `language`.
+    fn transcribe(&self, audio: Vec<u8>, language: Language) -> TranscriptFuture<'_>;
+}
+
+pub static RECOGNIZER: LazyLock<Box<dyn SpeechRecognizer>> =
+    LazyLock::new(|| match std::env::var("ASR_PROVIDER").as_deref() {
+        Ok("stub") => Box::new(StubRecognizer {
+            transcript: std::env::var("ASR_STUB_TRANSCRIPT").unwrap_or_default(),
+        }),
+        _ => Box::new(ElevenLabsRecognizer),
+    });
+
+pub struct ElevenLabsRecognizer;
+
+#[derive(Deserialize)]
+struct ElevenLabsTranscript {
+    text: String,
+}
+
+impl SpeechRecognizer for ElevenLabsRecognizer {
+    fn transcribe(&self, audio: Vec<u8>, language: Language) -> TranscriptFuture<'_> {
+        Box::pin(async move {
+            let elevenlabs_api_key = std::env::var("ELEVENLABS_API_KEY")
+                .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+            let form = reqwest::multipart::Form::new()
+                .text("model_id", "scribe_v1")
+                .text("language_code", language.iso_639_3().to_string())
+                // We only want the words, not "(laughs)" and the like
+                .text("tag_audio_events", "false")
+                .part(
+                    "file",
+                    reqwest::multipart::Part::bytes(audio).file_name("recording"),
+                );
+
+            let response = reqwest::Client::new()
+                .post("https://api.elevenlabs.io/v1/speech-to-text")
+                .header("xi-api-key", elevenlabs_api_key)
+                .multipart(form)
+                .send()
+                .await
+                .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+            if !response.status().is_success() {
+                return Err(StatusCode::BAD_GATEWAY);
+            }
+
+            let transcript: ElevenLabsTranscript = response
+                .json()
+                .await
+                .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+            Ok(transcript.text)
+        })
+    }
+}
+
+/// Hears the same thing every time, whatever the audio.
+pub struct StubRecognizer {
+    pub transcript: String,
+}
+
+impl SpeechRecognizer for StubRecognizer {
+    fn transcribe(&self, _audio: Vec<u8>, _language: Language) -> TranscriptFuture<'_> {
+        Box::pin(async move { Ok(self.transcript.clone()) })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn stub_ignores_the_audio() {
+        let recognizer: Box<dyn SpeechRecognizer> = Box::new(StubRecognizer {
+            transcript: "le chat mange".to_string(),
+        });
+        let transcript = recognizer
+            .transcribe(vec![1, 2, 3], Language::French)
+            .await
+            .unwrap();
+        assert_eq!(transcript, "le chat mange");
+    }
+}
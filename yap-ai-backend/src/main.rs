@@ -1,3 +1,5 @@
+mod asr;
+
 use axum::{
     Router,
     extract::{Json, Path},
@@ -11,7 +13,10 @@ use axum_extra::{
 };
 use base64::Engine;
 use jsonwebtoken::{Algorithm, DecodingKey, Validation, decode};
-use language_utils::{Language, TtsRequest, autograde, transcription_challenge};
+use language_utils::{
+    AsrRequest, AsrResponse, Language, MnemonicRequest, MnemonicResponse, TtsRequest, autograde,
+    transcription_challenge,
+};
 use serde::{Deserialize, Serialize};
 use std::sync::LazyLock;
 use tower_http::compression::CompressionLayer;
@@ -149,6 +154,22 @@ async fn text_to_speech(
     Ok(base64_audio)
 }
 
+async fn speech_to_text(
+    TypedHeader(auth): TypedHeader<Authorization<Bearer>>,
+    Json(request): Json<AsrRequest>,
+) -> Result<Json<AsrResponse>, StatusCode> {
+    // Verify JWT token
+    let _claims = verify_jwt(auth.token()).await?;
+
+    let audio = base64::engine::general_purpose::STANDARD
+        .decode(&request.audio)
+        .map_err(|_| StatusCode::BAD_REQUEST)?;
+
+    let transcript = asr::RECOGNIZER.transcribe(audio, request.language).await?;
+
+    Ok(Json(AsrResponse { transcript }))
+}
+
 async fn google_text_to_speech(
     TypedHeader(auth): TypedHeader<Authorization<Bearer>>,
     Json(request): Json<TtsRequest>,
@@ -268,6 +289,114 @@ The explanation should be written as if speaking directly to the user. Markdown
     Ok(Json(autograde_response))
 }
 
+async fn autograde_production(
+    TypedHeader(auth): TypedHeader<Authorization<Bearer>>,
+    Json(request): Json<autograde::AutoGradeProductionRequest>,
+) -> Result<Json<autograde::AutoGradeTranslationResponse>, StatusCode> {
+    // Verify JWT token
+    let _claims = verify_jwt(auth.token()).await?;
+
+    let autograde::AutoGradeProductionRequest {
+        challenge_sentence,
+        reference_sentence,
+        user_sentence,
+        primary_expression,
+        lexemes,
+        language,
+    } = request;
+
+    let language = match language {
+        Language::French => "French",
+        Language::Spanish => "Spanish",
+        Language::English => "English",
+        Language::Korean => "Korean",
+    };
+
+    let system_prompt = format!(
+        r#"The user is learning {language}. They were challenged to translate an English sentence into {language}. Your goal is to identify which {language} words or phrases they were able to produce, and which ones they forgot or got wrong. A reference translation is provided, but it is not the only correct answer: any natural {language} sentence with the same meaning is correct, even if it uses different words. If they translated the sentence correctly, that means they remembered everything! This will be used as part of a spaced-repetition system, which will help users study the words they need to. The system can only incorporate this for the words in the reference translation, which will be provided to you. Words are provided with additional context about their part of speech and lemmatised form. The 'primary expression' is also provided, which is the expression that the sentence most needed to test.
+
+Small mistakes like a missing accent or a typo still count as remembering a word, but mention them in the explanation. Using the wrong gender or conjugation of a word counts as remembering the word itself, as long as it was the right word. If the user used a different word that is also correct, count the corresponding reference word as remembered. Respond with JSON.
+
+Example Input: "English challenge sentence: I have things to do.
+Reference translation: J'ai à faire.
+User response: J'ai des choses à aller.
+Primary expression: faire
+Expressions: {{word: 'je', lemma: 'je', pos: 'PRON'}}, {{word: 'ai', lemma: 'avoir', pos: 'VERB'}}, {{word: 'à', lemma: 'à', pos: 'ADP'}}, {{word: 'faire', lemma: 'faire', pos: 'VERB'}}"
+
+Example output: {{
+"explanation": "'To do' is 'faire', not 'aller' (which means 'to go'). A correct translation is: 'J'ai des choses à faire.'"
+"primary_expression_status": "Forgot",
+"expressions_remembered": [{{"Heteronym": {{ "word": "je", "lemma": "je", "pos": "Pron" }}, {{"Heteronym": {{ "word": "ai", "lemma": "avoir", "pos": "Verb" }}, {{"Heteronym": {{ "word": "à", "lemma": "à", "pos": "Adp" }}}}],
+"expressions_forgot": [{{"Heteronym": {{ "word": "faire", "lemma": "faire", "pos": "Verb" }}}}],
+}}
+
+Only include expressions from the provided list in the expressions_remembered or expressions_forgot arrays.
+
+The explanation should be written as if speaking directly to the user. Markdown formatting is allowed. Try to keep the explanations short and concise. The user is still learning {language}, so respond in English!
+"#
+    );
+
+    let autograde_response: autograde::AutoGradeTranslationResponse = CLIENT.chat_with_system_prompt(
+        system_prompt,
+        &{
+            format!(
+            "English challenge sentence: {challenge_sentence}\nReference translation: {reference_sentence}\nUser response: {user_sentence}\nPrimary expression: {primary_expression}\nExpressions: {expressions}",
+            primary_expression = serde_json::to_value(&primary_expression).unwrap(),
+            expressions = serde_json::to_value(&lexemes).unwrap()
+        )}
+    )
+    .await
+    .inspect_err(|e| eprintln!("Error: {e:?}"))
+    .map_err(|_e| StatusCode::INTERNAL_SERVER_ERROR)?;
+    eprintln!("Response: {autograde_response:?}");
+
+    Ok(Json(autograde_response))
+}
+
+async fn mnemonic(
+    TypedHeader(auth): TypedHeader<Authorization<Bearer>>,
+    Json(request): Json<MnemonicRequest>,
+) -> Result<Json<MnemonicResponse>, StatusCode> {
+    // Verify JWT token
+    let _claims = verify_jwt(auth.token()).await?;
+
+    let MnemonicRequest {
+        language,
+        word,
+        meanings,
+    } = request;
+
+    let language = match language {
+        Language::French => "French",
+        Language::Spanish => "Spanish",
+        Language::English => "English",
+        Language::Korean => "Korean",
+    };
+
+    let system_prompt = format!(
+        r#"The user is learning {language}, and keeps forgetting a word. Write a short, vivid mnemonic to help them remember what it means. The best mnemonics link how the word sounds to an English word or phrase, and tie that to the meaning with a memorable image. Keep it to one or two sentences, and write it as if speaking directly to the user. Respond with JSON.
+
+Example Input: "Word: poubelle
+Meanings: bin, trash can"
+
+Example output: {{
+"mnemonic": "Picture a big-eared dog POOing on a BELL, then dumping it in the trash can: a poubelle."
+}}
+"#
+    );
+
+    let response: MnemonicResponse = CLIENT
+        .chat_with_system_prompt(
+            system_prompt,
+            &format!("Word: {word}\nMeanings: {}", meanings.join(", ")),
+        )
+        .await
+        .inspect_err(|e| eprintln!("Error: {e:?}"))
+        .map_err(|_e| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    Ok(Json(response))
+}
+
 async fn autograde_transcription(
     TypedHeader(auth): TypedHeader<Authorization<Bearer>>,
     Json(request): Json<autograde::AutoGradeTranscriptionRequest>,
@@ -507,8 +636,11 @@ async fn main() {
         .route("/", get(|| async { "Hello from fly.io!" }))
         .route("/tts", post(text_to_speech))
         .route("/tts/google", post(google_text_to_speech))
+        .route("/asr", post(speech_to_text))
         .route("/autograde-translation", post(autograde_translation))
+        .route("/autograde-production", post(autograde_production))
         .route("/autograde-transcription", post(autograde_transcription))
+        .route("/mnemonic", post(mnemonic))
         .route("/language-data/{language}", post(serve_language_data))
         .layer(CompressionLayer::new())
         .layer(cors);
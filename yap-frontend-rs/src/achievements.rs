@@ -0,0 +1,236 @@
+//! Achievements and levels. Like everything else in the deck these are derived from the event history as it's replayed,
+//! so they unlock at the same moment on every device and don't need to be stored anywhere.
+
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Utc};
+use lasso::Spur;
+use wasm_bindgen::prelude::*;
+
+use crate::{CardIndicator, Deck};
+
+/// Words need to be in this many of the most frequent words to count towards `TopThousandCoverage`.
+pub(crate) const TOP_WORDS: usize = 1000;
+
+#[derive(
+    Copy,
+    Clone,
+    Debug,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    tsify::Tsify,
+    serde::Serialize,
+    serde::Deserialize,
+)]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+pub enum Achievement {
+    /// Started learning 100 words.
+    FirstHundredWords,
+    ThousandReviews,
+    ThirtyDayStreak,
+    /// Know 90% of the 1000 most frequent words.
+    TopThousandCoverage,
+    FirstPerfectTranscription,
+}
+
+impl Achievement {
+    const ALL: [Achievement; 5] = [
+        Achievement::FirstHundredWords,
+        Achievement::ThousandReviews,
+        Achievement::ThirtyDayStreak,
+        Achievement::TopThousandCoverage,
+        Achievement::FirstPerfectTranscription,
+    ];
+}
+
+#[derive(Clone, Debug, tsify::Tsify, serde::Serialize, serde::Deserialize)]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+pub struct AchievementStatus {
+    pub achievement: Achievement,
+    /// When the event that unlocked the achievement happened, or `None` if it's still locked.
+    pub unlocked_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Clone, Debug, PartialEq, tsify::Tsify, serde::Serialize, serde::Deserialize)]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+pub struct LevelInfo {
+    pub level: u32,
+    pub xp: f64,
+    /// The total xp at which the current level started.
+    pub current_level_xp: f64,
+    /// The total xp needed to reach the next level.
+    pub next_level_xp: f64,
+}
+
+/// The total xp needed to reach `level`. Each level takes a little longer than the last.
+fn xp_for_level(level: u32) -> f64 {
+    let level = level.saturating_sub(1) as f64;
+    10.0 * level * level
+}
+
+fn level_info(xp: f64) -> LevelInfo {
+    let mut level = 1;
+    while xp_for_level(level + 1) <= xp {
+        level += 1;
+    }
+    LevelInfo {
+        level,
+        xp,
+        current_level_xp: xp_for_level(level),
+        next_level_xp: xp_for_level(level + 1),
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub(crate) struct AchievementTracker {
+    unlocked: BTreeMap<Achievement, DateTime<Utc>>,
+    reviews: u32,
+    /// Word cards that have left the new state.
+    words_known: u32,
+    /// The subset of `words_known` that are among the `TOP_WORDS` most frequent words.
+    top_words_known: u32,
+}
+
+impl AchievementTracker {
+    pub(crate) fn unlock(&mut self, achievement: Achievement, timestamp: DateTime<Utc>) {
+        self.unlocked.entry(achievement).or_insert(timestamp);
+    }
+
+    pub(crate) fn review(&mut self, timestamp: DateTime<Utc>) {
+        self.reviews += 1;
+        if self.reviews >= 1000 {
+            self.unlock(Achievement::ThousandReviews, timestamp);
+        }
+    }
+
+    pub(crate) fn word_known(&mut self, top_word: bool, timestamp: DateTime<Utc>) {
+        self.words_known += 1;
+        if top_word {
+            self.top_words_known += 1;
+        }
+        if self.words_known >= 100 {
+            self.unlock(Achievement::FirstHundredWords, timestamp);
+        }
+        if self.top_words_known as usize * 10 >= TOP_WORDS * 9 {
+            self.unlock(Achievement::TopThousandCoverage, timestamp);
+        }
+    }
+
+    /// A known word was removed from the deck. Achievements stay unlocked, but it has to be learned again to count.
+    pub(crate) fn word_removed(&mut self, top_word: bool) {
+        self.words_known = self.words_known.saturating_sub(1);
+        if top_word {
+            self.top_words_known = self.top_words_known.saturating_sub(1);
+        }
+    }
+
+    pub(crate) fn streak(&mut self, days: u32, timestamp: DateTime<Utc>) {
+        if days >= 30 {
+            self.unlock(Achievement::ThirtyDayStreak, timestamp);
+        }
+    }
+}
+
+impl Deck {
+    fn is_top_word(&self, card: &CardIndicator<Spur>) -> Option<bool> {
+        let lexeme = card.target_language()?;
+        Some(
+            self.language_pack
+                .word_frequencies
+                .get_index_of(lexeme)
+                .is_some_and(|rank| rank < TOP_WORDS),
+        )
+    }
+
+    /// Count `card` towards the vocabulary achievements, if it's a word.
+    pub(crate) fn track_word_known(
+        &mut self,
+        card: &CardIndicator<Spur>,
+        timestamp: DateTime<Utc>,
+    ) {
+        if let Some(top_word) = self.is_top_word(card) {
+            self.achievements.word_known(top_word, timestamp);
+        }
+    }
+
+    pub(crate) fn track_word_removed(&mut self, card: &CardIndicator<Spur>) {
+        if let Some(top_word) = self.is_top_word(card) {
+            self.achievements.word_removed(top_word);
+        }
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+impl Deck {
+    /// Every achievement, in a fixed order, with when it was unlocked.
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+    pub fn get_achievements(&self) -> Vec<AchievementStatus> {
+        Achievement::ALL
+            .into_iter()
+            .map(|achievement| AchievementStatus {
+                achievement,
+                unlocked_at: self.achievements.unlocked.get(&achievement).copied(),
+            })
+            .collect()
+    }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+    pub fn get_level(&self) -> LevelInfo {
+        level_info(self.xp)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Duration;
+
+    use super::Achievement;
+    use crate::LanguageEventContent;
+    use crate::tests::{apply, lexeme_card, test_deck};
+
+    #[test]
+    fn achievements_unlock_during_replay() {
+        let start = chrono::DateTime::parse_from_rfc3339("2025-01-01T12:00:00Z")
+            .unwrap()
+            .to_utc();
+        let mut events = vec![(
+            start,
+            LanguageEventContent::AddCards {
+                cards: vec![lexeme_card("le")],
+            },
+        )];
+        for day in 0..30 {
+            events.push((
+                start + Duration::days(day),
+                LanguageEventContent::ReviewCard {
+                    reviewed: lexeme_card("le"),
+                    rating: "good".to_string(),
+                },
+            ));
+        }
+        let deck = apply(test_deck(), &events);
+
+        let unlocked_at = |achievement| {
+            deck.get_achievements()
+                .into_iter()
+                .find(|status| status.achievement == achievement)
+                .unwrap()
+                .unlocked_at
+        };
+        assert_eq!(
+            unlocked_at(Achievement::ThirtyDayStreak),
+            Some(start + Duration::days(29))
+        );
+        assert_eq!(unlocked_at(Achievement::ThousandReviews), None);
+        assert_eq!(unlocked_at(Achievement::FirstHundredWords), None);
+
+        let level = deck.get_level();
+        assert!(level.current_level_xp <= level.xp && level.xp < level.next_level_xp);
+        assert_eq!(super::level_info(0.0).level, 1);
+        assert_eq!(super::level_info(10.0).level, 2);
+        assert_eq!(super::level_info(39.0).level, 2);
+        assert_eq!(super::level_info(40.0).level, 3);
+    }
+}
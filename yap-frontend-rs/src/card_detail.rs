@@ -0,0 +1,174 @@
+use chrono::{DateTime, Utc};
+use lasso::Spur;
+use rs_fsrs::Rating;
+use wasm_bindgen::prelude::*;
+
+use crate::{CardContent, CardIndicator, Deck};
+
+/// Which kind of challenge a review came from.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, tsify::Tsify, serde::Serialize, serde::Deserialize)]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+pub enum ReviewSource {
+    Flashcard,
+    TranslationChallenge,
+    TranscriptionChallenge,
+    /// Translating a sentence into the target language.
+    ProductionChallenge,
+    /// Filling in a word missing from a sentence.
+    ClozeChallenge,
+    MultipleChoice,
+    /// Reading a sentence aloud.
+    SpeakingChallenge,
+    /// Not a real review: the user said they already knew the card.
+    MarkedKnown,
+    /// Reviewed in a cram session, which leaves the card's schedule alone.
+    Cram,
+}
+
+/// A single review of a card, as recorded while replaying the event history.
+/// One of these is kept for every review of every card, so it's stored compactly.
+#[derive(Clone, Debug)]
+pub(crate) struct ReviewRecord {
+    pub(crate) timestamp: DateTime<Utc>,
+    pub(crate) rating: Rating,
+    pub(crate) source: ReviewSource,
+    /// The sentence the card was reviewed in, if it was reviewed as part of a sentence challenge.
+    pub(crate) sentence: Option<Spur>,
+    pub(crate) stability_before: f32,
+    pub(crate) difficulty_before: f32,
+    pub(crate) stability_after: f32,
+    pub(crate) difficulty_after: f32,
+    pub(crate) state_after: rs_fsrs::State,
+}
+
+impl ReviewRecord {
+    fn resolve(&self, rodeo: &lasso::RodeoReader) -> CardReview {
+        CardReview {
+            timestamp: self.timestamp,
+            rating: match self.rating {
+                Rating::Again => "again",
+                Rating::Hard => "hard",
+                Rating::Good => "good",
+                Rating::Easy => "easy",
+            }
+            .to_string(),
+            source: self.source,
+            sentence: self
+                .sentence
+                .map(|sentence| rodeo.resolve(&sentence).to_string()),
+            stability_before: self.stability_before as f64,
+            difficulty_before: self.difficulty_before as f64,
+            stability_after: self.stability_after as f64,
+            difficulty_after: self.difficulty_after as f64,
+        }
+    }
+}
+
+#[derive(Clone, Debug, tsify::Tsify, serde::Serialize, serde::Deserialize)]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+pub struct CardReview {
+    pub timestamp: DateTime<Utc>,
+    pub rating: String,
+    pub source: ReviewSource,
+    pub sentence: Option<String>,
+    pub stability_before: f64,
+    pub difficulty_before: f64,
+    pub stability_after: f64,
+    pub difficulty_after: f64,
+}
+
+#[derive(Clone, Debug, tsify::Tsify, serde::Serialize, serde::Deserialize)]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+pub struct CardDetail {
+    pub indicator: CardIndicator<String>,
+    pub content: CardContent<String>,
+    pub state: String,
+    pub due_timestamp_ms: f64,
+    pub stability: f64,
+    pub difficulty: f64,
+    pub lapses: i32,
+    /// Oldest first.
+    pub reviews: Vec<CardReview>,
+    /// Reviews from cram sessions, which don't affect scheduling. Oldest first.
+    pub cram_reviews: Vec<CardReview>,
+}
+
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+impl Deck {
+    /// Everything we know about a card: its dictionary content, where it is in its schedule, and how it got there.
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+    pub fn get_card_detail(&self, indicator: CardIndicator<String>) -> Option<CardDetail> {
+        let rodeo = &self.language_pack.rodeo;
+        let index = self.cards.get_index_of(&indicator.get_interned(rodeo)?)?;
+        let (indicator, card) = self.get_card(index)?;
+        let (_, card_data) = self.cards.get_index(index)?;
+
+        Some(CardDetail {
+            indicator: indicator.resolve(rodeo),
+            content: card.content.resolve(rodeo),
+            state: crate::state_name(card.fsrs_card.state).to_string(),
+            due_timestamp_ms: card_data.due_timestamp_ms(),
+            stability: card.fsrs_card.stability,
+            difficulty: card.fsrs_card.difficulty,
+            lapses: card.fsrs_card.lapses,
+            reviews: card_data
+                .reviews
+                .iter()
+                .map(|review| review.resolve(rodeo))
+                .collect(),
+            cram_reviews: card_data
+                .cram_reviews
+                .iter()
+                .map(|review| review.resolve(rodeo))
+                .collect(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ReviewSource;
+    use crate::LanguageEventContent;
+    use crate::tests::{apply, lexeme_card, test_deck};
+
+    #[test]
+    fn card_detail_includes_sentence_reviews() {
+        let start = chrono::DateTime::parse_from_rfc3339("2025-01-01T12:00:00Z")
+            .unwrap()
+            .to_utc();
+        let cards = ["le", "chat", "mange"].map(lexeme_card).to_vec();
+        let mut events = vec![(start, LanguageEventContent::AddCards { cards })];
+        for card in ["le", "chat", "mange"] {
+            events.push((
+                start,
+                LanguageEventContent::ReviewCard {
+                    reviewed: lexeme_card(card),
+                    rating: "easy".to_string(),
+                },
+            ));
+        }
+        let later = start + chrono::Duration::days(20);
+        let deck = apply(test_deck(), &events);
+        let sentence = "Le chat mange.".to_string();
+        let event = deck.translate_sentence_perfect(sentence.clone()).unwrap();
+        let crate::DeckEvent::Language(event) = event;
+        let deck = apply(deck, &[(later, event.content)]);
+
+        let detail = deck.get_card_detail(lexeme_card("chat")).unwrap();
+        assert_eq!(detail.state, "review");
+        assert_eq!(detail.reviews.len(), 2);
+        let [flashcard, translation] = &detail.reviews[..] else {
+            unreachable!()
+        };
+        assert_eq!(flashcard.source, ReviewSource::Flashcard);
+        assert_eq!(flashcard.rating, "easy");
+        assert_eq!(flashcard.sentence, None);
+        assert_eq!(translation.source, ReviewSource::TranslationChallenge);
+        assert_eq!(translation.sentence, Some(sentence));
+        assert_eq!(translation.timestamp, later);
+        assert_eq!(translation.stability_before, flashcard.stability_after);
+        assert!(translation.stability_after > translation.stability_before);
+
+        assert!(deck.get_card_detail(lexeme_card("chien")).is_none());
+    }
+}
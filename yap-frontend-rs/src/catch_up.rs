@@ -0,0 +1,233 @@
+//! Catch-up mode, for coming back after a long break (see `CatchUpSettings`). Sorting hundreds of overdue cards by
+//! due date would spend the first sessions on the cards that were forgotten longest ago. Instead the cards still most
+//! likely to be remembered and the most common words go first, since those lose the most by waiting another day, and
+//! only so many are shown each day.
+
+use std::cmp::Reverse;
+
+use chrono::{DateTime, Utc};
+use language_utils::Lexeme;
+use lasso::Spur;
+use ordered_float::NotNan;
+#[cfg(target_arch = "wasm32")]
+use wasm_bindgen::prelude::*;
+
+use crate::deck_settings::{
+    CatchUpSettings, DeckSettingsEvent, LanguageSettingsEvent, LanguageSettingsEventContent,
+    WorkloadSettings,
+};
+use crate::{CardIndicator, Deck};
+
+/// How far along catching up is.
+#[derive(tsify::Tsify, serde::Serialize, serde::Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+pub struct CatchUp {
+    /// Overdue reviews left, including today's.
+    pub backlog: u32,
+    /// How many of them are shown today.
+    pub reviews_today: u32,
+    /// How many days, counting today, it'll take to clear the backlog at this pace.
+    pub days_to_clear: u32,
+}
+
+impl Deck {
+    /// The card's word's position in the frequency list, or for a listening card, that of the most common word it
+    /// could be.
+    pub(crate) fn frequency_rank(&self, card: &CardIndicator<Spur>) -> Option<usize> {
+        let language_pack = &self.language_pack;
+        match card {
+            CardIndicator::TargetLanguage { lexeme } | CardIndicator::NativeToTarget { lexeme } => {
+                language_pack.word_frequencies.get_index_of(lexeme)
+            }
+            CardIndicator::ListeningHomophonous { pronunciation } => language_pack
+                .pronunciation_to_words
+                .get(pronunciation)
+                .into_iter()
+                .flatten()
+                .flat_map(|word| {
+                    language_pack
+                        .words_to_heteronyms
+                        .get(word)
+                        .into_iter()
+                        .flatten()
+                })
+                .filter_map(|heteronym| {
+                    language_pack
+                        .word_frequencies
+                        .get_index_of(&Lexeme::Heteronym(*heteronym))
+                })
+                .min(),
+        }
+    }
+
+    /// How much reviewing the card at `card_index` today is worth: its retrievability, weighted by how common its word
+    /// is.
+    fn catch_up_priority(&self, card_index: usize, now: DateTime<Utc>) -> f64 {
+        let (card, card_data) = self.cards.get_index(card_index).unwrap();
+        let rank = self
+            .frequency_rank(card)
+            .unwrap_or(self.language_pack.word_frequencies.len());
+        let importance = 1.0 / (rank as f64 + std::f64::consts::E).ln();
+        card_data.fsrs_card.get_retrievability(now) * importance
+    }
+
+    /// If there are enough overdue reviews in `due_cards` (sorted by due date) to catch up on, put them in catch-up
+    /// order ahead of any new cards and say how far along catching up is. Otherwise leave `due_cards` alone.
+    pub(crate) fn catch_up(&self, due_cards: &mut [usize], now: DateTime<Utc>) -> Option<CatchUp> {
+        let settings = self.settings.catch_up;
+        let is_review = |card_index: &usize| {
+            let (_, card_data) = self.cards.get_index(*card_index).unwrap();
+            card_data.fsrs_card.state == rs_fsrs::State::Review
+        };
+        let backlog = due_cards.iter().filter(|index| is_review(index)).count() as u32;
+        if !settings.enabled || backlog == 0 || backlog < settings.min_backlog {
+            return None;
+        }
+
+        // Stable, so learning steps and new cards stay in due date order after the reviews
+        due_cards.sort_by_cached_key(|card_index| {
+            let priority = is_review(card_index).then(|| self.catch_up_priority(*card_index, now));
+            Reverse(priority.and_then(|priority| NotNan::new(priority).ok()))
+        });
+
+        let reviews_per_day = self.catch_up_reviews_per_day();
+        let (_, reviews_remaining) = self.daily_counts.remaining(
+            &WorkloadSettings {
+                reviews_per_day,
+                ..self.settings.workload.clone()
+            },
+            self.settings.day.day_of(now),
+        );
+        let reviews_today = reviews_remaining.min(backlog);
+        Some(CatchUp {
+            backlog,
+            reviews_today,
+            days_to_clear: u32::from(reviews_today > 0)
+                + (backlog - reviews_today).div_ceil(reviews_per_day.max(1)),
+        })
+    }
+
+    /// The daily review limit while catching up.
+    pub(crate) fn catch_up_reviews_per_day(&self) -> u32 {
+        self.settings
+            .workload
+            .reviews_per_day
+            .min(self.settings.catch_up.reviews_per_day)
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+impl Deck {
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+    pub fn catch_up_enabled(&self) -> bool {
+        self.settings.catch_up.enabled
+    }
+
+    /// A settings event turning catch-up mode on or off, from now on.
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+    pub fn set_catch_up(&self, enabled: bool) -> DeckSettingsEvent {
+        DeckSettingsEvent::Language(LanguageSettingsEvent {
+            language: self.target_language,
+            content: LanguageSettingsEventContent::SetCatchUpSettings(CatchUpSettings {
+                enabled,
+                ..self.settings.catch_up
+            }),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{DateTime, Utc};
+
+    use super::CatchUp;
+    use crate::LanguageEventContent;
+    use crate::deck_settings::{CatchUpSettings, LanguageSettingsEventContent};
+    use crate::tests::{apply, lexeme_card, test_deck, test_deck_with_settings};
+
+    #[test]
+    fn catch_up_after_a_break() {
+        let words = ["mes", "chien", "le", "mais", "chat", "mange"];
+        let long_ago = chrono::Utc::now() - chrono::Duration::days(60);
+        let mut events = vec![(
+            long_ago,
+            LanguageEventContent::AddCards {
+                cards: words.map(lexeme_card).to_vec(),
+            },
+        )];
+        for word in words {
+            events.push((
+                long_ago,
+                LanguageEventContent::ReviewCard {
+                    reviewed: lexeme_card(word),
+                    rating: "good".to_string(),
+                },
+            ));
+        }
+        // Graduate them all, so they're overdue reviews rather than cards still being learned
+        let a_day_later = long_ago + chrono::Duration::days(1);
+        for word in words {
+            events.push((
+                a_day_later,
+                LanguageEventContent::ReviewCard {
+                    reviewed: lexeme_card(word),
+                    rating: "good".to_string(),
+                },
+            ));
+        }
+        // Off unless turned on
+        let review_info = apply(test_deck(), &events).get_review_info(vec![]);
+        assert_eq!(review_info.catch_up(), None);
+        assert_eq!(review_info.due_count(), 6);
+
+        let set_min_backlog = |timestamp: DateTime<Utc>, min_backlog: u32| {
+            (
+                timestamp,
+                LanguageSettingsEventContent::SetCatchUpSettings(CatchUpSettings {
+                    enabled: true,
+                    min_backlog,
+                    reviews_per_day: 2,
+                }),
+            )
+        };
+        let deck = apply(
+            test_deck_with_settings(&[set_min_backlog(long_ago, 3)]),
+            &events,
+        );
+
+        let review_info = deck.get_review_info(vec![]);
+        assert_eq!(
+            review_info.catch_up(),
+            Some(CatchUp {
+                backlog: 6,
+                reviews_today: 2,
+                days_to_clear: 3,
+            })
+        );
+        assert_eq!(review_info.due_count(), 2);
+        assert_eq!(review_info.due_over_limit_count(), 4);
+        // All equally likely to be remembered, so the most common words go first
+        let due: Vec<_> = review_info
+            .due_cards
+            .iter()
+            .map(|index| {
+                let (card, _) = deck.cards.get_index(*index).unwrap();
+                card.resolve(&deck.language_pack.rodeo)
+            })
+            .collect();
+        assert_eq!(due, [lexeme_card("le"), lexeme_card("chat")]);
+
+        // Below the threshold, reviews are shown as usual
+        let mut deck = apply(
+            test_deck_with_settings(&[
+                set_min_backlog(long_ago, 3),
+                set_min_backlog(a_day_later + chrono::Duration::days(1), 7),
+            ]),
+            &events,
+        );
+        deck.apply_settings_until(DateTime::<Utc>::MAX_UTC);
+        let review_info = deck.get_review_info(vec![]);
+        assert_eq!(review_info.catch_up(), None);
+        assert_eq!(review_info.due_count(), 6);
+    }
+}
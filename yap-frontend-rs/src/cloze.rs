@@ -0,0 +1,320 @@
+//! Cloze deletion: the user sees a sentence with a word blanked out, along with its translation, and types the
+//! missing word. Grading is done locally, so these work offline.
+
+use chrono::{DateTime, Utc};
+use language_utils::{Heteronym, Literal, TtsProvider, TtsRequest};
+use lasso::Spur;
+use rs_fsrs::Rating;
+use wasm_bindgen::prelude::*;
+
+use crate::card_detail::ReviewSource;
+use crate::{
+    AudioRequest, CardIndicator, ComprehensibleSentence, Deck, DeckEvent, LanguageEvent,
+    LanguageEventContent, LanguagePack,
+};
+
+#[derive(tsify::Tsify, serde::Serialize, serde::Deserialize, Debug, Clone)]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+pub enum ClozePart<S> {
+    Provided {
+        part: Literal<S>,
+    },
+    /// The user has to type `answer`.
+    Blank {
+        answer: Literal<S>,
+    },
+}
+
+#[derive(tsify::Tsify, serde::Serialize, serde::Deserialize, Debug, Clone)]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+pub struct ClozeComprehensibleSentence<S> {
+    pub(crate) audio: AudioRequest,
+    pub(crate) target_language: S,
+    native_translation: S,
+    /// The word that was blanked out. Every occurrence of it in the sentence is a blank.
+    pub(crate) heteronym: Heteronym<S>,
+    pub(crate) parts: Vec<ClozePart<S>>,
+}
+
+impl ClozeComprehensibleSentence<Spur> {
+    pub(crate) fn resolve(
+        &self,
+        rodeo: &lasso::RodeoReader,
+    ) -> ClozeComprehensibleSentence<String> {
+        ClozeComprehensibleSentence {
+            audio: self.audio.clone(),
+            target_language: rodeo.resolve(&self.target_language).to_string(),
+            native_translation: rodeo.resolve(&self.native_translation).to_string(),
+            heteronym: self.heteronym.resolve(rodeo),
+            parts: self
+                .parts
+                .iter()
+                .map(|part| match part {
+                    ClozePart::Provided { part } => ClozePart::Provided {
+                        part: part.resolve(rodeo),
+                    },
+                    ClozePart::Blank { answer } => ClozePart::Blank {
+                        answer: answer.resolve(rodeo),
+                    },
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Ordered from best to worst.
+#[derive(
+    Clone, Debug, PartialEq, Eq, PartialOrd, Ord, tsify::Tsify, serde::Serialize, serde::Deserialize,
+)]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+pub enum BlankGrade {
+    Perfect {},
+    /// Right apart from accents.
+    WrongAccents {},
+    /// Another form of the same word, such as the wrong conjugation.
+    WrongForm {},
+    Incorrect {},
+}
+
+impl BlankGrade {
+    fn rating(&self) -> Rating {
+        match self {
+            BlankGrade::Perfect {} | BlankGrade::WrongAccents {} => Rating::Good,
+            BlankGrade::WrongForm {} => Rating::Hard,
+            BlankGrade::Incorrect {} => Rating::Again,
+        }
+    }
+}
+
+fn grade_blank(
+    answer: &Literal<String>,
+    submission: &str,
+    language_pack: &LanguagePack,
+) -> BlankGrade {
+    let answer_text = answer.text.trim().to_lowercase();
+    let submission = submission.trim().to_lowercase();
+    if submission == answer_text {
+        return BlankGrade::Perfect {};
+    }
+    if crate::remove_accents(&submission) == crate::remove_accents(&answer_text) {
+        return BlankGrade::WrongAccents {};
+    }
+
+    let rodeo = &language_pack.rodeo;
+    let same_lemma = answer
+        .heteronym
+        .as_ref()
+        .and_then(|heteronym| heteronym.get_interned(rodeo))
+        .zip(rodeo.get(&submission))
+        .and_then(|(answer, submission)| {
+            let heteronyms = language_pack.words_to_heteronyms.get(&submission)?;
+            Some(
+                heteronyms
+                    .iter()
+                    .any(|heteronym| heteronym.lemma == answer.lemma),
+            )
+        })
+        .unwrap_or(false);
+    if same_lemma {
+        BlankGrade::WrongForm {}
+    } else {
+        BlankGrade::Incorrect {}
+    }
+}
+
+impl Deck {
+    pub(crate) fn cloze_challenge(
+        &self,
+        heteronym: Heteronym<Spur>,
+        sentence: &ComprehensibleSentence,
+    ) -> ClozeComprehensibleSentence<Spur> {
+        let parts = sentence
+            .target_language_literals
+            .iter()
+            .cloned()
+            .map(|literal| {
+                if literal.heteronym == Some(heteronym) {
+                    ClozePart::Blank { answer: literal }
+                } else {
+                    ClozePart::Provided { part: literal }
+                }
+            })
+            .collect();
+        ClozeComprehensibleSentence {
+            audio: AudioRequest {
+                request: TtsRequest {
+                    text: self
+                        .language_pack
+                        .rodeo
+                        .resolve(&sentence.target_language)
+                        .to_string(),
+                    language: self.target_language,
+                },
+                provider: TtsProvider::ElevenLabs,
+            },
+            target_language: sentence.target_language,
+            native_translation: *sentence.native_languages.first().unwrap(),
+            heteronym,
+            parts,
+        }
+    }
+
+    pub(crate) fn apply_cloze_challenge(
+        &mut self,
+        challenge_sentence: &str,
+        heteronym: &Heteronym<String>,
+        grades: &[BlankGrade],
+        timestamp: DateTime<Utc>,
+    ) {
+        let rodeo = &self.language_pack.rodeo;
+        let Some(heteronym) = heteronym.get_interned(rodeo) else {
+            return;
+        };
+        let challenge_sentence = rodeo.get(challenge_sentence);
+        // The card is only as good as the worst blank
+        let Some(rating) = grades.iter().max().map(BlankGrade::rating) else {
+            return;
+        };
+        if rating != Rating::Again
+            && let Some(challenge_sentence) = challenge_sentence
+        {
+            *self
+                .sentences_reviewed
+                .entry(challenge_sentence)
+                .or_insert(0) += 1;
+        }
+        self.log_review(
+            CardIndicator::TargetLanguage {
+                lexeme: language_utils::Lexeme::Heteronym(heteronym),
+            },
+            rating,
+            timestamp,
+            ReviewSource::ClozeChallenge,
+            challenge_sentence,
+        );
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+impl Deck {
+    /// Grade each blank in `challenge` against what the user typed for it.
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+    pub fn grade_cloze(
+        &self,
+        challenge: ClozeComprehensibleSentence<String>,
+        submissions: Vec<String>,
+    ) -> Vec<BlankGrade> {
+        let mut submissions = submissions.into_iter();
+        challenge
+            .parts
+            .iter()
+            .filter_map(|part| match part {
+                ClozePart::Blank { answer } => Some(grade_blank(
+                    answer,
+                    &submissions.next().unwrap_or_default(),
+                    &self.language_pack,
+                )),
+                ClozePart::Provided { .. } => None,
+            })
+            .collect()
+    }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+    pub fn cloze_sentence(
+        &self,
+        challenge: ClozeComprehensibleSentence<String>,
+        grades: Vec<BlankGrade>,
+    ) -> Option<DeckEvent> {
+        Some(DeckEvent::Language(LanguageEvent {
+            language: self.target_language,
+            content: LanguageEventContent::ClozeChallenge {
+                challenge_sentence: challenge.target_language,
+                heteronym: challenge.heteronym,
+                grades,
+            },
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use language_utils::Lexeme;
+
+    use super::{BlankGrade, ClozePart};
+    use crate::card_detail::ReviewSource;
+    use crate::tests::{apply, lexeme_card, test_deck};
+    use crate::{CardIndicator, LanguageEventContent};
+
+    #[test]
+    fn cloze_challenges_are_graded_locally() {
+        let start = chrono::DateTime::parse_from_rfc3339("2025-01-01T12:00:00Z")
+            .unwrap()
+            .to_utc();
+        let words = ["le", "chat", "mange"];
+        let mut events = vec![(
+            start,
+            LanguageEventContent::AddCards {
+                cards: words.map(lexeme_card).to_vec(),
+            },
+        )];
+        for word in words {
+            events.push((
+                start,
+                LanguageEventContent::ReviewCard {
+                    reviewed: lexeme_card(word),
+                    rating: "easy".to_string(),
+                },
+            ));
+        }
+        let deck = apply(test_deck(), &events);
+
+        let rodeo = &deck.language_pack.rodeo;
+        let CardIndicator::TargetLanguage {
+            lexeme: Lexeme::Heteronym(mange),
+        } = lexeme_card("mange").get_interned(rodeo).unwrap()
+        else {
+            unreachable!()
+        };
+        let sentence = deck
+            .get_comprehensible_sentence_containing(
+                &Lexeme::Heteronym(mange),
+                &deck.sentences_reviewed,
+                &deck.language_pack,
+                chrono::Utc::now(),
+            )
+            .unwrap();
+        let challenge = deck.cloze_challenge(mange, &sentence).resolve(rodeo);
+        assert_eq!(challenge.target_language, "Le chat mange.");
+        assert_eq!(challenge.native_translation, "The cat eats.");
+        let blanks = challenge
+            .parts
+            .iter()
+            .filter(|part| matches!(part, ClozePart::Blank { .. }))
+            .count();
+        assert_eq!(blanks, 1);
+
+        let grade =
+            |submission: &str| deck.grade_cloze(challenge.clone(), vec![submission.to_string()]);
+        assert_eq!(grade("Mange"), vec![BlankGrade::Perfect {}]);
+        assert_eq!(grade("mangé"), vec![BlankGrade::WrongAccents {}]);
+        assert_eq!(grade("chat"), vec![BlankGrade::Incorrect {}]);
+        // The right verb, but the wrong form of it
+        assert_eq!(grade("manges"), vec![BlankGrade::WrongForm {}]);
+
+        let review_after = |grades: Vec<BlankGrade>| {
+            let crate::DeckEvent::Language(event) =
+                deck.cloze_sentence(challenge.clone(), grades).unwrap();
+            let deck = apply(
+                deck.clone(),
+                &[(start + chrono::Duration::days(3), event.content)],
+            );
+            let detail = deck.get_card_detail(lexeme_card("mange")).unwrap();
+            detail.reviews.last().unwrap().clone()
+        };
+        let review = review_after(grade("chat"));
+        assert_eq!(review.source, ReviewSource::ClozeChallenge);
+        assert_eq!(review.rating, "again");
+        assert_eq!(review.sentence.as_deref(), Some("Le chat mange."));
+        assert_eq!(review_after(grade("manges")).rating, "hard");
+    }
+}
@@ -0,0 +1,267 @@
+//! Custom study: reviewing a hand-picked slice of the deck, like "all verbs", "words I failed this week" or
+//! "everything before my trip". The cards can be reviewed as usual with `Deck::review_card`, or crammed with
+//! `Deck::cram_card`, which records the review without moving the card's schedule.
+
+use chrono::{DateTime, Utc};
+use language_utils::{Lexeme, PartOfSpeech};
+use lasso::Spur;
+use rs_fsrs::Rating;
+#[cfg(target_arch = "wasm32")]
+use wasm_bindgen::prelude::*;
+
+use crate::card_detail::{ReviewRecord, ReviewSource};
+use crate::{
+    CardData, CardIndicator, Challenge, Deck, LanguageEventContent, ReviewInfo, state_name,
+};
+
+/// Which cards to study. Every filter that's set has to match; an empty filter matches every card that isn't
+/// suspended.
+#[derive(Clone, Debug, Default, serde::Serialize, serde::Deserialize, tsify::Tsify)]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+pub struct CustomStudyFilter {
+    /// Only words that are one of these parts of speech. Listening and multiword cards never match.
+    #[serde(default)]
+    pub parts_of_speech: Vec<PartOfSpeech>,
+    /// Only cards in one of these FSRS states: "new", "learning", "review" or "relearning".
+    #[serde(default)]
+    pub states: Vec<String>,
+    #[serde(default)]
+    #[tsify(optional)]
+    pub min_lapses: Option<u32>,
+    /// Only cards that have been failed since then.
+    #[serde(default)]
+    #[tsify(optional, type = "string")]
+    pub failed_since: Option<DateTime<Utc>>,
+    #[serde(default)]
+    #[tsify(optional, type = "string")]
+    pub added_after: Option<DateTime<Utc>>,
+    #[serde(default)]
+    #[tsify(optional, type = "string")]
+    pub added_before: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub multiword_only: bool,
+}
+
+impl CustomStudyFilter {
+    fn matches(&self, indicator: &CardIndicator<Spur>, card_data: &CardData) -> bool {
+        let lexeme = match indicator {
+            CardIndicator::TargetLanguage { lexeme } | CardIndicator::NativeToTarget { lexeme } => {
+                Some(lexeme)
+            }
+            CardIndicator::ListeningHomophonous { .. } => None,
+        };
+        if !self.parts_of_speech.is_empty()
+            && !lexeme
+                .and_then(Lexeme::heteronym)
+                .is_some_and(|heteronym| self.parts_of_speech.contains(&heteronym.pos))
+        {
+            return false;
+        }
+        if self.multiword_only && lexeme.and_then(Lexeme::multiword).is_none() {
+            return false;
+        }
+        if !self.states.is_empty()
+            && !self
+                .states
+                .iter()
+                .any(|state| state == state_name(card_data.fsrs_card.state))
+        {
+            return false;
+        }
+        if let Some(min_lapses) = self.min_lapses
+            && (card_data.fsrs_card.lapses.max(0) as u32) < min_lapses
+        {
+            return false;
+        }
+        if let Some(failed_since) = self.failed_since
+            && !card_data
+                .reviews
+                .iter()
+                .any(|review| review.timestamp >= failed_since && review.rating == Rating::Again)
+        {
+            return false;
+        }
+        self.added_after
+            .is_none_or(|added_after| card_data.added_at >= added_after)
+            && self
+                .added_before
+                .is_none_or(|added_before| card_data.added_at < added_before)
+    }
+}
+
+impl Deck {
+    /// Record a cram review. It goes in the card's history, but the card's memory state, due date and the daily
+    /// review counts are left as they were.
+    pub(crate) fn log_cram_review(
+        &mut self,
+        card: &CardIndicator<Spur>,
+        rating: Rating,
+        timestamp: DateTime<Utc>,
+    ) {
+        let Some(card_data) = self.cards.get_mut(card) else {
+            return;
+        };
+        let fsrs_card = &card_data.fsrs_card;
+        card_data.cram_reviews.push(ReviewRecord {
+            timestamp,
+            rating,
+            source: ReviewSource::Cram,
+            sentence: None,
+            stability_before: fsrs_card.stability as f32,
+            difficulty_before: fsrs_card.difficulty as f32,
+            stability_after: fsrs_card.stability as f32,
+            difficulty_after: fsrs_card.difficulty as f32,
+            state_after: fsrs_card.state,
+        });
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+impl Deck {
+    /// The cards matching `filter`, soonest due first. Suspended cards are left out.
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+    pub fn get_custom_study_cards(&self, filter: CustomStudyFilter) -> Vec<CardIndicator<String>> {
+        let mut cards: Vec<_> = self
+            .cards
+            .iter()
+            .filter(|(indicator, card_data)| {
+                !card_data.suspended && filter.matches(indicator, card_data)
+            })
+            .collect();
+        cards.sort_by_key(|(_, card_data)| card_data.fsrs_card.due);
+        cards
+            .into_iter()
+            .map(|(indicator, _)| indicator.resolve(&self.language_pack.rodeo))
+            .collect()
+    }
+
+    /// A flashcard for studying `card`, even if it isn't due or is buried.
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+    pub fn get_custom_study_challenge(
+        &self,
+        card: CardIndicator<String>,
+    ) -> Option<Challenge<String>> {
+        let rodeo = &self.language_pack.rodeo;
+        let index = self.cards.get_index_of(&card.get_interned(rodeo)?)?;
+        Some(ReviewInfo::get_flashcard(self, index)?.resolve(rodeo))
+    }
+
+    /// Like `review_card`, but for a cram session: the card is next due when it was going to be anyway.
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+    pub fn cram_card(
+        &self,
+        reviewed: CardIndicator<String>,
+        rating: String,
+    ) -> Option<crate::DeckEvent> {
+        self.card_event(reviewed, |reviewed| LanguageEventContent::CramCard {
+            reviewed,
+            rating,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use language_utils::PartOfSpeech;
+
+    use super::CustomStudyFilter;
+    use crate::tests::{apply, lexeme_card, test_deck};
+    use crate::{CardIndicator, Challenge, LanguageEventContent};
+
+    #[test]
+    fn custom_study() {
+        let start = chrono::DateTime::parse_from_rfc3339("2025-01-01T12:00:00Z")
+            .unwrap()
+            .to_utc();
+        let week_later = start + chrono::Duration::days(7);
+        let multiword = CardIndicator::TargetLanguage {
+            lexeme: language_utils::Lexeme::Multiword("il y a".to_string()),
+        };
+        let review = |word: &str, rating: &str| LanguageEventContent::ReviewCard {
+            reviewed: lexeme_card(word),
+            rating: rating.to_string(),
+        };
+        let deck = apply(
+            test_deck(),
+            &[
+                (
+                    start,
+                    LanguageEventContent::AddCards {
+                        cards: vec![lexeme_card("chat"), lexeme_card("mange"), multiword.clone()],
+                    },
+                ),
+                (start, review("chat", "good")),
+                (start, review("mange", "good")),
+                (
+                    week_later,
+                    LanguageEventContent::AddCards {
+                        cards: vec![lexeme_card("chien")],
+                    },
+                ),
+                (week_later, review("chat", "again")),
+            ],
+        );
+
+        let cards = |filter: CustomStudyFilter| deck.get_custom_study_cards(filter);
+        assert_eq!(
+            cards(CustomStudyFilter {
+                parts_of_speech: vec![PartOfSpeech::Noun],
+                ..Default::default()
+            }),
+            [lexeme_card("chat"), lexeme_card("chien")]
+        );
+        assert_eq!(
+            cards(CustomStudyFilter {
+                states: vec!["new".to_string()],
+                ..Default::default()
+            }),
+            [multiword.clone(), lexeme_card("chien")]
+        );
+        assert_eq!(
+            cards(CustomStudyFilter {
+                multiword_only: true,
+                ..Default::default()
+            }),
+            [multiword]
+        );
+        assert_eq!(
+            cards(CustomStudyFilter {
+                failed_since: Some(week_later),
+                ..Default::default()
+            }),
+            [lexeme_card("chat")]
+        );
+        assert_eq!(
+            cards(CustomStudyFilter {
+                added_before: Some(week_later),
+                parts_of_speech: vec![PartOfSpeech::Noun, PartOfSpeech::Verb],
+                ..Default::default()
+            }),
+            [lexeme_card("mange"), lexeme_card("chat")]
+        );
+
+        // Cramming is recorded, but doesn't move the card
+        assert!(matches!(
+            deck.get_custom_study_challenge(lexeme_card("mange")),
+            Some(Challenge::FlashCardReview { .. })
+        ));
+        let before = deck.get_card_detail(lexeme_card("mange")).unwrap();
+        let crammed = apply(
+            deck,
+            &[(
+                start + chrono::Duration::hours(1),
+                LanguageEventContent::CramCard {
+                    reviewed: lexeme_card("mange"),
+                    rating: "again".to_string(),
+                },
+            )],
+        );
+        let after = crammed.get_card_detail(lexeme_card("mange")).unwrap();
+        assert_eq!(after.due_timestamp_ms, before.due_timestamp_ms);
+        assert_eq!(after.stability, before.stability);
+        assert_eq!(after.lapses, before.lapses);
+        assert_eq!(after.reviews.len(), before.reviews.len());
+        assert_eq!(after.cram_reviews.len(), 1);
+        assert_eq!(after.cram_reviews[0].rating, "again");
+    }
+}
@@ -0,0 +1,498 @@
+use std::collections::{BTreeMap, VecDeque};
+
+use chrono::{DateTime, NaiveDate, Utc};
+use language_utils::Language;
+use ordered_float::NotNan;
+use weapon::data_model::{Event, EventType, Timestamped};
+
+/// Per-language settings, built up from the `settings` event stream.
+#[derive(Clone, Debug, Default, tsify::Tsify, serde::Serialize, serde::Deserialize)]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+pub struct DeckSettings {
+    pub languages: BTreeMap<Language, LanguageSettings>,
+}
+
+impl DeckSettings {
+    pub fn get(&self, language: Language) -> LanguageSettings {
+        self.languages.get(&language).cloned().unwrap_or_default()
+    }
+}
+
+impl weapon::AppState for DeckSettings {
+    type Event = DeckSettingsEvent;
+
+    fn apply_event(mut self, event: &Timestamped<Self::Event>) -> Self {
+        let DeckSettingsEvent::Language(LanguageSettingsEvent { language, content }) = &event.event;
+        self.languages.entry(*language).or_default().apply(content);
+        self
+    }
+}
+
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize, PartialEq, Eq, tsify::Tsify)]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+pub struct LanguageSettings {
+    pub fsrs: FsrsSettings,
+    /// The stability, in days, given to cards the user marks as already known.
+    #[tsify(type = "number")]
+    pub known_card_stability: NotNan<f64>,
+    pub workload: WorkloadSettings,
+    pub day: DaySettings,
+    pub sentence_selection: SentenceSelectionSettings,
+    pub new_cards: NewCardSettings,
+    pub leeches: LeechSettings,
+    pub burying: BurySettings,
+    pub catch_up: CatchUpSettings,
+}
+
+impl Default for LanguageSettings {
+    fn default() -> Self {
+        Self {
+            fsrs: FsrsSettings::default(),
+            known_card_stability: NotNan::new(60.0).unwrap(),
+            workload: WorkloadSettings::default(),
+            day: DaySettings::default(),
+            sentence_selection: SentenceSelectionSettings::default(),
+            new_cards: NewCardSettings::default(),
+            leeches: LeechSettings::default(),
+            burying: BurySettings::default(),
+            catch_up: CatchUpSettings::default(),
+        }
+    }
+}
+
+impl LanguageSettings {
+    pub(crate) fn apply(&mut self, content: &LanguageSettingsEventContent) {
+        match content {
+            LanguageSettingsEventContent::SetFsrsSettings(fsrs) => self.fsrs = fsrs.clone(),
+            LanguageSettingsEventContent::SetKnownCardStability { stability } => {
+                self.known_card_stability = *stability
+            }
+            LanguageSettingsEventContent::SetWorkloadSettings(workload) => {
+                self.workload = workload.clone()
+            }
+            LanguageSettingsEventContent::SetDaySettings(day) => self.day = *day,
+            LanguageSettingsEventContent::SetSentenceSelectionSettings(sentence_selection) => {
+                self.sentence_selection = sentence_selection.clone()
+            }
+            LanguageSettingsEventContent::SetNewCardSettings(new_cards) => {
+                self.new_cards = new_cards.clone()
+            }
+            LanguageSettingsEventContent::SetLeechSettings(leeches) => {
+                self.leeches = leeches.clone()
+            }
+            LanguageSettingsEventContent::SetBurySettings(burying) => self.burying = *burying,
+            LanguageSettingsEventContent::SetCatchUpSettings(catch_up) => self.catch_up = *catch_up,
+        }
+    }
+}
+
+#[derive(
+    Clone, Debug, serde::Serialize, serde::Deserialize, PartialEq, Eq, Ord, PartialOrd, tsify::Tsify,
+)]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+pub struct FsrsSettings {
+    /// The probability we aim for the user to still remember a card when it comes due.
+    #[tsify(type = "number")]
+    pub desired_retention: NotNan<f64>,
+    /// The longest a card can go between reviews, in days.
+    pub maximum_interval: i32,
+    /// Randomize intervals slightly so that cards added together don't stay clumped together.
+    pub enable_fuzz: bool,
+    /// Personalized model weights (see `Deck::optimize_fsrs_weights`). `None` uses the FSRS defaults.
+    #[serde(default)]
+    #[tsify(optional, type = "number[] | null")]
+    pub weights: Option<Vec<NotNan<f64>>>,
+}
+
+impl Default for FsrsSettings {
+    fn default() -> Self {
+        Self {
+            desired_retention: NotNan::new(0.7).unwrap(),
+            maximum_interval: 36500,
+            enable_fuzz: false,
+            weights: None,
+        }
+    }
+}
+
+impl FsrsSettings {
+    pub(crate) fn parameters(&self) -> rs_fsrs::Parameters {
+        let defaults = rs_fsrs::Parameters::default();
+        let w = self
+            .weights
+            .as_ref()
+            .and_then(|weights| {
+                let weights = weights.iter().map(|w| w.into_inner()).collect::<Vec<_>>();
+                weights.try_into().ok()
+            })
+            .unwrap_or(defaults.w);
+        rs_fsrs::Parameters {
+            request_retention: self.desired_retention.into_inner().clamp(0.5, 0.99),
+            maximum_interval: self.maximum_interval.max(1),
+            enable_fuzz: self.enable_fuzz,
+            w,
+            ..defaults
+        }
+    }
+}
+
+/// Decks start with no limits and no load balancing (`WorkloadSettings::default`), so replaying a history recorded
+/// before these settings existed gives the same schedule it always did. They only change with a `SetWorkloadSettings`
+/// event, like the one `Deck::set_daily_limits` makes.
+#[derive(
+    Clone, Debug, serde::Serialize, serde::Deserialize, PartialEq, Eq, Ord, PartialOrd, tsify::Tsify,
+)]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+pub struct WorkloadSettings {
+    /// How many cards the user sees for the first time each day.
+    pub new_cards_per_day: u32,
+    /// How many cards the user has already seen are reviewed each day.
+    pub reviews_per_day: u32,
+    /// Move each review by a day or so to even out how many reviews are due on each day.
+    pub load_balancing: bool,
+}
+
+impl Default for WorkloadSettings {
+    fn default() -> Self {
+        Self {
+            new_cards_per_day: u32::MAX,
+            reviews_per_day: u32::MAX,
+            load_balancing: false,
+        }
+    }
+}
+
+impl WorkloadSettings {
+    /// What users get when they turn daily limits on.
+    pub fn recommended() -> Self {
+        Self {
+            new_cards_per_day: 20,
+            reviews_per_day: 200,
+            load_balancing: true,
+        }
+    }
+}
+
+/// Where the boundaries between the user's days are, for streaks, daily limits and burying.
+#[derive(
+    Copy,
+    Clone,
+    Debug,
+    serde::Serialize,
+    serde::Deserialize,
+    PartialEq,
+    Eq,
+    Ord,
+    PartialOrd,
+    tsify::Tsify,
+)]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+pub struct DaySettings {
+    /// The local hour at which a new day starts, so that reviewing just after midnight counts towards the day before.
+    pub day_start_hour: u8,
+    /// The user's offset from UTC in minutes, positive east of UTC (so UTC-8 is -480). Note that this is the opposite
+    /// sign to JavaScript's `getTimezoneOffset`.
+    pub utc_offset_minutes: i32,
+}
+
+impl Default for DaySettings {
+    fn default() -> Self {
+        Self {
+            day_start_hour: 4,
+            utc_offset_minutes: 0,
+        }
+    }
+}
+
+impl DaySettings {
+    fn shift(&self) -> chrono::Duration {
+        chrono::Duration::minutes(self.utc_offset_minutes.clamp(-18 * 60, 18 * 60) as i64)
+            - chrono::Duration::hours(self.day_start_hour.min(23) as i64)
+    }
+
+    /// The user's day that `timestamp` falls in.
+    pub(crate) fn day_of(&self, timestamp: DateTime<Utc>) -> NaiveDate {
+        (timestamp + self.shift()).date_naive()
+    }
+
+    pub(crate) fn start_of(&self, day: NaiveDate) -> DateTime<Utc> {
+        day.and_hms_opt(0, 0, 0).unwrap().and_utc() - self.shift()
+    }
+
+    /// The start of the day after the one `timestamp` falls in.
+    pub(crate) fn next_day_start(&self, timestamp: DateTime<Utc>) -> DateTime<Utc> {
+        self.start_of(self.day_of(timestamp) + chrono::Days::new(1))
+    }
+}
+
+/// How sentences are picked for sentence challenges (see `SentenceSelectionSettings::score`). Each weight is how
+/// much that consideration counts for; setting one to zero turns it off.
+#[derive(
+    Clone, Debug, serde::Serialize, serde::Deserialize, PartialEq, Eq, Ord, PartialOrd, tsify::Tsify,
+)]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+pub struct SentenceSelectionSettings {
+    /// Reward for each other due card the sentence would also review.
+    #[tsify(type = "number")]
+    pub due_card_weight: NotNan<f64>,
+    /// Penalty for each time the sentence has been reviewed before.
+    #[tsify(type = "number")]
+    pub review_count_weight: NotNan<f64>,
+    /// Penalty for having seen the sentence recently, which halves with each day since.
+    #[tsify(type = "number")]
+    pub recency_weight: NotNan<f64>,
+    /// The sentence length, in words, that isn't penalized at all.
+    pub ideal_length: u32,
+    /// Penalty for how far the sentence's length is from `ideal_length`. Being twice as long costs the same as being
+    /// half as long.
+    #[tsify(type = "number")]
+    pub length_weight: NotNan<f64>,
+    /// Allow one word in the sentence that the user has started learning, but hasn't got into the review state yet.
+    pub allow_unmature_word: bool,
+    /// Penalty for containing a not-yet-mature word, when they're allowed at all.
+    #[tsify(type = "number")]
+    pub unmature_word_weight: NotNan<f64>,
+}
+
+impl Default for SentenceSelectionSettings {
+    fn default() -> Self {
+        Self {
+            due_card_weight: NotNan::new(1.0).unwrap(),
+            review_count_weight: NotNan::new(0.5).unwrap(),
+            recency_weight: NotNan::new(2.0).unwrap(),
+            ideal_length: 8,
+            length_weight: NotNan::new(1.0).unwrap(),
+            allow_unmature_word: false,
+            unmature_word_weight: NotNan::new(1.5).unwrap(),
+        }
+    }
+}
+
+/// Which words new cards are suggested for, and in what order (see `new_card_order`).
+#[derive(
+    Clone, Debug, serde::Serialize, serde::Deserialize, PartialEq, Eq, Ord, PartialOrd, tsify::Tsify,
+)]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+pub enum NewCardOrder {
+    /// The most common words first.
+    Frequency,
+    /// The words in one of the language pack's themed word packs (see `Deck::get_word_packs`) first.
+    WordPack { name: String },
+    /// A mix of nouns, verbs, adjectives and so on, rather than however they fall in the frequency list.
+    PartOfSpeechBalanced,
+    /// Words from sentences the user has already seen first, so they can be read in full.
+    SeenSentences,
+}
+
+#[derive(
+    Clone, Debug, serde::Serialize, serde::Deserialize, PartialEq, Eq, Ord, PartialOrd, tsify::Tsify,
+)]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+pub struct NewCardSettings {
+    pub order: NewCardOrder,
+    /// Only text cards are suggested until the deck has this many cards.
+    pub text_only_cards: u32,
+    /// Multiword terms are held back until the deck has this many cards.
+    pub multiword_min_cards: u32,
+    /// How many text cards to suggest for each listening card.
+    pub text_cards_per_listening_card: u32,
+}
+
+impl Default for NewCardSettings {
+    fn default() -> Self {
+        Self {
+            order: NewCardOrder::Frequency,
+            text_only_cards: 20,
+            multiword_min_cards: 20,
+            text_cards_per_listening_card: 2,
+        }
+    }
+}
+
+/// When a card counts as a leech: one the user keeps forgetting, where more of the same reviews isn't helping.
+#[derive(
+    Clone, Debug, serde::Serialize, serde::Deserialize, PartialEq, Eq, Ord, PartialOrd, tsify::Tsify,
+)]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+pub struct LeechSettings {
+    /// A card that's been forgotten this many times is a leech.
+    pub lapse_threshold: u32,
+    /// A card is also a leech if at least `recent_failure_rate` of its last `recent_reviews` reviews were failures.
+    pub recent_reviews: u32,
+    #[tsify(type = "number")]
+    pub recent_failure_rate: NotNan<f64>,
+    /// Suspend a leech once it's been forgotten this many times, and again every half as many lapses after that if
+    /// the user unsuspends it. `None` never suspends leeches.
+    #[serde(default)]
+    #[tsify(optional)]
+    pub suspend_after_lapses: Option<u32>,
+}
+
+impl Default for LeechSettings {
+    fn default() -> Self {
+        Self {
+            lapse_threshold: 8,
+            recent_reviews: 6,
+            recent_failure_rate: NotNan::new(0.5).unwrap(),
+            suspend_after_lapses: None,
+        }
+    }
+}
+
+/// Once a card is reviewed, its siblings (the other cards for the same word, see `Deck::siblings`) are put off until
+/// the next day, since reviewing one mostly reviews the others too.
+#[derive(
+    Copy,
+    Clone,
+    Debug,
+    serde::Serialize,
+    serde::Deserialize,
+    PartialEq,
+    Eq,
+    Ord,
+    PartialOrd,
+    tsify::Tsify,
+)]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+pub struct BurySettings {
+    /// Bury siblings that haven't been studied yet, so a word's listening card isn't introduced the same day as its
+    /// text card.
+    pub bury_new_siblings: bool,
+    /// Bury siblings that have graduated to reviews. Siblings still in their learning or relearning steps never are.
+    pub bury_review_siblings: bool,
+}
+
+impl Default for BurySettings {
+    fn default() -> Self {
+        Self {
+            bury_new_siblings: true,
+            bury_review_siblings: true,
+        }
+    }
+}
+
+/// Catch-up mode, for coming back after a long break to more overdue reviews than can be done in one sitting. The
+/// overdue cards most worth saving go first, and the rest are spread over the following days.
+///
+/// It's off until turned on with a `SetCatchUpSettings` event, like the one `Deck::set_catch_up` makes, since while
+/// catching up it lowers the daily review limit below `WorkloadSettings::reviews_per_day`.
+#[derive(
+    Copy,
+    Clone,
+    Debug,
+    serde::Serialize,
+    serde::Deserialize,
+    PartialEq,
+    Eq,
+    Ord,
+    PartialOrd,
+    tsify::Tsify,
+)]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+pub struct CatchUpSettings {
+    pub enabled: bool,
+    /// Catch-up mode starts once at least this many reviews are overdue, and ends once fewer are.
+    pub min_backlog: u32,
+    /// How many overdue reviews are shown each day while catching up, if that's less than
+    /// `WorkloadSettings::reviews_per_day`.
+    pub reviews_per_day: u32,
+}
+
+impl Default for CatchUpSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            min_backlog: 100,
+            reviews_per_day: 100,
+        }
+    }
+}
+
+#[derive(
+    Clone, Debug, serde::Serialize, serde::Deserialize, PartialEq, Eq, Ord, PartialOrd, tsify::Tsify,
+)]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+pub enum DeckSettingsEvent {
+    Language(LanguageSettingsEvent),
+}
+
+#[derive(
+    Clone, Debug, serde::Serialize, serde::Deserialize, PartialEq, Eq, Ord, PartialOrd, tsify::Tsify,
+)]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+pub struct LanguageSettingsEvent {
+    pub language: Language,
+    pub content: LanguageSettingsEventContent,
+}
+
+#[derive(
+    Clone, Debug, serde::Serialize, serde::Deserialize, PartialEq, Eq, Ord, PartialOrd, tsify::Tsify,
+)]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+#[allow(clippy::enum_variant_names)]
+pub enum LanguageSettingsEventContent {
+    SetFsrsSettings(FsrsSettings),
+    SetKnownCardStability {
+        #[tsify(type = "number")]
+        stability: NotNan<f64>,
+    },
+    SetWorkloadSettings(WorkloadSettings),
+    SetDaySettings(DaySettings),
+    SetSentenceSelectionSettings(SentenceSelectionSettings),
+    SetNewCardSettings(NewCardSettings),
+    SetLeechSettings(LeechSettings),
+    SetBurySettings(BurySettings),
+    SetCatchUpSettings(CatchUpSettings),
+}
+
+/// The settings changes for `language`, in the order they should be applied.
+pub(crate) fn language_settings_changes<'a>(
+    events: impl Iterator<Item = &'a Timestamped<EventType<DeckSettingsEvent>>>,
+    language: Language,
+) -> VecDeque<Timestamped<LanguageSettingsEventContent>> {
+    events
+        .filter_map(|event| match &event.event {
+            EventType::User(DeckSettingsEvent::Language(LanguageSettingsEvent {
+                language: event_language,
+                content,
+            })) if *event_language == language => Some(Timestamped {
+                timestamp: event.timestamp,
+                within_device_events_index: event.within_device_events_index,
+                event: content.clone(),
+            }),
+            _ => None,
+        })
+        .collect()
+}
+
+#[derive(
+    Clone, Debug, serde::Serialize, serde::Deserialize, Ord, PartialOrd, Eq, PartialEq, tsify::Tsify,
+)]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+#[serde(tag = "version")]
+pub enum VersionedDeckSettingsEvent {
+    V1(DeckSettingsEvent),
+}
+impl Event for DeckSettingsEvent {
+    fn to_json(&self) -> Result<serde_json::Value, serde_json::Error> {
+        let versioned = VersionedDeckSettingsEvent::from(self.clone());
+        serde_json::to_value(versioned)
+    }
+
+    fn from_json(json: &serde_json::Value) -> Result<Self, serde_json::Error> {
+        serde_json::from_value::<VersionedDeckSettingsEvent>(json.clone())
+            .map(|versioned| versioned.into())
+    }
+}
+impl From<DeckSettingsEvent> for VersionedDeckSettingsEvent {
+    fn from(event: DeckSettingsEvent) -> Self {
+        VersionedDeckSettingsEvent::V1(event)
+    }
+}
+impl From<VersionedDeckSettingsEvent> for DeckSettingsEvent {
+    fn from(event: VersionedDeckSettingsEvent) -> Self {
+        match event {
+            VersionedDeckSettingsEvent::V1(event) => event,
+        }
+    }
+}
@@ -0,0 +1,128 @@
+//! Dictation: transcription challenges start out asking for a single word, but as the user's listening cards mature,
+//! the other words they can reliably make out are asked for too, until they're writing down whole sentences.
+
+use language_utils::{Heteronym, Literal, transcription_challenge};
+use lasso::Spur;
+
+use crate::{CardIndicator, Deck};
+
+/// How stable (in days) a listening card needs to be before its word is asked for in sentences practicing other
+/// words. Three weeks is when Anki starts calling a card mature.
+const DICTATION_STABILITY: f64 = 21.0;
+
+impl Deck {
+    /// Whether the user hears `heteronym` well enough to transcribe it when it isn't the word being practiced.
+    fn can_transcribe(&self, heteronym: &Heteronym<Spur>) -> bool {
+        let Some(pronunciation) = self
+            .language_pack
+            .word_to_pronunciation
+            .get(&heteronym.word)
+        else {
+            return false;
+        };
+        self.cards
+            .get(&CardIndicator::ListeningHomophonous {
+                pronunciation: *pronunciation,
+            })
+            .is_some_and(|card_data| {
+                card_data.fsrs_card.state == rs_fsrs::State::Review
+                    && card_data.fsrs_card.stability >= DICTATION_STABILITY
+                    && !card_data.suspended
+            })
+    }
+
+    /// Split a sentence into the parts the user is asked to transcribe and the parts they're given. `target` is always
+    /// asked for, along with any other word the user can transcribe. Runs of words to transcribe are asked for
+    /// together, so the user writes down a phrase at a time rather than filling in one word after another.
+    pub(crate) fn dictation_parts(
+        &self,
+        target: Heteronym<Spur>,
+        literals: &[Literal<Spur>],
+    ) -> Vec<transcription_challenge::Part> {
+        let rodeo = &self.language_pack.rodeo;
+        let mut parts = Vec::new();
+        for literal in literals {
+            let asked = literal
+                .heteronym
+                .is_some_and(|heteronym| heteronym == target || self.can_transcribe(&heteronym));
+            let literal = literal.resolve(rodeo);
+            if !asked {
+                parts.push(transcription_challenge::Part::Provided { part: literal });
+            } else if let Some(transcription_challenge::Part::AskedToTranscribe { parts }) =
+                parts.last_mut()
+            {
+                parts.push(literal);
+            } else {
+                parts.push(transcription_challenge::Part::AskedToTranscribe {
+                    parts: vec![literal],
+                });
+            }
+        }
+        parts
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use language_utils::transcription_challenge::Part;
+
+    use crate::tests::{apply, lexeme_card, test_deck};
+    use crate::{CardIndicator, LanguageEventContent};
+
+    #[test]
+    fn mature_listening_cards_are_dictated() {
+        let start = chrono::DateTime::parse_from_rfc3339("2025-01-01T12:00:00Z")
+            .unwrap()
+            .to_utc();
+        let listening = |pronunciation: &str| CardIndicator::ListeningHomophonous {
+            pronunciation: pronunciation.to_string(),
+        };
+        let deck = apply(
+            test_deck(),
+            &[
+                (
+                    start,
+                    LanguageEventContent::AddCards {
+                        cards: vec![listening("ʃa"), listening("mɑ̃ʒ")],
+                    },
+                ),
+                (
+                    start,
+                    LanguageEventContent::ReviewCard {
+                        reviewed: listening("mɑ̃ʒ"),
+                        rating: "good".to_string(),
+                    },
+                ),
+                // Known cards start out stable enough to be dictated
+                (
+                    start,
+                    LanguageEventContent::MarkKnown {
+                        cards: vec![listening("lə")],
+                    },
+                ),
+            ],
+        );
+
+        let rodeo = &deck.language_pack.rodeo;
+        let CardIndicator::TargetLanguage {
+            lexeme: language_utils::Lexeme::Heteronym(chat),
+        } = lexeme_card("chat").get_interned(rodeo).unwrap()
+        else {
+            unreachable!()
+        };
+        let literals =
+            &deck.language_pack.sentences_to_literals[&rodeo.get("Le chat mange.").unwrap()];
+        let parts = deck.dictation_parts(chat, literals);
+        let parts: Vec<Vec<&str>> = parts
+            .iter()
+            .map(|part| match part {
+                Part::AskedToTranscribe { parts } => {
+                    parts.iter().map(|part| part.text.as_str()).collect()
+                }
+                Part::Provided { .. } => vec![],
+            })
+            .collect();
+        // "le" is asked for along with "chat", but "mange" is still too new
+        assert_eq!(parts, vec![vec!["Le", "chat"], vec![], vec![]]);
+    }
+}
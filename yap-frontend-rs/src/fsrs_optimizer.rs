@@ -0,0 +1,368 @@
+//! Fits FSRS weights to the user's own review history.
+//!
+//! Most of our reviews come from sentence challenges rather than flashcards, so the default weights (fit on Anki
+//! flashcard data) don't describe our learners very well. This runs entirely locally: for each card we replay its
+//! reviews through the FSRS memory model, predict the probability of recall at every review that happened on a later
+//! day than the previous one, and minimize the log loss of those predictions.
+
+use rs_fsrs::{Parameters, Rating};
+use wasm_bindgen::prelude::*;
+
+use crate::Deck;
+use crate::card_detail::{ReviewRecord, ReviewSource};
+use crate::deck_settings::{DeckSettingsEvent, FsrsSettings, LanguageSettingsEvent};
+
+const NUM_WEIGHTS: usize = 19;
+
+/// Lower and upper bounds for each weight, matching the ones used by the reference FSRS optimizer.
+const BOUNDS: [(f64, f64); NUM_WEIGHTS] = [
+    (0.01, 100.0),
+    (0.01, 100.0),
+    (0.01, 100.0),
+    (0.01, 100.0),
+    (1.0, 10.0),
+    (0.001, 4.0),
+    (0.001, 4.0),
+    (0.001, 0.75),
+    (0.0, 4.5),
+    (0.0, 0.8),
+    (0.001, 3.5),
+    (0.001, 5.0),
+    (0.001, 0.25),
+    (0.001, 0.9),
+    (0.0, 4.0),
+    (0.0, 1.0),
+    (1.0, 6.0),
+    (0.0, 2.0),
+    (0.0, 2.0),
+];
+
+/// Below this many predictions there isn't enough signal to move away from the defaults.
+const MIN_PREDICTIONS: usize = 50;
+const MAX_ITERATIONS: usize = 200;
+
+#[derive(Clone, Debug, tsify::Tsify, serde::Serialize, serde::Deserialize)]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+pub struct FsrsOptimizationReport {
+    /// The personalized weights, or `None` if there wasn't enough review history to fit them.
+    pub weights: Option<Vec<f64>>,
+    /// How many reviews the loss is computed over (reviews on the same day as the previous one aren't predicted).
+    pub predictions: usize,
+    pub default_log_loss: f64,
+    pub default_rmse: f64,
+    pub optimized_log_loss: Option<f64>,
+    pub optimized_rmse: Option<f64>,
+}
+
+/// A predicted probability of recall, along with whether the card was actually recalled.
+struct Prediction {
+    retrievability: f64,
+    recalled: bool,
+}
+
+fn parameters(weights: &[f64; NUM_WEIGHTS]) -> Parameters {
+    Parameters {
+        w: *weights,
+        ..Default::default()
+    }
+}
+
+/// Replay a single card's reviews through the memory model, pushing a prediction for every review that happened at
+/// least a day after the previous one.
+fn predict_card(parameters: &Parameters, reviews: &[ReviewRecord], out: &mut Vec<Prediction>) {
+    let Some((first, rest)) = reviews.split_first() else {
+        return;
+    };
+    let mut stability = parameters.init_stability(first.rating);
+    let mut difficulty = parameters.init_difficulty(first.rating);
+    let mut last_review = first.timestamp;
+
+    for review in rest {
+        let elapsed_days = (review.timestamp - last_review).num_days();
+        if elapsed_days <= 0 {
+            stability = parameters.short_term_stability(stability, review.rating);
+        } else {
+            let retrievability = Parameters::forgetting_curve(elapsed_days as f64, stability);
+            out.push(Prediction {
+                retrievability,
+                recalled: review.rating != Rating::Again,
+            });
+            stability = if review.rating == Rating::Again {
+                parameters.next_forget_stability(difficulty, stability, retrievability)
+            } else {
+                parameters.next_recall_stability(
+                    difficulty,
+                    stability,
+                    retrievability,
+                    review.rating,
+                )
+            };
+        }
+        stability = stability.max(0.01);
+        difficulty = parameters.next_difficulty(difficulty, review.rating);
+        last_review = review.timestamp;
+    }
+}
+
+fn predictions<'a>(
+    weights: &[f64; NUM_WEIGHTS],
+    histories: impl Iterator<Item = &'a [ReviewRecord]>,
+) -> Vec<Prediction> {
+    let parameters = parameters(weights);
+    let mut out = Vec::new();
+    for reviews in histories {
+        predict_card(&parameters, reviews, &mut out);
+    }
+    out
+}
+
+fn log_loss(predictions: &[Prediction]) -> f64 {
+    let total: f64 = predictions
+        .iter()
+        .map(|prediction| {
+            let p = prediction.retrievability.clamp(1e-4, 1.0 - 1e-4);
+            if prediction.recalled {
+                -p.ln()
+            } else {
+                -(1.0 - p).ln()
+            }
+        })
+        .sum();
+    total / predictions.len().max(1) as f64
+}
+
+/// Calibration error: predictions are bucketed by predicted retrievability, and we take the (count-weighted) RMSE
+/// between the average prediction and the actual recall rate of each bucket.
+fn rmse(predictions: &[Prediction]) -> f64 {
+    const BINS: usize = 20;
+    let mut bins = [(0usize, 0.0f64, 0.0f64); BINS];
+    for prediction in predictions {
+        let bin = ((prediction.retrievability * BINS as f64) as usize).min(BINS - 1);
+        bins[bin].0 += 1;
+        bins[bin].1 += prediction.retrievability;
+        bins[bin].2 += if prediction.recalled { 1.0 } else { 0.0 };
+    }
+    let squared_error: f64 = bins
+        .iter()
+        .filter(|(count, _, _)| *count > 0)
+        .map(|(count, predicted, actual)| {
+            let count = *count as f64;
+            count * (predicted / count - actual / count).powi(2)
+        })
+        .sum();
+    (squared_error / predictions.len().max(1) as f64).sqrt()
+}
+
+/// The training objective: log loss plus a small penalty for straying from the defaults, which keeps the fit sane
+/// when there's little data.
+fn objective(
+    weights: &[f64; NUM_WEIGHTS],
+    histories: &[&[ReviewRecord]],
+    defaults: &[f64; NUM_WEIGHTS],
+) -> f64 {
+    let predictions = predictions(weights, histories.iter().copied());
+    let regularization: f64 = weights
+        .iter()
+        .zip(defaults)
+        .zip(BOUNDS)
+        .map(|((w, d), (low, high))| ((w - d) / (high - low)).powi(2))
+        .sum();
+    log_loss(&predictions) + regularization / predictions.len().max(1) as f64
+}
+
+/// Projected gradient descent with finite-difference gradients and a backtracking step size.
+/// Everything here is deterministic, so every device fits the same weights from the same history.
+fn optimize(histories: &[&[ReviewRecord]], defaults: &[f64; NUM_WEIGHTS]) -> [f64; NUM_WEIGHTS] {
+    let clamp = |weights: &mut [f64; NUM_WEIGHTS]| {
+        for (w, (low, high)) in weights.iter_mut().zip(BOUNDS) {
+            *w = w.clamp(low, high);
+        }
+    };
+
+    let mut weights = *defaults;
+    clamp(&mut weights);
+    let mut loss = objective(&weights, histories, defaults);
+    let mut step = 0.05;
+
+    for _ in 0..MAX_ITERATIONS {
+        // Gradient with respect to weights normalized to their bounds, so one step size suits every weight
+        let mut gradient = [0.0; NUM_WEIGHTS];
+        for i in 0..NUM_WEIGHTS {
+            let (low, high) = BOUNDS[i];
+            let h = (high - low) * 1e-4;
+            let mut forward = weights;
+            forward[i] = (forward[i] + h).min(high);
+            let mut backward = weights;
+            backward[i] = (backward[i] - h).max(low);
+            let span = (forward[i] - backward[i]) / (high - low);
+            if span > 0.0 {
+                gradient[i] = (objective(&forward, histories, defaults)
+                    - objective(&backward, histories, defaults))
+                    / span;
+            }
+        }
+        let norm = gradient.iter().map(|g| g * g).sum::<f64>().sqrt();
+        if norm < 1e-9 {
+            break;
+        }
+
+        let mut improved = false;
+        while step > 1e-6 {
+            let mut candidate = weights;
+            for i in 0..NUM_WEIGHTS {
+                let (low, high) = BOUNDS[i];
+                candidate[i] -= step * gradient[i] / norm * (high - low);
+            }
+            clamp(&mut candidate);
+            let candidate_loss = objective(&candidate, histories, defaults);
+            if candidate_loss < loss {
+                weights = candidate;
+                loss = candidate_loss;
+                step *= 1.5;
+                improved = true;
+                break;
+            }
+            step /= 2.0;
+        }
+        if !improved {
+            break;
+        }
+    }
+
+    weights
+}
+
+impl Deck {
+    fn review_histories(&self) -> Vec<&[ReviewRecord]> {
+        self.cards
+            .values()
+            .map(|card_data| card_data.reviews.as_slice())
+            .filter(|reviews| reviews.len() > 1)
+            // Cards marked as known skip the memory model's initial state, so we can't replay them
+            .filter(|reviews| reviews[0].source != ReviewSource::MarkedKnown)
+            .collect()
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+impl Deck {
+    /// Fit FSRS weights to this deck's review history and compare them against the defaults.
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+    pub fn optimize_fsrs_weights(&self) -> FsrsOptimizationReport {
+        let histories = self.review_histories();
+        let defaults = Parameters::default().w;
+        let default_predictions = predictions(&defaults, histories.iter().copied());
+
+        let mut report = FsrsOptimizationReport {
+            weights: None,
+            predictions: default_predictions.len(),
+            default_log_loss: log_loss(&default_predictions),
+            default_rmse: rmse(&default_predictions),
+            optimized_log_loss: None,
+            optimized_rmse: None,
+        };
+        if default_predictions.len() < MIN_PREDICTIONS {
+            return report;
+        }
+
+        let weights = optimize(&histories, &defaults);
+        let optimized_predictions = predictions(&weights, histories.iter().copied());
+        report.weights = Some(weights.to_vec());
+        report.optimized_log_loss = Some(log_loss(&optimized_predictions));
+        report.optimized_rmse = Some(rmse(&optimized_predictions));
+        report
+    }
+
+    /// A settings event that switches this deck to the given weights (e.g. from `optimize_fsrs_weights`), keeping the
+    /// rest of the FSRS settings as they are. Passing `None` goes back to the default weights.
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+    pub fn set_fsrs_weights(&self, weights: Option<Vec<f64>>) -> Option<DeckSettingsEvent> {
+        let weights = match weights {
+            Some(weights) => {
+                if weights.len() != NUM_WEIGHTS {
+                    return None;
+                }
+                Some(
+                    weights
+                        .into_iter()
+                        .map(ordered_float::NotNan::new)
+                        .collect::<Result<Vec<_>, _>>()
+                        .ok()?,
+                )
+            }
+            None => None,
+        };
+        Some(DeckSettingsEvent::Language(LanguageSettingsEvent {
+            language: self.target_language,
+            content: crate::deck_settings::LanguageSettingsEventContent::SetFsrsSettings(
+                FsrsSettings {
+                    weights,
+                    ..self.settings.fsrs.clone()
+                },
+            ),
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::card_detail::ReviewSource;
+    use chrono::{DateTime, Duration, Utc};
+
+    fn review(timestamp: DateTime<Utc>, rating: Rating) -> ReviewRecord {
+        ReviewRecord {
+            timestamp,
+            rating,
+            source: ReviewSource::Flashcard,
+            sentence: None,
+            stability_before: 0.0,
+            difficulty_before: 0.0,
+            stability_after: 0.0,
+            difficulty_after: 0.0,
+            state_after: rs_fsrs::State::Review,
+        }
+    }
+
+    /// Reviews from a learner who forgets much faster than the default weights expect.
+    fn forgetful_histories() -> Vec<Vec<ReviewRecord>> {
+        let start = DateTime::parse_from_rfc3339("2025-01-01T12:00:00Z")
+            .unwrap()
+            .to_utc();
+        (0..60)
+            .map(|card| {
+                let mut time = start + Duration::minutes(card);
+                let mut reviews = vec![review(time, Rating::Good)];
+                for (index, gap) in [1, 3, 7, 15].into_iter().enumerate() {
+                    time += Duration::days(gap);
+                    let forgot = (card as usize + index) % 3 != 0;
+                    reviews.push(review(
+                        time,
+                        if forgot { Rating::Again } else { Rating::Good },
+                    ));
+                }
+                reviews
+            })
+            .collect()
+    }
+
+    #[test]
+    fn optimizing_improves_log_loss() {
+        let histories = forgetful_histories();
+        let histories = histories.iter().map(Vec::as_slice).collect::<Vec<_>>();
+        let defaults = Parameters::default().w;
+
+        let default_predictions = predictions(&defaults, histories.iter().copied());
+        let weights = optimize(&histories, &defaults);
+        let optimized_predictions = predictions(&weights, histories.iter().copied());
+
+        assert_eq!(default_predictions.len(), optimized_predictions.len());
+        assert!(log_loss(&optimized_predictions) < log_loss(&default_predictions));
+        assert!(rmse(&optimized_predictions) < rmse(&default_predictions));
+        for (w, (low, high)) in weights.iter().zip(BOUNDS) {
+            assert!((low..=high).contains(w));
+        }
+        // deterministic
+        assert_eq!(weights, optimize(&histories, &defaults));
+    }
+}
@@ -0,0 +1,202 @@
+//! Which lexemes the user knows (has a card for in the review state), kept up to date as cards change state rather
+//! than rebuilt from every card whenever it's needed. Alongside it is a count, for every sentence, of the lexemes in
+//! it the user doesn't know, so checking whether a sentence is comprehensible is a lookup instead of a scan.
+
+use std::collections::HashMap;
+
+use language_utils::Lexeme;
+use lasso::Spur;
+
+use crate::{CardIndicator, Deck};
+
+/// Dense IDs for the lexemes and sentences in a language pack, and which sentences each lexeme is in.
+#[derive(Debug, Default)]
+pub(crate) struct LexemeIndex {
+    lexeme_ids: HashMap<Lexeme<Spur>, u32>,
+    sentence_ids: HashMap<Spur, u32>,
+    /// For each lexeme, the sentences it's in (each only once).
+    lexeme_sentences: Vec<Vec<u32>>,
+    /// For each sentence, how many distinct lexemes it has.
+    sentence_lexeme_counts: Vec<u16>,
+}
+
+impl LexemeIndex {
+    pub(crate) fn new<'a>(
+        sentences_to_all_lexemes: impl Iterator<Item = (&'a Spur, &'a Vec<Lexeme<Spur>>)>,
+    ) -> Self {
+        let mut index = LexemeIndex::default();
+        for (sentence, lexemes) in sentences_to_all_lexemes {
+            let sentence_id = index.sentence_ids.len() as u32;
+            index.sentence_ids.insert(*sentence, sentence_id);
+            let mut count = 0;
+            for lexeme in lexemes {
+                let next_id = index.lexeme_ids.len() as u32;
+                let lexeme_id = *index.lexeme_ids.entry(*lexeme).or_insert(next_id);
+                if lexeme_id == next_id {
+                    index.lexeme_sentences.push(Vec::new());
+                }
+                let sentences = &mut index.lexeme_sentences[lexeme_id as usize];
+                // Sentences are added in order, so a repeated lexeme will have just been added
+                if sentences.last() != Some(&sentence_id) {
+                    sentences.push(sentence_id);
+                    count += 1;
+                }
+            }
+            index.sentence_lexeme_counts.push(count);
+        }
+        index
+    }
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct KnownLexemes {
+    /// Bitset over lexeme IDs.
+    known: Vec<u64>,
+    /// For each sentence, how many of its distinct lexemes aren't known.
+    unknown_counts: Vec<u16>,
+}
+
+impl KnownLexemes {
+    pub(crate) fn new(index: &LexemeIndex) -> Self {
+        KnownLexemes {
+            known: vec![0; index.lexeme_sentences.len().div_ceil(64)],
+            unknown_counts: index.sentence_lexeme_counts.clone(),
+        }
+    }
+
+    pub(crate) fn contains(&self, index: &LexemeIndex, lexeme: &Lexeme<Spur>) -> bool {
+        index.lexeme_ids.get(lexeme).is_some_and(|id| {
+            let id = *id as usize;
+            self.known[id / 64] & (1 << (id % 64)) != 0
+        })
+    }
+
+    fn set(&mut self, index: &LexemeIndex, lexeme: &Lexeme<Spur>, known: bool) {
+        let Some(id) = index.lexeme_ids.get(lexeme) else {
+            return;
+        };
+        let id = *id as usize;
+        let bit = 1 << (id % 64);
+        if (self.known[id / 64] & bit != 0) == known {
+            return;
+        }
+        self.known[id / 64] ^= bit;
+        for sentence in &index.lexeme_sentences[id] {
+            let count = &mut self.unknown_counts[*sentence as usize];
+            if known {
+                *count -= 1;
+            } else {
+                *count += 1;
+            }
+        }
+    }
+
+    /// How many distinct lexemes in `sentence` the user doesn't know, or `None` if it isn't in the language pack.
+    pub(crate) fn unknown_count(&self, index: &LexemeIndex, sentence: &Spur) -> Option<u16> {
+        let id = index.sentence_ids.get(sentence)?;
+        Some(self.unknown_counts[*id as usize])
+    }
+}
+
+impl Deck {
+    /// Bring the index up to date after `card` has changed state (or been removed).
+    pub(crate) fn update_known_lexemes(&mut self, card: &CardIndicator<Spur>) {
+        let CardIndicator::TargetLanguage { lexeme } = card else {
+            return;
+        };
+        let known = self
+            .cards
+            .get(card)
+            .is_some_and(|card_data| card_data.fsrs_card.state == rs_fsrs::State::Review);
+        self.known_lexemes
+            .set(&self.language_pack.lexeme_index, lexeme, known);
+    }
+
+    pub(crate) fn is_known_lexeme(&self, lexeme: &Lexeme<Spur>) -> bool {
+        self.known_lexemes
+            .contains(&self.language_pack.lexeme_index, lexeme)
+    }
+
+    /// Whether the user has a card for any heteronym of `word`, whatever state it's in.
+    pub(crate) fn has_word_card(&self, word: &Spur) -> bool {
+        self.language_pack
+            .words_to_heteronyms
+            .get(word)
+            .is_some_and(|heteronyms| {
+                heteronyms.iter().any(|heteronym| {
+                    self.cards.contains_key(&CardIndicator::TargetLanguage {
+                        lexeme: Lexeme::Heteronym(*heteronym),
+                    })
+                })
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::{apply, lexeme_card, test_deck};
+    use crate::{CardIndicator, LanguageEventContent};
+
+    #[test]
+    fn unknown_counts_follow_reviews() {
+        let start = chrono::DateTime::parse_from_rfc3339("2025-01-01T12:00:00Z")
+            .unwrap()
+            .to_utc();
+        let words = ["le", "chat", "mange"];
+        let mut events = vec![(
+            start,
+            LanguageEventContent::AddCards {
+                cards: words.map(lexeme_card).to_vec(),
+            },
+        )];
+        for word in words {
+            events.push((
+                start,
+                LanguageEventContent::ReviewCard {
+                    reviewed: lexeme_card(word),
+                    rating: "easy".to_string(),
+                },
+            ));
+        }
+        let deck = apply(test_deck(), &events);
+        let pack = deck.language_pack.clone();
+        let unknown = |deck: &crate::Deck, sentence: &str| {
+            deck.known_lexemes
+                .unknown_count(&pack.lexeme_index, &pack.rodeo.get(sentence).unwrap())
+                .unwrap()
+        };
+        assert_eq!(unknown(&deck, "Le chat mange."), 0);
+        assert_eq!(unknown(&deck, "Le chien mange."), 1);
+
+        // Forgetting a word makes every sentence with it harder, but only counts once per sentence
+        let deck = apply(
+            deck,
+            &[(
+                start + chrono::Duration::days(10),
+                LanguageEventContent::ReviewCard {
+                    reviewed: lexeme_card("mange"),
+                    rating: "again".to_string(),
+                },
+            )],
+        );
+        let CardIndicator::TargetLanguage { lexeme } =
+            lexeme_card("mange").get_interned(&pack.rodeo).unwrap()
+        else {
+            unreachable!()
+        };
+        assert!(!deck.is_known_lexeme(&lexeme));
+        assert_eq!(unknown(&deck, "Le chat mange."), 1);
+        assert_eq!(unknown(&deck, "Le chien mange."), 2);
+
+        let deck = apply(
+            deck,
+            &[(
+                start + chrono::Duration::days(10),
+                LanguageEventContent::RemoveCard {
+                    card: lexeme_card("chat"),
+                },
+            )],
+        );
+        assert_eq!(unknown(&deck, "Le chat mange."), 2);
+    }
+}
@@ -0,0 +1,249 @@
+//! Leeches: cards the user keeps forgetting. Doing more of the same reviews doesn't help these, so they're listed as
+//! troublesome words, reviewed as plain flashcards (with the dictionary's example sentences) instead of in harder
+//! sentence challenges, can get a mnemonic from the server, and can be suspended automatically (see
+//! `LeechSettings`).
+
+use language_utils::{MnemonicRequest, MnemonicResponse};
+use rs_fsrs::Rating;
+use wasm_bindgen::prelude::*;
+
+use crate::card_detail::ReviewSource;
+use crate::deck_settings::LeechSettings;
+use crate::utils::hit_ai_server;
+use crate::{CardContent, CardData, CardIndicator, Deck};
+
+impl CardData {
+    /// The fraction of the card's last `window` reviews that were failures, or `None` if it hasn't had that many.
+    fn recent_failure_rate(&self, window: usize) -> Option<f64> {
+        let reviews: Vec<_> = self
+            .reviews
+            .iter()
+            .rev()
+            .filter(|review| review.source != ReviewSource::MarkedKnown)
+            .take(window)
+            .collect();
+        if window == 0 || reviews.len() < window {
+            return None;
+        }
+        let failures = reviews
+            .iter()
+            .filter(|review| review.rating == Rating::Again)
+            .count();
+        Some(failures as f64 / window as f64)
+    }
+}
+
+impl LeechSettings {
+    pub(crate) fn is_leech(&self, card_data: &CardData) -> bool {
+        card_data.fsrs_card.lapses.max(0) as u32 >= self.lapse_threshold.max(1)
+            || card_data
+                .recent_failure_rate(self.recent_reviews as usize)
+                .is_some_and(|rate| rate >= self.recent_failure_rate.into_inner())
+    }
+
+    /// Whether a card that's just lapsed, bringing it to `lapses` lapses, should be suspended.
+    pub(crate) fn should_suspend(&self, lapses: i32) -> bool {
+        let Some(threshold) = self.suspend_after_lapses else {
+            return false;
+        };
+        let threshold = threshold.max(1) as i32;
+        lapses >= threshold && (lapses - threshold) % (threshold / 2).max(1) == 0
+    }
+}
+
+#[derive(tsify::Tsify, serde::Serialize, serde::Deserialize, Debug, Clone)]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+pub struct TroublesomeWord {
+    pub indicator: CardIndicator<String>,
+    pub content: CardContent<String>,
+    pub lapses: i32,
+    /// The fraction of recent reviews that were failures, if there have been enough reviews to tell.
+    pub recent_failure_rate: Option<f64>,
+    pub suspended: bool,
+}
+
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+impl Deck {
+    /// Every card that's a leech, most forgotten first.
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+    pub fn get_troublesome_words(&self) -> Vec<TroublesomeWord> {
+        let rodeo = &self.language_pack.rodeo;
+        let settings = &self.settings.leeches;
+        let mut words: Vec<TroublesomeWord> = self
+            .cards
+            .iter()
+            .enumerate()
+            .filter(|(_, (_, card_data))| settings.is_leech(card_data))
+            .filter_map(|(index, (_, card_data))| {
+                let (indicator, card) = self.get_card(index)?;
+                Some(TroublesomeWord {
+                    indicator: indicator.resolve(rodeo),
+                    content: card.content.resolve(rodeo),
+                    lapses: card_data.fsrs_card.lapses,
+                    recent_failure_rate: card_data
+                        .recent_failure_rate(settings.recent_reviews as usize),
+                    suspended: card_data.suspended,
+                })
+            })
+            .collect();
+        words.sort_by(|a, b| {
+            b.lapses.cmp(&a.lapses).then(
+                b.recent_failure_rate
+                    .unwrap_or(0.0)
+                    .total_cmp(&a.recent_failure_rate.unwrap_or(0.0)),
+            )
+        });
+        words
+    }
+
+    /// What to ask the server for a mnemonic for `card`. Listening cards don't get one, since the trouble there is
+    /// hearing the word rather than remembering what it means.
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+    pub fn get_mnemonic_request(&self, card: CardIndicator<String>) -> Option<MnemonicRequest> {
+        let rodeo = &self.language_pack.rodeo;
+        let index = self.cards.get_index_of(&card.get_interned(rodeo)?)?;
+        let (_, card) = self.get_card(index)?;
+        let (word, meanings) = match card.content.resolve(rodeo) {
+            CardContent::Heteronym(heteronym, definitions) => (
+                heteronym.word,
+                definitions
+                    .into_iter()
+                    .map(|definition| definition.native)
+                    .collect(),
+            ),
+            CardContent::Multiword(term, content) => (term, vec![content.meaning]),
+            CardContent::Listening { .. } => return None,
+        };
+        Some(MnemonicRequest {
+            language: self.target_language,
+            word,
+            meanings,
+        })
+    }
+}
+
+/// Ask the server for a mnemonic to help remember a troublesome word.
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+pub async fn get_mnemonic(
+    request: MnemonicRequest,
+    access_token: Option<String>,
+) -> Result<String, JsValue> {
+    let response = hit_ai_server("/mnemonic", request, access_token.as_ref())
+        .await
+        .map_err(|e| JsValue::from_str(&format!("Request error: {e:?}")))?;
+
+    if !response.ok() {
+        return Err(JsValue::from_str(&format!(
+            "HTTP error: {}",
+            response.status()
+        )));
+    }
+
+    let response: MnemonicResponse = response
+        .json()
+        .await
+        .map_err(|e| JsValue::from_str(&format!("Response parsing error: {e:?}")))?;
+
+    Ok(response.mnemonic)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::deck_settings::{LanguageSettingsEventContent, LeechSettings};
+    use crate::tests::{apply, lexeme_card, test_deck_with_settings};
+    use crate::{CardIndicator, Challenge, Deck, LanguageEventContent};
+
+    /// Forget "chat" and relearn it, a month after the last time.
+    fn lapse(deck: Deck, start: chrono::DateTime<chrono::Utc>, times: i64) -> Deck {
+        (0..times).fold(deck, |deck, _| {
+            let last_review = deck
+                .get_card_detail(lexeme_card("chat"))
+                .unwrap()
+                .reviews
+                .last()
+                .map_or(start, |review| review.timestamp);
+            let review = |rating: &str| LanguageEventContent::ReviewCard {
+                reviewed: lexeme_card("chat"),
+                rating: rating.to_string(),
+            };
+            let forgotten = last_review + chrono::Duration::days(30);
+            apply(
+                deck,
+                &[
+                    (forgotten, review("again")),
+                    (forgotten + chrono::Duration::minutes(10), review("good")),
+                ],
+            )
+        })
+    }
+
+    #[test]
+    fn leeches() {
+        let start = chrono::DateTime::parse_from_rfc3339("2025-01-01T12:00:00Z")
+            .unwrap()
+            .to_utc();
+        let listening = CardIndicator::ListeningHomophonous {
+            pronunciation: "ʃa".to_string(),
+        };
+        let mut events = vec![(
+            start,
+            LanguageEventContent::AddCards {
+                cards: vec![lexeme_card("chat"), lexeme_card("le"), listening.clone()],
+            },
+        )];
+        for word in ["chat", "le"] {
+            events.push((
+                start,
+                LanguageEventContent::ReviewCard {
+                    reviewed: lexeme_card(word),
+                    rating: "easy".to_string(),
+                },
+            ));
+        }
+        // Suspending leeches gets turned on between the third and fourth lapses
+        let deck = test_deck_with_settings(&[(
+            start + chrono::Duration::days(100),
+            LanguageSettingsEventContent::SetLeechSettings(LeechSettings {
+                suspend_after_lapses: Some(4),
+                ..Default::default()
+            }),
+        )]);
+        let deck = lapse(apply(deck, &events), start, 2);
+        assert!(deck.get_troublesome_words().is_empty());
+
+        // Half of the last six reviews were failures
+        let deck = lapse(deck, start, 1);
+        let troublesome = deck.get_troublesome_words();
+        assert_eq!(troublesome.len(), 1);
+        assert_eq!(troublesome[0].indicator, lexeme_card("chat"));
+        assert_eq!(troublesome[0].lapses, 3);
+        assert_eq!(troublesome[0].recent_failure_rate, Some(0.5));
+
+        // Leeches are shown as flashcards instead of in sentences
+        let rodeo = &deck.language_pack.rodeo;
+        let index = deck
+            .cards
+            .get_index_of(&lexeme_card("chat").get_interned(rodeo).unwrap())
+            .unwrap();
+        let challenge = deck
+            .get_review_info(vec![])
+            .get_challenge_for_card(&deck, index)
+            .unwrap();
+        assert!(matches!(
+            challenge,
+            Challenge::FlashCardReview { is_leech: true, .. }
+        ));
+
+        let request = deck.get_mnemonic_request(lexeme_card("chat")).unwrap();
+        assert_eq!(
+            (request.word.as_str(), request.meanings),
+            ("chat", vec!["cat".to_string()])
+        );
+        assert!(deck.get_mnemonic_request(listening).is_none());
+
+        // Once suspending is turned on, the next lapse past the threshold suspends the card
+        assert!(!deck.get_troublesome_words()[0].suspended);
+        let deck = lapse(deck, start, 1);
+        assert!(deck.get_troublesome_words()[0].suspended);
+    }
+}
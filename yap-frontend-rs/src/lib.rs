@@ -1,16 +1,38 @@
+mod achievements;
 mod audio;
+mod card_detail;
+mod catch_up;
+mod cloze;
+mod custom_study;
 mod deck_selection;
+mod deck_settings;
+mod dictation;
 mod directories;
+mod fsrs_optimizer;
+mod known_lexemes;
 mod language_pack;
+mod leeches;
+mod mark_known;
+mod minimal_pairs;
+mod multiple_choice;
+mod new_card_order;
 mod next_cards;
 mod notifications;
 pub mod opfs_test;
+mod placement_test;
+mod production;
+mod sentence_selection;
+mod siblings;
 mod simulation;
+mod speaking;
+mod stats;
 mod supabase;
 mod utils;
+mod workload;
 
 use chrono::{DateTime, Utc};
 use deck_selection::DeckSelectionEvent;
+use deck_settings::{DeckSettingsEvent, LanguageSettings, LanguageSettingsEventContent};
 use futures::StreamExt;
 use imdex_map::IndexMap;
 use language_utils::ConsolidatedLanguageDataWithCapacity;
@@ -28,17 +50,27 @@ use opfs::persistent::{self};
 use rs_fsrs::{FSRS, Rating};
 use serde::{Deserialize, Serialize};
 use std::cell::RefCell;
-use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::collections::{BTreeMap, BTreeSet, HashMap, VecDeque};
 use std::sync::Arc;
 use std::sync::LazyLock;
 use wasm_bindgen::prelude::*;
 use weapon::data_model::Event;
 use weapon::data_model::{EventStore, EventType, ListenerKey, Timestamped};
 
+use crate::card_detail::ReviewSource;
 use crate::deck_selection::DeckSelection;
+use crate::deck_settings::DeckSettings;
 use crate::directories::Directories;
 use crate::utils::hit_ai_server;
+pub use catch_up::CatchUp;
+pub use cloze::{BlankGrade, ClozeComprehensibleSentence, ClozePart};
+pub use custom_study::CustomStudyFilter;
+pub use leeches::{TroublesomeWord, get_mnemonic};
+pub use multiple_choice::MultipleChoice;
 pub use next_cards::NextCardsIterator;
+pub use production::{TranslateToTargetLanguage, autograde_production};
+pub use simulation::{SimulatedDay, SimulationOptions};
+pub use speaking::{SpeakComprehensibleSentence, SpokenPart, transcribe_speech};
 
 #[wasm_bindgen]
 pub struct Weapon {
@@ -156,6 +188,14 @@ impl Weapon {
             );
     }
 
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+    pub fn request_deck_settings(&self) {
+        let _flusher = FlushLater::new(self); // The addition of a new stream can trigger listeners, so we want to make sure to flush them after.
+        self.store
+            .borrow_mut()
+            .get_or_insert_default::<EventType<DeckSettingsEvent>>("settings".to_string(), None);
+    }
+
     #[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
     pub fn get_stream_num_events(&self, stream_id: String) -> Option<usize> {
         let store = self.store.borrow();
@@ -172,31 +212,34 @@ impl Weapon {
             .map(|s| s.state(DeckSelection::NoneSelected))
     }
 
+    pub fn get_deck_settings_state(&self) -> Option<DeckSettings> {
+        let store = self.store.borrow();
+        store
+            .get::<EventType<DeckSettingsEvent>>("settings".to_string())
+            .map(|s| s.state(DeckSettings::default()))
+    }
+
     pub async fn get_deck_state(
         &self,
         target_language: Language,
     ) -> Result<Deck, persistent::Error> {
         let language_pack = self.get_language_pack(target_language).await?;
 
-        let initial_deck_state = Deck {
-            cards: IndexMap::new(),
-            sentences_reviewed: BTreeMap::new(),
-            words_listened_to: BTreeMap::new(),
-            fsrs: FSRS::new(rs_fsrs::Parameters {
-                request_retention: 0.7, // target a 70% chance of forgetting
-                ..Default::default()
-            }),
-            total_reviews: 0,
-            xp: 0.0,
-            daily_streak: None,
-            language_pack,
-            target_language,
-        };
         let store = self.store.borrow_mut();
-        let Some(stream) = store.get::<EventType<DeckEvent>>("reviews".to_string()) else {
-            return Ok(initial_deck_state);
+
+        // Settings live in their own stream, but changes must take effect at the point in the review history where they were made
+        let pending_settings = store
+            .get::<EventType<DeckSettingsEvent>>("settings".to_string())
+            .map(|s| deck_settings::language_settings_changes(s.iter(), target_language))
+            .unwrap_or_default();
+
+        let initial_deck_state = Deck::new(language_pack, target_language, pending_settings);
+        let mut deck = match store.get::<EventType<DeckEvent>>("reviews".to_string()) {
+            Some(stream) => stream.state(initial_deck_state),
+            None => initial_deck_state,
         };
-        Ok(stream.state(initial_deck_state))
+        deck.apply_settings_until(DateTime::<Utc>::MAX_UTC);
+        Ok(deck)
     }
 
     #[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
@@ -416,13 +459,30 @@ impl Weapon {
     ) -> Result<(), JsValue> {
         let event: serde_json::Value =
             serde_json::from_str(&event).map_err(|e| JsValue::from_str(&format!("{e:?}")))?;
-        let event =
-            <Timestamped<EventType<DeckEvent>> as weapon::data_model::Event>::from_json(&event)
-                .map_err(|e| JsValue::from_str(&format!("{e:?}")))?;
 
-        self.store
-            .borrow_mut()
-            .add_device_event(stream_id, device_id, event, None);
+        // Each stream holds a different type of event
+        let mut store = self.store.borrow_mut();
+        match stream_id.as_str() {
+            "settings" => store.add_device_event(
+                stream_id,
+                device_id,
+                parse_remote_event::<EventType<DeckSettingsEvent>>(&event)?,
+                None,
+            ),
+            "deck_selection" => store.add_device_event(
+                stream_id,
+                device_id,
+                parse_remote_event::<EventType<DeckSelectionEvent>>(&event)?,
+                None,
+            ),
+            _ => store.add_device_event(
+                stream_id,
+                device_id,
+                parse_remote_event::<EventType<DeckEvent>>(&event)?,
+                None,
+            ),
+        };
+        drop(store);
         self.flush_notifications();
         Ok(())
     }
@@ -441,6 +501,16 @@ impl Weapon {
         self.flush_notifications();
     }
 
+    pub fn add_deck_settings_event(&self, event: DeckSettingsEvent) {
+        self.store.borrow_mut().add_raw_event(
+            "settings".to_string(),
+            self.device_id.clone(),
+            event,
+            None,
+        );
+        self.flush_notifications();
+    }
+
     pub fn add_deck_selection_event(&self, event: DeckSelectionEvent) {
         self.store.borrow_mut().add_raw_event(
             "deck_selection".to_string(),
@@ -486,6 +556,13 @@ impl Weapon {
     }
 }
 
+fn parse_remote_event<E: weapon::data_model::Event>(
+    event: &serde_json::Value,
+) -> Result<Timestamped<E>, JsValue> {
+    <Timestamped<E> as weapon::data_model::Event>::from_json(event)
+        .map_err(|e| JsValue::from_str(&format!("{e:?}")))
+}
+
 #[derive(Clone, Debug, tsify::Tsify, serde::Serialize, serde::Deserialize)]
 #[tsify(into_wasm_abi, from_wasm_abi)]
 pub struct EarliestUnsyncedEvent {
@@ -586,6 +663,8 @@ pub struct LanguagePack {
     phrasebook: BTreeMap<Spur, PhrasebookEntry>,
     word_to_pronunciation: HashMap<Spur, Spur>,
     pronunciation_to_words: HashMap<Spur, Vec<Spur>>,
+    lexeme_index: known_lexemes::LexemeIndex,
+    word_packs: BTreeMap<String, Vec<Lexeme<Spur>>>,
 }
 
 impl LanguagePack {
@@ -693,7 +772,7 @@ impl LanguagePack {
             map
         };
 
-        let sentences_to_all_lexemes = {
+        let sentences_to_all_lexemes: HashMap<Spur, Vec<Lexeme<Spur>>> = {
             language_data
                 .consolidated_language_data
                 .nlp_sentences
@@ -780,6 +859,29 @@ impl LanguagePack {
                 .collect()
         };
 
+        let lexeme_index = known_lexemes::LexemeIndex::new(
+            sentences
+                .iter()
+                .filter_map(|sentence| sentences_to_all_lexemes.get_key_value(sentence)),
+        );
+
+        let word_packs = {
+            language_data
+                .consolidated_language_data
+                .word_packs
+                .iter()
+                .map(|(name, lexemes)| {
+                    (
+                        name.clone(),
+                        lexemes
+                            .iter()
+                            .filter_map(|lexeme| lexeme.get_interned(&rodeo))
+                            .collect(),
+                    )
+                })
+                .collect()
+        };
+
         Self {
             rodeo,
             translations,
@@ -794,6 +896,8 @@ impl LanguagePack {
             phrasebook,
             word_to_pronunciation,
             pronunciation_to_words,
+            lexeme_index,
+            word_packs,
         }
     }
 }
@@ -814,6 +918,7 @@ pub enum SentenceReviewResult {
 pub enum CardType {
     TargetLanguage,
     Listening,
+    NativeToTarget,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq, Ord, PartialOrd, tsify::Tsify)]
@@ -828,8 +933,16 @@ pub struct AddCardOptions {
 )]
 #[tsify(into_wasm_abi, from_wasm_abi)]
 pub enum CardIndicator<S> {
-    TargetLanguage { lexeme: Lexeme<S> },
-    ListeningHomophonous { pronunciation: S },
+    TargetLanguage {
+        lexeme: Lexeme<S>,
+    },
+    ListeningHomophonous {
+        pronunciation: S,
+    },
+    /// The user is shown the meaning and has to come up with the target language word.
+    NativeToTarget {
+        lexeme: Lexeme<S>,
+    },
 }
 
 impl<S> CardIndicator<S> {
@@ -846,6 +959,13 @@ impl<S> CardIndicator<S> {
             _ => None,
         }
     }
+
+    pub fn native_to_target(&self) -> Option<&Lexeme<S>> {
+        match self {
+            CardIndicator::NativeToTarget { lexeme } => Some(lexeme),
+            _ => None,
+        }
+    }
 }
 
 impl CardIndicator<String> {
@@ -859,6 +979,9 @@ impl CardIndicator<String> {
                     pronunciation: rodeo.get(pronunciation)?,
                 }
             }
+            CardIndicator::NativeToTarget { lexeme } => CardIndicator::NativeToTarget {
+                lexeme: lexeme.get_interned(rodeo)?,
+            },
         })
     }
 }
@@ -874,6 +997,9 @@ impl CardIndicator<Spur> {
                     pronunciation: rodeo.resolve(pronunciation).to_string(),
                 }
             }
+            CardIndicator::NativeToTarget { lexeme } => CardIndicator::NativeToTarget {
+                lexeme: lexeme.resolve(rodeo),
+            },
         }
     }
 }
@@ -885,6 +1011,12 @@ pub enum SentenceReviewIndicator {
         challenge_sentence: String,
         result: SentenceReviewResult,
     },
+    /// The user translated a native language sentence into the target language. `challenge_sentence` is the target
+    /// language sentence it was a translation of.
+    NativeToTarget {
+        challenge_sentence: String,
+        result: SentenceReviewResult,
+    },
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq, Ord, PartialOrd, tsify::Tsify)]
@@ -911,6 +1043,52 @@ pub enum LanguageEventContent {
     TranscriptionChallenge {
         challenge: Vec<transcription_challenge::PartGraded>,
     },
+    /// Stop scheduling a card until it's unsuspended.
+    SuspendCard {
+        card: CardIndicator<String>,
+    },
+    UnsuspendCard {
+        card: CardIndicator<String>,
+    },
+    /// Hide a card until the start of the next day.
+    BuryCard {
+        card: CardIndicator<String>,
+    },
+    /// Take a card out of the deck entirely, forgetting its review history.
+    RemoveCard {
+        card: CardIndicator<String>,
+    },
+    /// The user already knows these cards, so add them straight into the review state.
+    MarkKnown {
+        cards: Vec<CardIndicator<String>>,
+    },
+    /// The user filled in the blanks where `heteronym` was missing from `challenge_sentence`.
+    ClozeChallenge {
+        challenge_sentence: String,
+        heteronym: Heteronym<String>,
+        grades: Vec<cloze::BlankGrade>,
+    },
+    MultipleChoiceChallenge {
+        card: CardIndicator<String>,
+        correct: bool,
+    },
+    /// The user read `challenge_sentence` aloud.
+    SpeakingChallenge {
+        challenge_sentence: String,
+        pronounced: Vec<Heteronym<String>>,
+        mispronounced: Vec<Heteronym<String>>,
+    },
+    /// The user was played the word pronounced `said`, and asked whether it was that or `other`.
+    MinimalPairDrill {
+        said: String,
+        other: String,
+        correct: bool,
+    },
+    /// A review in a cram session. It's kept in the card's history, but doesn't change when the card is next due.
+    CramCard {
+        reviewed: CardIndicator<String>,
+        rating: String,
+    },
 }
 
 // Event types
@@ -952,12 +1130,31 @@ impl From<VersionedDeckEvent> for DeckEvent {
 #[derive(Clone, Debug)]
 struct CardData {
     fsrs_card: rs_fsrs::Card,
+    reviews: Vec<card_detail::ReviewRecord>,
+    /// Kept apart from `reviews`, since they aren't part of the card's schedule.
+    cram_reviews: Vec<card_detail::ReviewRecord>,
+    suspended: bool,
+    buried_until: Option<DateTime<Utc>>,
+    added_at: DateTime<Utc>,
 }
 
+/// Streak freezes are earned for every week of streak, up to this many at a time.
+const MAX_STREAK_FREEZES: u32 = 2;
+
 #[derive(Clone, Debug)]
 struct DailyStreak {
-    streak_start: chrono::DateTime<chrono::Utc>,
-    last_review_time: chrono::DateTime<chrono::Utc>,
+    /// How many days the user has reviewed on during the current streak.
+    days: u32,
+    last_review_day: chrono::NaiveDate,
+    /// Each freeze covers one day without reviews.
+    freezes: u32,
+}
+
+impl DailyStreak {
+    /// How many days have been missed since the last review, as of `today`.
+    fn days_missed(&self, today: chrono::NaiveDate) -> u32 {
+        ((today - self.last_review_day).num_days() - 1).max(0) as u32
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -965,12 +1162,22 @@ struct DailyStreak {
 pub struct Deck {
     cards: IndexMap<CardIndicator<Spur>, CardData>,
     sentences_reviewed: BTreeMap<Spur, u32>,
+    /// When each sentence was last used in a challenge, to avoid showing the same one again too soon.
+    sentences_last_seen: HashMap<Spur, DateTime<Utc>>,
     words_listened_to: BTreeMap<Heteronym<Spur>, u32>,
 
     fsrs: FSRS,
+    settings: LanguageSettings,
+    /// Settings changes that haven't been reached yet while replaying the review history
+    pending_settings: VecDeque<Timestamped<LanguageSettingsEventContent>>,
     total_reviews: u64,
     xp: f64,
     daily_streak: Option<DailyStreak>,
+    due_forecast: workload::DueForecast,
+    daily_counts: workload::DailyCounts,
+    achievements: achievements::AchievementTracker,
+    contrast_attempts: BTreeMap<minimal_pairs::Contrast, minimal_pairs::ContrastAttempts>,
+    known_lexemes: known_lexemes::KnownLexemes,
 
     language_pack: Arc<LanguagePack>,
     target_language: Language,
@@ -998,7 +1205,11 @@ impl weapon::AppState for Deck {
             content: event,
         }) = event;
 
-        self.update_daily_streak(timestamp);
+        self.apply_settings_until(*timestamp);
+        self.update_daily_streak(self.settings.day.day_of(*timestamp));
+        if let Some(streak) = &self.daily_streak {
+            self.achievements.streak(streak.days, *timestamp);
+        }
         self.total_reviews += 1;
 
         if *event_language != self.target_language {
@@ -1011,29 +1222,11 @@ impl weapon::AppState for Deck {
                     if let Some(card) = card.get_interned(&self.language_pack.rodeo) {
                         if !self.cards.contains_key(&card) {
                             // Make sure the card is actually in the respective database
-                            match &card {
-                                CardIndicator::TargetLanguage { lexeme } => {
-                                    if !self.language_pack.word_frequencies.contains_key(lexeme) {
-                                        continue;
-                                    }
-                                }
-                                CardIndicator::ListeningHomophonous { pronunciation } => {
-                                    if !self
-                                        .language_pack
-                                        .pronunciation_to_words
-                                        .contains_key(pronunciation)
-                                    {
-                                        continue;
-                                    }
-                                }
+                            if !self.is_in_language_pack(&card) {
+                                continue;
                             }
 
-                            self.cards.insert(
-                                card.clone(),
-                                CardData {
-                                    fsrs_card: rs_fsrs::Card::new(),
-                                },
-                            );
+                            self.cards.insert(card.clone(), CardData::new(*timestamp));
                         }
                     }
                 }
@@ -1041,80 +1234,42 @@ impl weapon::AppState for Deck {
             LanguageEventContent::ReviewCard { reviewed, rating } => {
                 if let Some(reviewed) = reviewed.get_interned(&self.language_pack.rodeo) {
                     // Log review
-                    let rating = match rating.as_str() {
-                        "again" => Rating::Again,
-                        "hard" => Rating::Hard,
-                        "good" => Rating::Good,
-                        "easy" => Rating::Easy,
-                        _ => return self, // Invalid rating, don't apply
+                    let Some(rating) = parse_rating(rating) else {
+                        return self; // Invalid rating, don't apply
                     };
 
-                    self.log_review(reviewed.clone(), rating, *timestamp);
-                }
-            }
-            LanguageEventContent::TranslationChallenge {
-                review:
-                    SentenceReviewIndicator::TargetToNative {
-                        challenge_sentence,
-                        result: SentenceReviewResult::Perfect {},
-                    },
-            } => {
-                if let Some(challenge_sentence) = self.language_pack.rodeo.get(challenge_sentence) {
-                    if let Some(lexemes) = self
-                        .language_pack
-                        .sentences_to_lexemes
-                        .get(&challenge_sentence)
-                    {
-                        let sentence_review_count = self
-                            .sentences_reviewed
-                            .entry(challenge_sentence)
-                            .or_insert(0);
-                        *sentence_review_count += 1;
-
-                        let lexemes = lexemes.clone();
-                        for lexeme in lexemes {
-                            self.log_review(
-                                CardIndicator::TargetLanguage { lexeme },
-                                Rating::Good,
-                                *timestamp,
-                            );
-                        }
-                    }
+                    self.log_review(
+                        reviewed.clone(),
+                        rating,
+                        *timestamp,
+                        ReviewSource::Flashcard,
+                        None,
+                    );
                 }
             }
-            LanguageEventContent::TranslationChallenge {
-                review:
-                    SentenceReviewIndicator::TargetToNative {
-                        challenge_sentence: _,
-                        result:
-                            SentenceReviewResult::Wrong {
-                                submission: _,
-                                lexemes_remembered,
-                                lexemes_forgotten,
-                            },
-                    },
-            } => {
-                for lexeme in lexemes_remembered {
-                    if let Some(lexeme) = lexeme.get_interned(&self.language_pack.rodeo) {
-                        self.log_review(
-                            CardIndicator::TargetLanguage { lexeme },
-                            Rating::Good,
-                            *timestamp,
-                        );
-                    }
-                }
-
-                for lexeme in lexemes_forgotten {
-                    if let Some(lexeme) = lexeme.get_interned(&self.language_pack.rodeo) {
-                        self.log_review(
-                            CardIndicator::TargetLanguage { lexeme },
-                            Rating::Again,
-                            *timestamp,
-                        );
-                    }
-                }
+            LanguageEventContent::TranslationChallenge { review } => {
+                self.apply_sentence_review(review, *timestamp);
             }
             LanguageEventContent::TranscriptionChallenge { challenge } => {
+                let challenge_sentence = challenge
+                    .iter()
+                    .flat_map(|part| match part {
+                        transcription_challenge::PartGraded::AskedToTranscribe {
+                            parts, ..
+                        } => parts
+                            .iter()
+                            .flat_map(|part| {
+                                vec![part.heard.text.clone(), part.heard.whitespace.clone()]
+                            })
+                            .collect::<Vec<_>>(),
+                        transcription_challenge::PartGraded::Provided { part } => {
+                            vec![part.text.clone(), part.whitespace.clone()]
+                        }
+                    })
+                    .collect::<Vec<String>>()
+                    .join("");
+                let challenge_sentence = self.language_pack.rodeo.get(&challenge_sentence);
+
                 let mut perfect = true;
                 // Process each part of the transcription challenge
                 for part in challenge {
@@ -1153,7 +1308,13 @@ impl weapon::AppState for Deck {
                                         perfect = false;
                                     }
 
-                                    self.log_review(card, rating, *timestamp);
+                                    self.log_review(
+                                        card,
+                                        rating,
+                                        *timestamp,
+                                        ReviewSource::TranscriptionChallenge,
+                                        challenge_sentence,
+                                    );
                                 }
                             }
                         }
@@ -1163,33 +1324,104 @@ impl weapon::AppState for Deck {
                     }
                 }
                 if perfect {
-                    let challenge_sentence = challenge
-                        .iter()
-                        .flat_map(|part| match part {
-                            transcription_challenge::PartGraded::AskedToTranscribe {
-                                parts,
-                                ..
-                            } => parts
-                                .iter()
-                                .flat_map(|part| {
-                                    vec![part.heard.text.clone(), part.heard.whitespace.clone()]
-                                })
-                                .collect::<Vec<_>>(),
-                            transcription_challenge::PartGraded::Provided { part } => {
-                                vec![part.text.clone(), part.whitespace.clone()]
-                            }
-                        })
-                        .collect::<Vec<String>>()
-                        .join("");
-                    if let Some(challenge_sentence) =
-                        self.language_pack.rodeo.get(&challenge_sentence)
-                    {
+                    if let Some(challenge_sentence) = challenge_sentence {
                         let sentence_review_count = self
                             .sentences_reviewed
                             .entry(challenge_sentence)
                             .or_insert(0);
                         *sentence_review_count += 1;
                     }
+                    if challenge.iter().any(|part| {
+                        matches!(
+                            part,
+                            transcription_challenge::PartGraded::AskedToTranscribe { .. }
+                        )
+                    }) {
+                        self.achievements.unlock(
+                            achievements::Achievement::FirstPerfectTranscription,
+                            *timestamp,
+                        );
+                    }
+                }
+            }
+            LanguageEventContent::SuspendCard { card } => {
+                if let Some(card_data) = card
+                    .get_interned(&self.language_pack.rodeo)
+                    .and_then(|card| self.cards.get_mut(&card))
+                {
+                    card_data.suspended = true;
+                }
+            }
+            LanguageEventContent::UnsuspendCard { card } => {
+                if let Some(card_data) = card
+                    .get_interned(&self.language_pack.rodeo)
+                    .and_then(|card| self.cards.get_mut(&card))
+                {
+                    card_data.suspended = false;
+                }
+            }
+            LanguageEventContent::BuryCard { card } => {
+                let buried_until = self.settings.day.next_day_start(*timestamp);
+                if let Some(card_data) = card
+                    .get_interned(&self.language_pack.rodeo)
+                    .and_then(|card| self.cards.get_mut(&card))
+                {
+                    card_data.buried_until = Some(buried_until);
+                }
+            }
+            LanguageEventContent::RemoveCard { card } => {
+                if let Some(card) = card.get_interned(&self.language_pack.rodeo)
+                    && let Some(card_data) = self.cards.remove(&card)
+                {
+                    self.due_forecast
+                        .remove(&card_data.fsrs_card, &self.settings.day);
+                    if card_data.fsrs_card.state != rs_fsrs::State::New {
+                        self.track_word_removed(&card);
+                    }
+                    self.update_known_lexemes(&card);
+                }
+            }
+            LanguageEventContent::MarkKnown { cards } => {
+                for card in cards {
+                    if let Some(card) = card.get_interned(&self.language_pack.rodeo) {
+                        self.mark_known(card, *timestamp);
+                    }
+                }
+            }
+            LanguageEventContent::ClozeChallenge {
+                challenge_sentence,
+                heteronym,
+                grades,
+            } => {
+                self.apply_cloze_challenge(challenge_sentence, heteronym, grades, *timestamp);
+            }
+            LanguageEventContent::MultipleChoiceChallenge { card, correct } => {
+                self.apply_multiple_choice(card, *correct, *timestamp);
+            }
+            LanguageEventContent::SpeakingChallenge {
+                challenge_sentence,
+                pronounced,
+                mispronounced,
+            } => {
+                self.apply_speaking_challenge(
+                    challenge_sentence,
+                    pronounced,
+                    mispronounced,
+                    *timestamp,
+                );
+            }
+            LanguageEventContent::MinimalPairDrill {
+                said,
+                other,
+                correct,
+            } => {
+                self.apply_minimal_pair_drill(said, other, *correct);
+            }
+            LanguageEventContent::CramCard { reviewed, rating } => {
+                if let Some(reviewed) = reviewed.get_interned(&self.language_pack.rodeo)
+                    && let Some(rating) = parse_rating(rating)
+                {
+                    self.log_cram_review(&reviewed, rating, *timestamp);
                 }
             }
         }
@@ -1199,78 +1431,314 @@ impl weapon::AppState for Deck {
 }
 
 impl Deck {
+    fn new(
+        language_pack: Arc<LanguagePack>,
+        target_language: Language,
+        pending_settings: VecDeque<Timestamped<LanguageSettingsEventContent>>,
+    ) -> Self {
+        let settings = LanguageSettings::default();
+        Deck {
+            cards: IndexMap::new(),
+            sentences_reviewed: BTreeMap::new(),
+            sentences_last_seen: HashMap::new(),
+            words_listened_to: BTreeMap::new(),
+            fsrs: FSRS::new(settings.fsrs.parameters()),
+            settings,
+            pending_settings,
+            total_reviews: 0,
+            xp: 0.0,
+            daily_streak: None,
+            due_forecast: workload::DueForecast::default(),
+            daily_counts: workload::DailyCounts::default(),
+            achievements: achievements::AchievementTracker::default(),
+            contrast_attempts: BTreeMap::new(),
+            known_lexemes: known_lexemes::KnownLexemes::new(&language_pack.lexeme_index),
+            language_pack,
+            target_language,
+        }
+    }
+
     fn log_review(
         &mut self,
         card: CardIndicator<Spur>,
         rating: Rating,
         timestamp: DateTime<Utc>,
+        source: ReviewSource,
+        sentence: Option<Spur>,
     ) -> Option<&CardData> {
-        let word_frequencies = &self.language_pack.word_frequencies;
-        let pronunciation_to_words = &self.language_pack.pronunciation_to_words;
-
         // Make sure the card is actually in the respective database
-        match &card {
-            CardIndicator::TargetLanguage { lexeme } => {
-                if !word_frequencies.contains_key(lexeme) {
-                    return None;
-                }
-            }
-            CardIndicator::ListeningHomophonous { pronunciation } => {
-                if !pronunciation_to_words.contains_key(pronunciation) {
-                    return None;
-                }
-            }
+        if !self.is_in_language_pack(&card) {
+            return None;
+        }
+
+        if let Some(sentence) = sentence {
+            self.sentences_last_seen.insert(sentence, timestamp);
         }
 
         let card_data = self.cards.get_mut(&card)?;
+        let was_new = card_data.fsrs_card.state == rs_fsrs::State::New;
         let old_stability = card_data.fsrs_card.stability;
+        let old_difficulty = card_data.fsrs_card.difficulty;
+        let old_lapses = card_data.fsrs_card.lapses;
         let record_log = self.fsrs.repeat(card_data.fsrs_card.clone(), timestamp);
-        card_data.fsrs_card = record_log[&rating].card.clone();
+        let mut fsrs_card = record_log[&rating].card.clone();
+        let days = &self.settings.day;
+        // Take the card's old due date out first, so it isn't counted against itself while balancing
+        self.due_forecast.remove(&card_data.fsrs_card, days);
+        if self.settings.workload.load_balancing {
+            self.due_forecast.balance(
+                &mut fsrs_card,
+                self.settings.fsrs.maximum_interval.max(1),
+                days,
+            );
+        }
+        self.due_forecast.add(&fsrs_card, days);
+        self.daily_counts
+            .record(&card, card_data.fsrs_card.state, days.day_of(timestamp));
+        card_data.fsrs_card = fsrs_card;
+        // A buried card that gets failed anyway, in a sentence say, shouldn't have its steps put off until tomorrow
+        if matches!(
+            card_data.fsrs_card.state,
+            rs_fsrs::State::Learning | rs_fsrs::State::Relearning
+        ) {
+            card_data.buried_until = None;
+        }
         let new_stability = card_data.fsrs_card.stability;
+        card_data.reviews.push(card_detail::ReviewRecord {
+            timestamp,
+            rating,
+            source,
+            sentence,
+            stability_before: old_stability as f32,
+            difficulty_before: old_difficulty as f32,
+            stability_after: new_stability as f32,
+            difficulty_after: card_data.fsrs_card.difficulty as f32,
+            state_after: card_data.fsrs_card.state,
+        });
+        let lapses = card_data.fsrs_card.lapses;
+        if lapses > old_lapses && self.settings.leeches.should_suspend(lapses) {
+            card_data.suspended = true;
+        }
         self.xp += (new_stability - old_stability).max(0.0) / 10.0;
-        Some(card_data)
+        self.achievements.review(timestamp);
+        if was_new {
+            self.track_word_known(&card, timestamp);
+        }
+        if source != ReviewSource::MarkedKnown {
+            self.bury_siblings(&card, timestamp);
+        }
+        self.update_known_lexemes(&card);
+        self.cards.get(&card)
     }
 
-    fn update_daily_streak(&mut self, timestamp: &DateTime<Utc>) {
-        match &self.daily_streak {
-            None => {
-                // First review ever
-                self.daily_streak = Some(DailyStreak {
-                    streak_start: *timestamp,
-                    last_review_time: *timestamp,
-                });
+    fn apply_sentence_review(
+        &mut self,
+        review: &SentenceReviewIndicator,
+        timestamp: DateTime<Utc>,
+    ) {
+        // Each direction has its own cards, so recognizing a word doesn't count as being able to produce it
+        let (challenge_sentence, result, card, source): (_, _, fn(_) -> _, _) = match review {
+            SentenceReviewIndicator::TargetToNative {
+                challenge_sentence,
+                result,
+            } => (
+                challenge_sentence,
+                result,
+                |lexeme| CardIndicator::TargetLanguage { lexeme },
+                ReviewSource::TranslationChallenge,
+            ),
+            SentenceReviewIndicator::NativeToTarget {
+                challenge_sentence,
+                result,
+            } => (
+                challenge_sentence,
+                result,
+                |lexeme| CardIndicator::NativeToTarget { lexeme },
+                ReviewSource::ProductionChallenge,
+            ),
+        };
+        match result {
+            SentenceReviewResult::Perfect {} => {
+                if let Some(challenge_sentence) = self.language_pack.rodeo.get(challenge_sentence) {
+                    if let Some(lexemes) = self
+                        .language_pack
+                        .sentences_to_lexemes
+                        .get(&challenge_sentence)
+                    {
+                        let sentence_review_count = self
+                            .sentences_reviewed
+                            .entry(challenge_sentence)
+                            .or_insert(0);
+                        *sentence_review_count += 1;
+
+                        let lexemes = lexemes.clone();
+                        for lexeme in lexemes {
+                            self.log_review(
+                                card(lexeme),
+                                Rating::Good,
+                                timestamp,
+                                source,
+                                Some(challenge_sentence),
+                            );
+                        }
+                    }
+                }
             }
-            Some(streak) => {
-                if timestamp > &streak.last_review_time {
-                    // This is a newer review
-                    let hours_since_last = (*timestamp - streak.last_review_time).num_hours();
-
-                    if hours_since_last <= 30 {
-                        // Within 30 hours, continue streak
-                        self.daily_streak = Some(DailyStreak {
-                            streak_start: streak.streak_start,
-                            last_review_time: *timestamp,
-                        });
-                    } else {
-                        // More than 30 hours, start new streak
-                        self.daily_streak = Some(DailyStreak {
-                            streak_start: *timestamp,
-                            last_review_time: *timestamp,
-                        });
+            SentenceReviewResult::Wrong {
+                submission: _,
+                lexemes_remembered,
+                lexemes_forgotten,
+            } => {
+                let challenge_sentence = self.language_pack.rodeo.get(challenge_sentence);
+                for lexeme in lexemes_remembered {
+                    if let Some(lexeme) = lexeme.get_interned(&self.language_pack.rodeo) {
+                        self.log_review(
+                            card(lexeme),
+                            Rating::Good,
+                            timestamp,
+                            source,
+                            challenge_sentence,
+                        );
+                    }
+                }
+
+                for lexeme in lexemes_forgotten {
+                    if let Some(lexeme) = lexeme.get_interned(&self.language_pack.rodeo) {
+                        self.log_review(
+                            card(lexeme),
+                            Rating::Again,
+                            timestamp,
+                            source,
+                            challenge_sentence,
+                        );
                     }
                 }
-                // If timestamp <= last_review_time, it's an old event being processed, ignore
             }
         }
     }
 
-    fn get_card(&self, index: usize) -> Option<(CardIndicator<Spur>, Card)> {
-        let (card_indicator, card_data) = self.cards.get_index(index)?;
-
-        let card = Card {
-            content: match card_indicator {
+    fn is_in_language_pack(&self, card: &CardIndicator<Spur>) -> bool {
+        match card {
+            CardIndicator::TargetLanguage { lexeme } | CardIndicator::NativeToTarget { lexeme } => {
+                self.language_pack.word_frequencies.contains_key(lexeme)
+            }
+            CardIndicator::ListeningHomophonous { pronunciation } => self
+                .language_pack
+                .pronunciation_to_words
+                .contains_key(pronunciation),
+        }
+    }
+
+    /// Apply every pending settings change made at or before `timestamp`.
+    fn apply_settings_until(&mut self, timestamp: DateTime<Utc>) {
+        let mut fsrs_changed = false;
+        let mut days_changed = false;
+        while let Some(change) = self
+            .pending_settings
+            .front()
+            .filter(|change| change.timestamp <= timestamp)
+        {
+            match change.event {
+                LanguageSettingsEventContent::SetFsrsSettings(_) => fsrs_changed = true,
+                LanguageSettingsEventContent::SetDaySettings(_) => days_changed = true,
+                _ => {}
+            }
+            self.settings.apply(&change.event);
+            self.pending_settings.pop_front();
+        }
+        if fsrs_changed {
+            self.fsrs = FSRS::new(self.settings.fsrs.parameters());
+            self.reschedule_review_cards();
+        } else if days_changed {
+            self.rebuild_due_forecast();
+        }
+    }
+
+    /// Recompute the due date of every card in the review state using the current FSRS parameters.
+    /// Fuzz is skipped here so that rescheduling is the same on every device.
+    fn reschedule_review_cards(&mut self) {
+        let parameters = rs_fsrs::Parameters {
+            enable_fuzz: false,
+            ..self.settings.fsrs.parameters()
+        };
+        for card_data in self.cards.values_mut() {
+            let card = &mut card_data.fsrs_card;
+            if card.state != rs_fsrs::State::Review {
+                continue;
+            }
+            let interval = parameters.next_interval(card.stability, 0) as i64;
+            card.scheduled_days = interval;
+            card.due = card.last_review + chrono::Duration::days(interval);
+        }
+        self.rebuild_due_forecast();
+    }
+
+    fn rebuild_due_forecast(&mut self) {
+        self.due_forecast = workload::DueForecast::default();
+        for card_data in self.cards.values() {
+            self.due_forecast
+                .add(&card_data.fsrs_card, &self.settings.day);
+        }
+    }
+
+    /// An event about a single card, if that card is in the deck.
+    fn card_event(
+        &self,
+        card: CardIndicator<String>,
+        content: impl FnOnce(CardIndicator<String>) -> LanguageEventContent,
+    ) -> Option<DeckEvent> {
+        let indicator = card.get_interned(&self.language_pack.rodeo)?;
+        self.cards.contains_key(&indicator).then(|| {
+            DeckEvent::Language(LanguageEvent {
+                language: self.target_language,
+                content: content(card),
+            })
+        })
+    }
+
+    fn update_daily_streak(&mut self, day: chrono::NaiveDate) {
+        match &mut self.daily_streak {
+            None => {
+                // First review ever
+                self.daily_streak = Some(DailyStreak {
+                    days: 1,
+                    last_review_day: day,
+                    freezes: 0,
+                });
+            }
+            Some(streak) => {
+                // If it's the same day (or an old event being processed), there's nothing to do
+                if day > streak.last_review_day {
+                    let days_missed = streak.days_missed(day);
+                    if days_missed <= streak.freezes {
+                        // Continue the streak, using up a freeze for each day missed
+                        streak.freezes -= days_missed;
+                        streak.days += 1;
+                        if streak.days % 7 == 0 {
+                            streak.freezes = (streak.freezes + 1).min(MAX_STREAK_FREEZES);
+                        }
+                    } else {
+                        // Start a new streak
+                        streak.days = 1;
+                    }
+                    streak.last_review_day = day;
+                }
+            }
+        }
+    }
+
+    fn get_card(&self, index: usize) -> Option<(CardIndicator<Spur>, Card)> {
+        let (card_indicator, card_data) = self.cards.get_index(index)?;
+
+        let card = Card {
+            content: match card_indicator {
+                // Production cards have the same content. `Challenge::FlashCardReview::is_production` says to show
+                // the meaning on the front and the word on the back.
                 CardIndicator::TargetLanguage {
                     lexeme: Lexeme::Heteronym(heteronym),
+                }
+                | CardIndicator::NativeToTarget {
+                    lexeme: Lexeme::Heteronym(heteronym),
                 } => {
                     let Some(entry) = self.language_pack.dictionary.get(heteronym).cloned() else {
                         panic!(
@@ -1282,6 +1750,9 @@ impl Deck {
                 }
                 CardIndicator::TargetLanguage {
                     lexeme: Lexeme::Multiword(multiword_term),
+                }
+                | CardIndicator::NativeToTarget {
+                    lexeme: Lexeme::Multiword(multiword_term),
                 } => {
                     let Some(entry) = self.language_pack.phrasebook.get(multiword_term).cloned()
                     else {
@@ -1311,17 +1782,10 @@ impl Deck {
                     };
                     let possible_words = possible_words.into_iter().collect::<BTreeSet<_>>();
 
-                    let words_known = self
-                        .cards
-                        .keys()
-                        .filter_map(CardIndicator::target_language)
-                        .filter_map(Lexeme::heteronym)
-                        .map(|heteronym| heteronym.word)
-                        .collect::<BTreeSet<_>>();
                     // figure out which of those words the user knows
                     let possible_words = possible_words
                         .iter()
-                        .map(|word| (words_known.contains(word), *word))
+                        .map(|word| (self.has_word_card(word), *word))
                         .collect();
                     CardContent::Listening {
                         pronunciation: *pronunciation,
@@ -1339,100 +1803,151 @@ impl Deck {
         required_lexeme: &Lexeme<Spur>,
         sentences_reviewed: &BTreeMap<Spur, u32>,
         language_pack: &LanguagePack,
+        now: DateTime<Utc>,
     ) -> Option<ComprehensibleSentence> {
-        // Get all words that are in "review" state or the target word
-        let mut comprehensible_words: BTreeSet<Lexeme<Spur>> = self
-            .cards
-            .iter()
-            .filter_map(|(card_indicator, card_data)| match card_indicator {
-                CardIndicator::TargetLanguage { lexeme } => Some((lexeme, card_data)),
-                CardIndicator::ListeningHomophonous { .. } => None,
-            })
-            .filter(|(_, card_data)| matches!(card_data.fsrs_card.state, rs_fsrs::State::Review))
-            .map(|(target_language_word, _)| *target_language_word)
-            .collect();
+        let settings = &self.settings.sentence_selection;
 
-        // Add the target word to comprehensible words
-        comprehensible_words.insert(*required_lexeme);
+        let lexeme_index = &language_pack.lexeme_index;
+        // The target word doesn't need to be known already
+        let required_unknown = u16::from(!self.is_known_lexeme(required_lexeme));
+        // Words the user has only started learning can be let in one per sentence, if the settings allow it
+        let unknown_allowed = u16::from(settings.allow_unmature_word);
 
         // Search through all sentences
         let candidate_sentences = language_pack
             .sentences_containing_lexeme_index
             .get(required_lexeme)?;
 
-        let mut possible_sentences = Vec::new();
+        let mut best_sentence: Option<(f64, Spur)> = None;
 
         // Warning: this loop is HOT!
         'checkSentences: for sentence in candidate_sentences {
+            let Some(unknown) = self.known_lexemes.unknown_count(lexeme_index, sentence) else {
+                continue;
+            };
+            if unknown.saturating_sub(required_unknown) > unknown_allowed {
+                continue; // Early exit!
+            }
             let Some(lexemes) = language_pack.sentences_to_all_lexemes.get(sentence) else {
                 continue;
             };
 
+            let mut unmature_word = None;
+            let mut other_due_words = Vec::new();
             for lexeme in lexemes {
-                if !comprehensible_words.contains(lexeme) {
-                    continue 'checkSentences; // Early exit!
+                if lexeme == required_lexeme {
+                    continue;
+                }
+                let card_data = self
+                    .cards
+                    .get(&CardIndicator::TargetLanguage { lexeme: *lexeme });
+                if self.is_known_lexeme(lexeme) {
+                    if card_data.is_some_and(|card_data| card_data.fsrs_card.due <= now)
+                        && !other_due_words.contains(&lexeme)
+                    {
+                        other_due_words.push(lexeme);
+                    }
+                } else if card_data.is_some() {
+                    unmature_word = Some(lexeme);
+                } else {
+                    // Not a word the user has started learning at all
+                    continue 'checkSentences;
                 }
             }
 
-            possible_sentences.push(sentence);
+            let candidate = sentence_selection::SentenceCandidate {
+                length: language_pack
+                    .sentences_to_literals
+                    .get(sentence)
+                    .map_or(0, |literals| {
+                        literals
+                            .iter()
+                            .filter(|literal| literal.heteronym.is_some())
+                            .count()
+                    }),
+                other_due_cards: other_due_words.len(),
+                times_reviewed: sentences_reviewed.get(sentence).copied().unwrap_or(0),
+                days_since_seen: self
+                    .sentences_last_seen
+                    .get(sentence)
+                    .map(|seen| (now - *seen).num_seconds() as f64 / 86400.0),
+                unmature_words: usize::from(unmature_word.is_some()),
+            };
+            let score = settings.score(&candidate);
+            if best_sentence.is_none_or(|(best_score, _)| score > best_score) {
+                best_sentence = Some((score, *sentence));
+            }
         }
 
-        if !possible_sentences.is_empty() {
-            possible_sentences.sort_by_key(|sentence| {
-                let sentence_review_count = sentences_reviewed.get(sentence).unwrap_or(&0);
-                *sentence_review_count
-            });
-            let target_language = **possible_sentences.first()?;
-
-            let lexemes = language_pack
-                .sentences_to_all_lexemes
-                .get(&target_language)?;
-
-            let unique_target_language_lexemes = {
-                let mut unique_target_language_lexemes = vec![];
-                let mut lexemes_set = BTreeSet::new();
-
-                for lexeme in lexemes {
-                    if !lexemes_set.contains(&lexeme) {
-                        unique_target_language_lexemes.push(*lexeme);
-                        lexemes_set.insert(lexeme);
-                    }
-                }
-                unique_target_language_lexemes
-            };
+        let (_, target_language) = best_sentence?;
 
-            let native_languages = language_pack
-                .translations
-                .get(&target_language)
-                .unwrap()
-                .clone();
+        let lexemes = language_pack
+            .sentences_to_all_lexemes
+            .get(&target_language)?;
 
-            let target_language_literals = language_pack
-                .sentences_to_literals
-                .get(&target_language)
-                .unwrap()
-                .clone();
+        let unique_target_language_lexemes = {
+            let mut unique_target_language_lexemes = vec![];
+            let mut lexemes_set = BTreeSet::new();
 
-            return Some(ComprehensibleSentence {
-                target_language,
-                target_language_literals,
-                unique_target_language_lexemes,
-                native_languages,
-            });
-        }
+            for lexeme in lexemes {
+                if !lexemes_set.contains(&lexeme) {
+                    unique_target_language_lexemes.push(*lexeme);
+                    lexemes_set.insert(lexeme);
+                }
+            }
+            unique_target_language_lexemes
+        };
+
+        let native_languages = language_pack
+            .translations
+            .get(&target_language)
+            .unwrap()
+            .clone();
+
+        let target_language_literals = language_pack
+            .sentences_to_literals
+            .get(&target_language)
+            .unwrap()
+            .clone();
 
-        None
+        Some(ComprehensibleSentence {
+            target_language,
+            target_language_literals,
+            unique_target_language_lexemes,
+            native_languages,
+        })
     }
 
     pub fn next_unknown_cards(&self, card_type: Option<CardType>) -> NextCardsIterator<'_> {
         let permitted_types = match card_type {
             Some(CardType::TargetLanguage) => vec![ChallengeType::Text],
             Some(CardType::Listening) => vec![ChallengeType::Listening],
+            Some(CardType::NativeToTarget) => vec![ChallengeType::Production],
             None => vec![ChallengeType::Text, ChallengeType::Listening],
         };
         NextCardsIterator::new(self, permitted_types)
     }
 }
+
+fn parse_rating(rating: &str) -> Option<Rating> {
+    match rating {
+        "again" => Some(Rating::Again),
+        "hard" => Some(Rating::Hard),
+        "good" => Some(Rating::Good),
+        "easy" => Some(Rating::Easy),
+        _ => None,
+    }
+}
+
+fn state_name(state: rs_fsrs::State) -> &'static str {
+    match state {
+        rs_fsrs::State::New => "new",
+        rs_fsrs::State::Learning => "learning",
+        rs_fsrs::State::Review => "review",
+        rs_fsrs::State::Relearning => "relearning",
+    }
+}
+
 #[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
 impl Deck {
     /// First, the frontend calls get_all_cards_summary to get a view of what cards are due and what cards are going to be due in the future.
@@ -1446,12 +1961,11 @@ impl Deck {
             .map(|(card_indicator, card_data)| CardSummary {
                 card_indicator: card_indicator.resolve(rodeo),
                 due_timestamp_ms: card_data.fsrs_card.due.timestamp_millis() as f64,
-                state: match card_data.fsrs_card.state {
-                    rs_fsrs::State::New => "new".to_string(),
-                    rs_fsrs::State::Learning => "learning".to_string(),
-                    rs_fsrs::State::Review => "review".to_string(),
-                    rs_fsrs::State::Relearning => "relearning".to_string(),
-                },
+                state: state_name(card_data.fsrs_card.state).to_string(),
+                suspended: card_data.suspended,
+                buried_until_ms: card_data
+                    .buried_until
+                    .map(|buried_until| buried_until.timestamp_millis() as f64),
             })
             .collect();
 
@@ -1468,11 +1982,23 @@ impl Deck {
         let mut due_cards = vec![];
         let mut future_cards = vec![];
         let mut due_but_banned_cards = vec![];
+        let mut suspended_cards = vec![];
+        let mut buried_cards = vec![];
+        let mut due_over_limit_cards = vec![];
 
         let no_listening_cards = banned_challenge_types.contains(&ChallengeType::Listening);
         let no_text_cards = banned_challenge_types.contains(&ChallengeType::Text);
+        let no_production_cards = banned_challenge_types.contains(&ChallengeType::Production);
 
         for (index, (card, card_data)) in self.cards.iter().enumerate() {
+            if card_data.suspended {
+                suspended_cards.push(index);
+                continue;
+            }
+            if card_data.is_buried(now) {
+                buried_cards.push(index);
+                continue;
+            }
             let due_date = card_data.fsrs_card.due;
             if due_date <= now {
                 match card {
@@ -1482,8 +2008,12 @@ impl Deck {
                     CardIndicator::ListeningHomophonous { .. } if no_listening_cards => {
                         due_but_banned_cards.push(index);
                     }
+                    CardIndicator::NativeToTarget { .. } if no_production_cards => {
+                        due_but_banned_cards.push(index);
+                    }
                     CardIndicator::TargetLanguage { .. }
-                    | CardIndicator::ListeningHomophonous { .. } => due_cards.push(index),
+                    | CardIndicator::ListeningHomophonous { .. }
+                    | CardIndicator::NativeToTarget { .. } => due_cards.push(index),
                 }
             } else {
                 future_cards.push(index);
@@ -1496,6 +2026,33 @@ impl Deck {
             ordered_float::NotNan::new(card.due_timestamp_ms()).unwrap()
         });
 
+        // After a long break, put the overdue reviews most worth doing first, and spread them over the coming days
+        let catch_up = self.catch_up(&mut due_cards, now);
+        let mut workload = self.settings.workload.clone();
+        if catch_up.is_some() {
+            workload.reviews_per_day = self.catch_up_reviews_per_day();
+        }
+
+        // Hold back whatever's over today's limits. Learning and relearning steps are always shown, so a card added or
+        // forgotten today isn't put off until tomorrow.
+        let (mut new_cards_remaining, mut reviews_remaining) = self
+            .daily_counts
+            .remaining(&workload, self.settings.day.day_of(now));
+        due_cards.retain(|card_index| {
+            let (_, card) = self.cards.get_index(*card_index).unwrap();
+            let remaining = match card.fsrs_card.state {
+                rs_fsrs::State::New => &mut new_cards_remaining,
+                rs_fsrs::State::Review => &mut reviews_remaining,
+                rs_fsrs::State::Learning | rs_fsrs::State::Relearning => return true,
+            };
+            if *remaining == 0 {
+                due_over_limit_cards.push(*card_index);
+                return false;
+            }
+            *remaining -= 1;
+            true
+        });
+
         due_but_banned_cards.sort_by_key(|card_index| {
             let (_, card) = self.cards.get_index(*card_index).unwrap();
             ordered_float::NotNan::new(card.due_timestamp_ms()).unwrap()
@@ -1510,6 +2067,10 @@ impl Deck {
             due_cards,
             due_but_banned_cards,
             future_cards,
+            suspended_cards,
+            buried_cards,
+            due_over_limit_cards,
+            catch_up,
         }
     }
 
@@ -1581,7 +2142,8 @@ impl Deck {
             .iter()
             .filter_map(|(card_indicator, card_data)| match card_indicator {
                 CardIndicator::TargetLanguage { lexeme } => Some((lexeme, card_data)),
-                CardIndicator::ListeningHomophonous { .. } => None,
+                CardIndicator::ListeningHomophonous { .. }
+                | CardIndicator::NativeToTarget { .. } => None,
             })
             .filter_map(|(lexeme, card_data)| {
                 if card_data.fsrs_card.state != rs_fsrs::State::New {
@@ -1607,25 +2169,61 @@ impl Deck {
 
     #[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
     pub fn get_daily_streak(&self) -> u32 {
+        let today = self.settings.day.day_of(Utc::now());
         match &self.daily_streak {
+            Some(streak) if streak.days_missed(today) <= streak.freezes => streak.days,
+            // Streak is broken
+            Some(_) | None => 0,
+        }
+    }
+
+    /// How many streak freezes the user has left, after covering any days they've missed since their last review.
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+    pub fn get_streak_freezes(&self) -> u32 {
+        let today = self.settings.day.day_of(Utc::now());
+        match &self.daily_streak {
+            Some(streak) => streak.freezes.saturating_sub(streak.days_missed(today)),
             None => 0,
-            Some(streak) => {
-                let now = chrono::Utc::now();
-                let hours_since_last = (now - streak.last_review_time).num_hours();
-
-                if hours_since_last <= 30 {
-                    // Streak is active (reviewed within last 30 hours)
-                    (streak.last_review_time.date_naive() - streak.streak_start.date_naive())
-                        .num_days() as u32
-                        + 1
-                } else {
-                    // Streak is broken
-                    0
-                }
-            }
         }
     }
 
+    /// A settings event recording the user's current UTC offset (positive east of UTC), if it has changed.
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+    pub fn update_utc_offset(&self, utc_offset_minutes: i32) -> Option<DeckSettingsEvent> {
+        if self.settings.day.utc_offset_minutes == utc_offset_minutes {
+            return None;
+        }
+        Some(DeckSettingsEvent::Language(
+            deck_settings::LanguageSettingsEvent {
+                language: self.target_language,
+                content: LanguageSettingsEventContent::SetDaySettings(deck_settings::DaySettings {
+                    utc_offset_minutes,
+                    ..self.settings.day
+                }),
+            },
+        ))
+    }
+
+    /// Whether daily limits and load balancing have been turned on.
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+    pub fn daily_limits_enabled(&self) -> bool {
+        self.settings.workload != deck_settings::WorkloadSettings::default()
+    }
+
+    /// A settings event turning the recommended daily limits and load balancing on or off. Turning them on only
+    /// affects reviews from then on, since settings events apply from their timestamp.
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+    pub fn set_daily_limits(&self, enabled: bool) -> DeckSettingsEvent {
+        DeckSettingsEvent::Language(deck_settings::LanguageSettingsEvent {
+            language: self.target_language,
+            content: LanguageSettingsEventContent::SetWorkloadSettings(if enabled {
+                deck_settings::WorkloadSettings::recommended()
+            } else {
+                deck_settings::WorkloadSettings::default()
+            }),
+        })
+    }
+
     #[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
     pub fn add_card_options(&self) -> AddCardOptions {
         AddCardOptions {
@@ -1643,6 +2241,12 @@ impl Deck {
                         .count() as u32,
                     CardType::Listening,
                 ),
+                (
+                    self.next_unknown_cards(Some(CardType::NativeToTarget))
+                        .take(5)
+                        .count() as u32,
+                    CardType::NativeToTarget,
+                ),
             ],
         }
     }
@@ -1682,6 +2286,26 @@ impl Deck {
         })
     }
 
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+    pub fn suspend_card(&self, card: CardIndicator<String>) -> Option<DeckEvent> {
+        self.card_event(card, |card| LanguageEventContent::SuspendCard { card })
+    }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+    pub fn unsuspend_card(&self, card: CardIndicator<String>) -> Option<DeckEvent> {
+        self.card_event(card, |card| LanguageEventContent::UnsuspendCard { card })
+    }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+    pub fn bury_card(&self, card: CardIndicator<String>) -> Option<DeckEvent> {
+        self.card_event(card, |card| LanguageEventContent::BuryCard { card })
+    }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+    pub fn remove_card(&self, card: CardIndicator<String>) -> Option<DeckEvent> {
+        self.card_event(card, |card| LanguageEventContent::RemoveCard { card })
+    }
+
     #[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
     pub fn translate_sentence_perfect(&self, challenge_sentence: String) -> Option<DeckEvent> {
         Some(DeckEvent::Language(LanguageEvent {
@@ -1814,6 +2438,12 @@ pub struct ReviewInfo {
     due_cards: Vec<usize>,
     due_but_banned_cards: Vec<usize>,
     future_cards: Vec<usize>,
+    suspended_cards: Vec<usize>,
+    buried_cards: Vec<usize>,
+    /// Due, but over today's new card or review limit.
+    due_over_limit_cards: Vec<usize>,
+    /// Set while there's a backlog of overdue reviews to catch up on.
+    catch_up: Option<catch_up::CatchUp>,
 }
 
 #[derive(tsify::Tsify, serde::Serialize, serde::Deserialize, Debug, Clone)]
@@ -1825,9 +2455,20 @@ pub enum Challenge<S> {
         audio: AudioRequest,
         is_new: bool,
         listening_prefix: Option<String>,
+        /// The user keeps forgetting this card, so it's shown as a flashcard rather than in a sentence.
+        is_leech: bool,
+        /// A production card, so the front shows the meaning and the user has to come up with the word on the back.
+        is_production: bool,
     },
     TranslateComprehensibleSentence(TranslateComprehensibleSentence<S>),
     TranscribeComprehensibleSentence(TranscribeComprehensibleSentence<S>),
+    TranslateToTargetLanguage(production::TranslateToTargetLanguage<S>),
+    /// Only used for cards that have been reviewed before, alternating with `TranslateComprehensibleSentence`.
+    ClozeComprehensibleSentence(cloze::ClozeComprehensibleSentence<S>),
+    /// Only given out by `get_multiple_choice_challenge_for_card`.
+    MultipleChoice(multiple_choice::MultipleChoice<S>),
+    /// Only given out by `get_speaking_challenge_for_card`.
+    SpeakComprehensibleSentence(speaking::SpeakComprehensibleSentence<S>),
 }
 
 impl<S> Challenge<S> {
@@ -1840,6 +2481,16 @@ impl<S> Challenge<S> {
             Challenge::TranscribeComprehensibleSentence(transcribe_comprehensible_sentence) => {
                 transcribe_comprehensible_sentence.audio.clone()
             }
+            Challenge::TranslateToTargetLanguage(translate_to_target_language) => {
+                translate_to_target_language.audio.clone()
+            }
+            Challenge::ClozeComprehensibleSentence(cloze_comprehensible_sentence) => {
+                cloze_comprehensible_sentence.audio.clone()
+            }
+            Challenge::MultipleChoice(multiple_choice) => multiple_choice.audio.clone(),
+            Challenge::SpeakComprehensibleSentence(speak_comprehensible_sentence) => {
+                speak_comprehensible_sentence.audio.clone()
+            }
         }
     }
 }
@@ -1853,12 +2504,16 @@ impl Challenge<Spur> {
                 audio,
                 is_new,
                 listening_prefix,
+                is_leech,
+                is_production,
             } => Challenge::FlashCardReview {
                 indicator: indicator.resolve(rodeo),
                 content: content.resolve(rodeo),
                 audio: audio.clone(),
                 is_new: *is_new,
                 listening_prefix: listening_prefix.clone(),
+                is_leech: *is_leech,
+                is_production: *is_production,
             },
             Challenge::TranslateComprehensibleSentence(translate_comprehensible_sentence) => {
                 Challenge::TranslateComprehensibleSentence(
@@ -1870,6 +2525,18 @@ impl Challenge<Spur> {
                     transcribe_comprehensible_sentence.resolve(rodeo),
                 )
             }
+            Challenge::TranslateToTargetLanguage(translate_to_target_language) => {
+                Challenge::TranslateToTargetLanguage(translate_to_target_language.resolve(rodeo))
+            }
+            Challenge::ClozeComprehensibleSentence(cloze_comprehensible_sentence) => {
+                Challenge::ClozeComprehensibleSentence(cloze_comprehensible_sentence.resolve(rodeo))
+            }
+            Challenge::MultipleChoice(multiple_choice) => {
+                Challenge::MultipleChoice(multiple_choice.resolve(rodeo))
+            }
+            Challenge::SpeakComprehensibleSentence(speak_comprehensible_sentence) => {
+                Challenge::SpeakComprehensibleSentence(speak_comprehensible_sentence.resolve(rodeo))
+            }
         }
     }
 }
@@ -1879,33 +2546,21 @@ impl Challenge<Spur> {
 pub enum ChallengeType {
     Text,
     Listening,
+    Production,
 }
 
-#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
 impl ReviewInfo {
-    fn get_listening_prefix(language: Language) -> &'static str {
-        match language {
-            Language::French => "Le mot est",
-            Language::Spanish => "La palabra es",
-            Language::English => "The word is",
-            Language::Korean => "단어는",
-        }
-    }
-
-    #[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
-    pub fn get_challenge_for_card(
-        &self,
-        deck: &Deck,
-        card_index: usize,
-    ) -> Option<Challenge<String>> {
+    /// A plain flashcard for the card at `card_index`, whatever state it's in.
+    pub(crate) fn get_flashcard(deck: &Deck, card_index: usize) -> Option<Challenge<Spur>> {
+        let (_, card_data) = deck.cards.get_index(card_index)?;
         let (card_indicator, card) = deck.get_card(card_index)?;
         let language_pack = &deck.language_pack;
 
-        // If we can't find a suitable challenge, we'll return a flashcard challenge. Let's construct it here
         let listening_prefix = matches!(&card.content, CardContent::Listening { .. })
             .then(|| Self::get_listening_prefix(deck.target_language).to_string());
+        let is_production = matches!(card_indicator, CardIndicator::NativeToTarget { .. });
 
-        let flashcard = Challenge::<Spur>::FlashCardReview {
+        Some(Challenge::FlashCardReview {
             audio: match &card.content {
                 CardContent::Heteronym(heteronym, _) => AudioRequest {
                     request: TtsRequest {
@@ -1947,18 +2602,54 @@ impl ReviewInfo {
             indicator: card_indicator,
             content: card.content.clone(),
             is_new: card.fsrs_card.state == rs_fsrs::State::New,
-            listening_prefix: listening_prefix.clone(),
-        };
+            listening_prefix,
+            is_leech: deck.settings.leeches.is_leech(card_data),
+            is_production,
+        })
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+impl ReviewInfo {
+    fn get_listening_prefix(language: Language) -> &'static str {
+        match language {
+            Language::French => "Le mot est",
+            Language::Spanish => "La palabra es",
+            Language::English => "The word is",
+            Language::Korean => "단어는",
+        }
+    }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+    pub fn get_challenge_for_card(
+        &self,
+        deck: &Deck,
+        card_index: usize,
+    ) -> Option<Challenge<String>> {
+        let now = Utc::now();
+        let (_, card_data) = deck.cards.get_index(card_index)?;
+        if card_data.suspended || card_data.is_buried(now) {
+            return None;
+        }
+        let (card_indicator, card) = deck.get_card(card_index)?;
+        let language_pack = &deck.language_pack;
+
+        let is_production = matches!(card_indicator, CardIndicator::NativeToTarget { .. });
+        let is_leech = deck.settings.leeches.is_leech(card_data);
 
-        let challenge: Challenge<Spur> = if card.fsrs_card.state == rs_fsrs::State::New {
+        // If we can't find a suitable challenge, we'll return a flashcard challenge
+        let flashcard = Self::get_flashcard(deck, card_index)?;
+
+        let challenge: Challenge<Spur> = if card.fsrs_card.state == rs_fsrs::State::New || is_leech
+        {
             flashcard
+        } else if let Some(lexeme) = card.content.lexeme()
+            && is_production
+        {
+            deck.get_production_challenge(&lexeme, now)
+                .map(Challenge::TranslateToTargetLanguage)
+                .unwrap_or(flashcard)
         } else if let Some(pronunciation) = card.content.pronunciation() {
-            let known_words: BTreeSet<Lexeme<Spur>> = deck
-                .cards
-                .keys()
-                .filter_map(CardIndicator::target_language)
-                .cloned()
-                .collect();
             let mut heteronyms = language_pack
                 .pronunciation_to_words
                 .get(&pronunciation)
@@ -1972,7 +2663,11 @@ impl ReviewInfo {
                         .unwrap()
                         .clone()
                 })
-                .filter(|heteronym| known_words.contains(&Lexeme::Heteronym(*heteronym)))
+                .filter(|heteronym| {
+                    deck.cards.contains_key(&CardIndicator::TargetLanguage {
+                        lexeme: Lexeme::Heteronym(*heteronym),
+                    })
+                })
                 .collect::<Vec<_>>();
             heteronyms.sort_by_key(|heteronym| deck.words_listened_to.get(heteronym).unwrap_or(&0));
 
@@ -1983,28 +2678,14 @@ impl ReviewInfo {
                         &Lexeme::Heteronym(*heteronym),
                         &deck.sentences_reviewed,
                         language_pack,
+                        now,
                     )?;
                     Some((*heteronym, sentence))
                 })
                 .next()
             {
-                let parts = sentence
-                    .target_language_literals
-                    .into_iter()
-                    .map(|literal| {
-                        if let Some(ref heteronym) = literal.heteronym
-                            && heteronym == &target_heteronym
-                        {
-                            transcription_challenge::Part::AskedToTranscribe {
-                                parts: vec![literal.resolve(&language_pack.rodeo)],
-                            }
-                        } else {
-                            transcription_challenge::Part::Provided {
-                                part: literal.resolve(&language_pack.rodeo),
-                            }
-                        }
-                    })
-                    .collect();
+                let parts =
+                    deck.dictation_parts(target_heteronym, &sentence.target_language_literals);
                 Challenge::TranscribeComprehensibleSentence(TranscribeComprehensibleSentence {
                     target_language: sentence.target_language,
                     native_language: *sentence.native_languages.first().unwrap(),
@@ -2024,16 +2705,25 @@ impl ReviewInfo {
                 flashcard
             }
         } else if let Some(lexeme) = card.content.lexeme() {
-            if let Some(ComprehensibleSentence {
+            let sentence = deck.get_comprehensible_sentence_containing(
+                &lexeme,
+                &deck.sentences_reviewed,
+                language_pack,
+                now,
+            );
+            if let Some(sentence) = sentence.as_ref()
+                && let Lexeme::Heteronym(heteronym) = lexeme
+                // Alternate between translating the sentence and filling in the word
+                && card_data.reviews.len() % 2 == 1
+            {
+                Challenge::ClozeComprehensibleSentence(deck.cloze_challenge(heteronym, sentence))
+            } else if let Some(ComprehensibleSentence {
                 target_language,
                 target_language_literals,
                 unique_target_language_lexemes,
                 native_languages,
-            }) = deck.get_comprehensible_sentence_containing(
-                &lexeme,
-                &deck.sentences_reviewed,
-                language_pack,
-            ) {
+            }) = sentence
+            {
                 let unique_target_language_lexeme_definitions = unique_target_language_lexemes
                     .iter()
                     .map(|lexeme| {
@@ -2100,9 +2790,25 @@ impl ReviewInfo {
 }
 
 impl CardData {
+    fn new(added_at: DateTime<Utc>) -> Self {
+        CardData {
+            fsrs_card: rs_fsrs::Card::new(),
+            reviews: Vec::new(),
+            cram_reviews: Vec::new(),
+            suspended: false,
+            buried_until: None,
+            added_at,
+        }
+    }
+
     pub fn due_timestamp_ms(&self) -> f64 {
         self.fsrs_card.due.timestamp_millis() as f64
     }
+
+    fn is_buried(&self, now: DateTime<Utc>) -> bool {
+        self.buried_until
+            .is_some_and(|buried_until| buried_until > now)
+    }
 }
 
 #[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
@@ -2126,6 +2832,26 @@ impl ReviewInfo {
     pub fn total_count(&self) -> usize {
         self.due_cards.len() + self.future_cards.len()
     }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen(getter))]
+    pub fn suspended_count(&self) -> usize {
+        self.suspended_cards.len()
+    }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen(getter))]
+    pub fn buried_count(&self) -> usize {
+        self.buried_cards.len()
+    }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen(getter))]
+    pub fn due_over_limit_count(&self) -> usize {
+        self.due_over_limit_cards.len()
+    }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen(getter))]
+    pub fn catch_up(&self) -> Option<CatchUp> {
+        self.catch_up
+    }
 }
 
 #[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
@@ -2133,6 +2859,8 @@ pub struct CardSummary {
     card_indicator: CardIndicator<String>,
     due_timestamp_ms: f64,
     state: String,
+    suspended: bool,
+    buried_until_ms: Option<f64>,
 }
 
 #[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
@@ -2151,6 +2879,16 @@ impl CardSummary {
     pub fn state(&self) -> String {
         self.state.clone()
     }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen(getter))]
+    pub fn suspended(&self) -> bool {
+        self.suspended
+    }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen(getter))]
+    pub fn buried_until_ms(&self) -> Option<f64> {
+        self.buried_until_ms
+    }
 }
 
 #[wasm_bindgen]
@@ -2205,12 +2943,22 @@ pub async fn autograde_translation(
         )));
     }
 
-    let mut response: autograde::AutoGradeTranslationResponse = response
+    let response: autograde::AutoGradeTranslationResponse = response
         .json()
         .await
         .map_err(|e| JsValue::from_str(&format!("Response parsing error: {e:?}")))?;
+    let response = include_primary_expression(response, primary_expression);
 
-    // make sure the primary expression is in the appropriate array:
+    log::info!("Autograde response: {response:#?}");
+
+    Ok(response)
+}
+
+/// Make sure the primary expression is in the array matching its status.
+fn include_primary_expression(
+    mut response: autograde::AutoGradeTranslationResponse,
+    primary_expression: Lexeme<String>,
+) -> autograde::AutoGradeTranslationResponse {
     if response.primary_expression_status == autograde::Remembered::Forgot
         && !response.expressions_forgot.contains(&primary_expression)
     {
@@ -2222,10 +2970,7 @@ pub async fn autograde_translation(
     {
         response.expressions_remembered.push(primary_expression);
     }
-
-    log::info!("Autograde response: {response:#?}");
-
-    Ok(response)
+    response
 }
 
 #[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
@@ -2396,7 +3141,378 @@ pub fn get_courses() -> Vec<language_utils::Course> {
 
 #[cfg(test)]
 mod tests {
+    use super::*;
     use chrono::Days;
+    use language_utils::{
+        ConsolidatedLanguageData, MultiwordTerms, PartOfSpeech, PhrasebookEntry, SentenceInfo,
+    };
+
+    fn heteronym(word: &str, lemma: &str, pos: PartOfSpeech) -> Heteronym<String> {
+        Heteronym {
+            word: word.to_string(),
+            lemma: lemma.to_string(),
+            pos,
+        }
+    }
+
+    fn sentence(words: &[(&str, &str, Option<Heteronym<String>>)]) -> (String, SentenceInfo) {
+        let text = words
+            .iter()
+            .map(|(text, whitespace, _)| format!("{text}{whitespace}"))
+            .collect::<String>();
+        let info = SentenceInfo {
+            words: words
+                .iter()
+                .map(|(text, whitespace, heteronym)| Literal {
+                    text: text.to_string(),
+                    whitespace: whitespace.to_string(),
+                    heteronym: heteronym.clone(),
+                })
+                .collect(),
+            multiword_terms: MultiwordTerms {
+                high_confidence: vec![],
+                low_confidence: vec![],
+            },
+        };
+        (text, info)
+    }
+
+    /// A tiny French language pack: "le", "chat", "chien", "mange", "mes"/"mais" (homophones), "chez" (a minimal
+    /// pair with "chat") and "il y a".
+    pub(crate) fn test_language_pack() -> Arc<LanguagePack> {
+        let le = heteronym("le", "le", PartOfSpeech::Det);
+        let chat = heteronym("chat", "chat", PartOfSpeech::Noun);
+        let chien = heteronym("chien", "chien", PartOfSpeech::Noun);
+        let mange = heteronym("mange", "manger", PartOfSpeech::Verb);
+        let mes = heteronym("mes", "mon", PartOfSpeech::Det);
+        let mais = heteronym("mais", "mais", PartOfSpeech::Cconj);
+        let chez = heteronym("chez", "chez", PartOfSpeech::Adp);
+        let manges = heteronym("manges", "manger", PartOfSpeech::Verb);
+
+        let sentences = vec![
+            sentence(&[
+                ("Le", " ", Some(le.clone())),
+                ("chat", " ", Some(chat.clone())),
+                ("mange", "", Some(mange.clone())),
+                (".", "", None),
+            ]),
+            sentence(&[
+                ("Le", " ", Some(le.clone())),
+                ("chien", " ", Some(chien.clone())),
+                ("mange", "", Some(mange.clone())),
+                (".", "", None),
+            ]),
+            sentence(&[
+                ("Le", " ", Some(le.clone())),
+                ("chat", " ", Some(chat.clone())),
+                ("mange", " ", Some(mange.clone())),
+                ("mais", " ", Some(mais.clone())),
+                ("le", " ", Some(le.clone())),
+                ("chien", " ", Some(chien.clone())),
+                ("mange", "", Some(mange.clone())),
+                (".", "", None),
+            ]),
+        ];
+        let translations = vec![
+            (sentences[0].0.clone(), vec!["The cat eats.".to_string()]),
+            (sentences[1].0.clone(), vec!["The dog eats.".to_string()]),
+            (
+                sentences[2].0.clone(),
+                vec!["The cat eats but the dog eats.".to_string()],
+            ),
+        ];
+
+        let words = [
+            (le.clone(), 1000, "the", "lə"),
+            (chat.clone(), 500, "cat", "ʃa"),
+            (mange.clone(), 400, "eats", "mɑ̃ʒ"),
+            (chien.clone(), 300, "dog", "ʃjɛ̃"),
+            (mais.clone(), 200, "but", "mɛ"),
+            (mes.clone(), 100, "my", "mɛ"),
+            (chez.clone(), 80, "at", "ʃe"),
+            (manges.clone(), 60, "eat", "mɑ̃ʒ"),
+        ];
+
+        let mut frequencies: Vec<FrequencyEntry<String>> = words
+            .iter()
+            .map(|(heteronym, count, _, _)| FrequencyEntry {
+                lexeme: Lexeme::Heteronym(heteronym.clone()),
+                count: *count,
+            })
+            .collect();
+        frequencies.push(FrequencyEntry {
+            lexeme: Lexeme::Multiword("il y a".to_string()),
+            count: 50,
+        });
+
+        let dictionary = words
+            .iter()
+            .map(|(heteronym, _, native, _)| {
+                (
+                    heteronym.clone(),
+                    DictionaryEntry {
+                        target_language_word: heteronym.word.clone(),
+                        definitions: vec![TargetToNativeWord {
+                            native: native.to_string(),
+                            note: None,
+                            example_sentence_target_language: String::new(),
+                            example_sentence_native_language: String::new(),
+                        }],
+                    },
+                )
+            })
+            .collect();
+
+        let phrasebook = vec![(
+            "il y a".to_string(),
+            PhrasebookEntry {
+                target_language_multi_word_term: "il y a".to_string(),
+                meaning: "there is".to_string(),
+                additional_notes: String::new(),
+                target_language_example: "Il y a un chat.".to_string(),
+                native_language_example: "There is a cat.".to_string(),
+            },
+        )];
+
+        let word_to_pronunciation = words
+            .iter()
+            .map(|(heteronym, _, _, ipa)| (heteronym.word.clone(), ipa.to_string()))
+            .collect::<Vec<_>>();
+        let mut pronunciation_to_words: BTreeMap<String, Vec<String>> = BTreeMap::new();
+        for (word, ipa) in &word_to_pronunciation {
+            pronunciation_to_words
+                .entry(ipa.clone())
+                .or_default()
+                .push(word.clone());
+        }
+
+        let consolidated_language_data = ConsolidatedLanguageData {
+            target_language_sentences: sentences.iter().map(|(text, _)| text.clone()).collect(),
+            translations,
+            nlp_sentences: sentences,
+            dictionary,
+            phrasebook,
+            frequencies,
+            word_to_pronunciation,
+            pronunciation_to_words: pronunciation_to_words.into_iter().collect(),
+            word_packs: vec![(
+                "animals".to_string(),
+                vec![
+                    Lexeme::Heteronym(chat.clone()),
+                    Lexeme::Heteronym(chien.clone()),
+                ],
+            )],
+        };
+
+        Arc::new(LanguagePack::new(ConsolidatedLanguageDataWithCapacity {
+            consolidated_language_data,
+            num_strings: 100,
+            num_string_bytes: 4096,
+        }))
+    }
+
+    pub(crate) fn test_deck() -> Deck {
+        Deck::new(test_language_pack(), Language::French, VecDeque::new())
+    }
+
+    /// A deck whose settings change at the given times, the way they do when its history is replayed.
+    pub(crate) fn test_deck_with_settings(
+        settings: &[(DateTime<Utc>, LanguageSettingsEventContent)],
+    ) -> Deck {
+        let pending_settings = settings
+            .iter()
+            .enumerate()
+            .map(|(index, (timestamp, event))| Timestamped {
+                timestamp: *timestamp,
+                within_device_events_index: index,
+                event: event.clone(),
+            })
+            .collect();
+        Deck::new(test_language_pack(), Language::French, pending_settings)
+    }
+
+    pub(crate) fn apply(deck: Deck, events: &[(DateTime<Utc>, LanguageEventContent)]) -> Deck {
+        events
+            .iter()
+            .enumerate()
+            .fold(deck, |deck, (index, (timestamp, content))| {
+                weapon::AppState::apply_event(
+                    deck,
+                    &Timestamped {
+                        timestamp: *timestamp,
+                        within_device_events_index: index,
+                        event: DeckEvent::Language(LanguageEvent {
+                            language: Language::French,
+                            content: content.clone(),
+                        }),
+                    },
+                )
+            })
+    }
+
+    pub(crate) fn lexeme_card(word: &str) -> CardIndicator<String> {
+        let pack = test_language_pack();
+        pack.word_frequencies
+            .keys()
+            .find(|lexeme| match lexeme {
+                Lexeme::Heteronym(heteronym) => pack.rodeo.resolve(&heteronym.word) == word,
+                Lexeme::Multiword(term) => pack.rodeo.resolve(term) == word,
+            })
+            .map(|lexeme| CardIndicator::TargetLanguage {
+                lexeme: lexeme.resolve(&pack.rodeo),
+            })
+            .unwrap()
+    }
+
+    #[test]
+    fn settings_change_reschedules_cards_on_replay() {
+        use crate::deck_settings::FsrsSettings;
+        use ordered_float::NotNan;
+
+        let start = DateTime::parse_from_rfc3339("2025-01-01T12:00:00Z")
+            .unwrap()
+            .to_utc();
+        let events = [
+            (
+                start,
+                LanguageEventContent::AddCards {
+                    cards: vec![lexeme_card("chat")],
+                },
+            ),
+            (
+                start,
+                LanguageEventContent::ReviewCard {
+                    reviewed: lexeme_card("chat"),
+                    rating: "easy".to_string(),
+                },
+            ),
+        ];
+        let before = apply(test_deck(), &events);
+
+        let change_time = start + chrono::Duration::hours(1);
+        let mut deck = Deck::new(
+            test_language_pack(),
+            Language::French,
+            VecDeque::from([Timestamped {
+                timestamp: change_time,
+                within_device_events_index: 0,
+                event: LanguageSettingsEventContent::SetFsrsSettings(FsrsSettings {
+                    desired_retention: NotNan::new(0.95).unwrap(),
+                    ..Default::default()
+                }),
+            }]),
+        );
+        deck = apply(deck, &events);
+        // the change hasn't been reached yet
+        assert_eq!(
+            deck.cards.values().next().unwrap().fsrs_card.due,
+            before.cards.values().next().unwrap().fsrs_card.due
+        );
+        deck.apply_settings_until(DateTime::<Utc>::MAX_UTC);
+
+        let before_card = &before.cards.values().next().unwrap().fsrs_card;
+        let after_card = &deck.cards.values().next().unwrap().fsrs_card;
+        assert_eq!(before_card.stability, after_card.stability);
+        // a higher desired retention means reviewing sooner
+        assert!(after_card.due < before_card.due);
+        assert!(after_card.due > start);
+    }
+
+    #[test]
+    fn streaks_use_local_days_and_freezes() {
+        use crate::deck_settings::DaySettings;
+
+        let timestamp = |s: &str| DateTime::parse_from_rfc3339(s).unwrap().to_utc();
+        let start = timestamp("2025-01-01T23:00:00Z");
+        let deck = Deck::new(
+            test_language_pack(),
+            Language::French,
+            VecDeque::from([Timestamped {
+                timestamp: start,
+                within_device_events_index: 0,
+                event: LanguageSettingsEventContent::SetDaySettings(DaySettings {
+                    day_start_hour: 4,
+                    utc_offset_minutes: -8 * 60,
+                }),
+            }]),
+        );
+        let event = || LanguageEventContent::AddCards { cards: vec![] };
+
+        // 3pm and 11:30pm on January 1st in UTC-8, which are on different days in UTC, then the following six days
+        let mut events = vec![(start, event())];
+        let evening = timestamp("2025-01-02T07:30:00Z");
+        for day in 0..7 {
+            events.push((evening + chrono::Duration::days(day), event()));
+        }
+        let deck = apply(deck, &events);
+        let streak = deck.daily_streak.as_ref().unwrap();
+        assert_eq!((streak.days, streak.freezes), (7, 1));
+
+        // Missing a day uses up the freeze
+        let deck = apply(deck, &[(evening + chrono::Duration::days(8), event())]);
+        let streak = deck.daily_streak.as_ref().unwrap();
+        assert_eq!((streak.days, streak.freezes), (8, 0));
+
+        // Without a freeze, the streak starts over
+        let deck = apply(deck, &[(evening + chrono::Duration::days(10), event())]);
+        assert_eq!(deck.daily_streak.as_ref().unwrap().days, 1);
+    }
+
+    #[test]
+    fn suspended_and_buried_cards_are_not_due() {
+        let now = Utc::now();
+        let cards = ["le", "chat", "mange"].map(lexeme_card).to_vec();
+        let deck = apply(
+            test_deck(),
+            &[
+                (now, LanguageEventContent::AddCards { cards }),
+                (
+                    now,
+                    LanguageEventContent::SuspendCard {
+                        card: lexeme_card("le"),
+                    },
+                ),
+                (
+                    now,
+                    LanguageEventContent::BuryCard {
+                        card: lexeme_card("chat"),
+                    },
+                ),
+                (
+                    now,
+                    LanguageEventContent::RemoveCard {
+                        card: lexeme_card("mange"),
+                    },
+                ),
+            ],
+        );
+
+        let review_info = deck.get_review_info(vec![]);
+        assert_eq!(review_info.due_count(), 0);
+        assert_eq!(review_info.suspended_count(), 1);
+        assert_eq!(review_info.buried_count(), 1);
+        for index in 0..deck.cards.len() {
+            assert!(review_info.get_challenge_for_card(&deck, index).is_none());
+        }
+        assert!(deck.get_card_detail(lexeme_card("mange")).is_none());
+        assert!(
+            deck.review_card(lexeme_card("mange"), "good".to_string())
+                .is_none()
+        );
+
+        let deck = apply(
+            deck,
+            &[(
+                now,
+                LanguageEventContent::UnsuspendCard {
+                    card: lexeme_card("le"),
+                },
+            )],
+        );
+        let review_info = deck.get_review_info(vec![]);
+        assert_eq!(review_info.due_count(), 1);
+        assert_eq!(review_info.suspended_count(), 0);
+    }
 
     #[test]
     fn test_fsrs() {
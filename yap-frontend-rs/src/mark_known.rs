@@ -0,0 +1,162 @@
+//! "I already know this": put cards straight into a long-interval review state instead of making the user learn them
+//! from scratch.
+
+use chrono::{DateTime, Utc};
+use lasso::Spur;
+use rs_fsrs::Rating;
+#[cfg(target_arch = "wasm32")]
+use wasm_bindgen::prelude::*;
+
+use crate::card_detail::{ReviewRecord, ReviewSource};
+use crate::{CardData, CardIndicator, Deck, DeckEvent, LanguageEvent, LanguageEventContent};
+
+impl Deck {
+    /// Apply a `MarkKnown` event to a single card. Cards that are already being learned are left alone, since they
+    /// have a real review history that's better than our guess.
+    pub(crate) fn mark_known(&mut self, card: CardIndicator<Spur>, timestamp: DateTime<Utc>) {
+        if !self.is_in_language_pack(&card) {
+            return;
+        }
+        if self
+            .cards
+            .get(&card)
+            .is_some_and(|card_data| card_data.fsrs_card.state != rs_fsrs::State::New)
+        {
+            return;
+        }
+
+        let parameters = self.settings.fsrs.parameters();
+        let stability = self.settings.known_card_stability.into_inner().max(1.0);
+        let difficulty = parameters.init_difficulty(Rating::Easy);
+        let interval = parameters.next_interval(stability, 0) as i64;
+
+        let mut fsrs_card = rs_fsrs::Card::new();
+        fsrs_card.state = rs_fsrs::State::Review;
+        fsrs_card.stability = stability;
+        fsrs_card.difficulty = difficulty;
+        fsrs_card.reps = 1;
+        fsrs_card.scheduled_days = interval;
+        fsrs_card.last_review = timestamp;
+        fsrs_card.due = timestamp + chrono::Duration::days(interval);
+
+        let card_data = self
+            .cards
+            .entry(card.clone())
+            .or_insert_with(|| CardData::new(timestamp));
+        self.due_forecast
+            .remove(&card_data.fsrs_card, &self.settings.day);
+        self.due_forecast.add(&fsrs_card, &self.settings.day);
+        card_data.fsrs_card = fsrs_card;
+        card_data.reviews.push(ReviewRecord {
+            timestamp,
+            rating: Rating::Easy,
+            source: ReviewSource::MarkedKnown,
+            sentence: None,
+            stability_before: 0.0,
+            difficulty_before: 0.0,
+            stability_after: stability as f32,
+            difficulty_after: difficulty as f32,
+            state_after: rs_fsrs::State::Review,
+        });
+        self.track_word_known(&card, timestamp);
+        self.update_known_lexemes(&card);
+    }
+
+    pub(crate) fn is_unknown(&self, card: &CardIndicator<Spur>) -> bool {
+        self.cards
+            .get(card)
+            .is_none_or(|card_data| card_data.fsrs_card.state == rs_fsrs::State::New)
+    }
+
+    fn mark_known_event(&self, cards: Vec<CardIndicator<String>>) -> Option<DeckEvent> {
+        if cards.is_empty() {
+            return None;
+        }
+        Some(DeckEvent::Language(LanguageEvent {
+            language: self.target_language,
+            content: LanguageEventContent::MarkKnown { cards },
+        }))
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+impl Deck {
+    /// Mark a single card as already known, adding it to the deck if it isn't there yet.
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+    pub fn mark_card_known(&self, card: CardIndicator<String>) -> Option<DeckEvent> {
+        let indicator = card.get_interned(&self.language_pack.rodeo)?;
+        if !self.is_in_language_pack(&indicator) || !self.is_unknown(&indicator) {
+            return None;
+        }
+        self.mark_known_event(vec![card])
+    }
+
+    /// Mark every word ranked `start..end` by frequency (0 being the most frequent) as already known.
+    /// "I know the top 500 words" is `mark_frequency_range_known(0, 500)`.
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+    pub fn mark_frequency_range_known(&self, start: usize, end: usize) -> Option<DeckEvent> {
+        let cards = self
+            .language_pack
+            .word_frequencies
+            .keys()
+            .skip(start)
+            .take(end.saturating_sub(start))
+            .map(|lexeme| CardIndicator::TargetLanguage { lexeme: *lexeme })
+            .filter(|card| self.is_unknown(card))
+            .map(|card| card.resolve(&self.language_pack.rodeo))
+            .collect();
+        self.mark_known_event(cards)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::LanguageEventContent;
+    use crate::tests::{apply, lexeme_card, test_deck};
+
+    #[test]
+    fn marking_a_frequency_range_known() {
+        let start = chrono::DateTime::parse_from_rfc3339("2025-01-01T12:00:00Z")
+            .unwrap()
+            .to_utc();
+        let deck = apply(
+            test_deck(),
+            &[
+                (
+                    start,
+                    LanguageEventContent::AddCards {
+                        cards: vec![lexeme_card("le")],
+                    },
+                ),
+                (
+                    start,
+                    LanguageEventContent::ReviewCard {
+                        reviewed: lexeme_card("le"),
+                        rating: "again".to_string(),
+                    },
+                ),
+            ],
+        );
+        assert!(deck.mark_card_known(lexeme_card("le")).is_none());
+        let known_before = deck.get_percent_of_words_known();
+
+        let crate::DeckEvent::Language(event) = deck.mark_frequency_range_known(0, 3).unwrap();
+        let LanguageEventContent::MarkKnown { cards } = &event.content else {
+            unreachable!()
+        };
+        // "le" is already being learned, so it keeps its own history
+        assert_eq!(cards.len(), 2);
+        let deck = apply(deck, &[(start, event.content)]);
+
+        assert!(deck.get_percent_of_words_known() > known_before);
+        let detail = deck.get_card_detail(lexeme_card("chat")).unwrap();
+        assert_eq!(detail.state, "review");
+        assert_eq!(detail.stability, 60.0);
+        assert!(
+            detail.due_timestamp_ms
+                > (start + chrono::Duration::days(30)).timestamp_millis() as f64
+        );
+        let le = deck.get_card_detail(lexeme_card("le")).unwrap();
+        assert_eq!(le.state, "learning");
+    }
+}
@@ -0,0 +1,308 @@
+//! Minimal-pair drills: two words the user knows that differ by a single sound (French "dessus"/"dessous", Korean
+//! 달/딸), one of which is said aloud, and the user picks which one they heard.
+//!
+//! Drills don't touch card schedules. Instead, accuracy is tracked for each contrast (the pair of sounds that
+//! differ), and drills keep coming back to whichever contrast the user gets wrong the most.
+
+use std::collections::BTreeMap;
+
+use lasso::Spur;
+use wasm_bindgen::prelude::*;
+
+use crate::multiple_choice::sequence_edit_distance;
+use crate::{AudioRequest, CardIndicator, Deck, DeckEvent, LanguageEvent, LanguageEventContent};
+
+/// Two sounds that are easy to confuse. Sorted, so the same contrast is counted together whichever of the two was
+/// said. A sound is empty when the other word simply has an extra one.
+#[derive(
+    tsify::Tsify, serde::Serialize, serde::Deserialize, Debug, Clone, PartialEq, Eq, PartialOrd, Ord,
+)]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+pub struct Contrast {
+    sounds: [String; 2],
+}
+
+#[derive(Clone, Debug, Default)]
+pub(crate) struct ContrastAttempts {
+    attempts: u32,
+    correct: u32,
+}
+
+impl ContrastAttempts {
+    /// Accuracy, starting from a prior of one right and one wrong so a single lucky guess doesn't count for much.
+    fn smoothed_accuracy(&self) -> f64 {
+        (self.correct + 1) as f64 / (self.attempts + 2) as f64
+    }
+}
+
+#[derive(tsify::Tsify, serde::Serialize, serde::Deserialize, Debug, Clone)]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+pub struct ContrastAccuracy {
+    contrast: Contrast,
+    attempts: u32,
+    correct: u32,
+}
+
+#[derive(tsify::Tsify, serde::Serialize, serde::Deserialize, Debug, Clone)]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+pub struct MinimalPair {
+    /// The pronunciation of the word that's said.
+    said: String,
+    /// The pronunciation of the word it might be mistaken for.
+    other: String,
+    contrast: Contrast,
+    audio: AudioRequest,
+    /// A word for each pronunciation, in alphabetical order.
+    options: Vec<String>,
+    correct_option: usize,
+}
+
+/// Split IPA into sounds, keeping diacritics and modifiers (nasalization, length, aspiration, tenseness) with the
+/// symbol they modify.
+fn ipa_segments(ipa: &str) -> Vec<&str> {
+    let is_modifier = |c: char| {
+        matches!(
+            c,
+            // Combining diacritics, then spacing modifiers like "ː" and "ʰ"
+            '\u{0300}'..='\u{036F}' | '\u{1DC0}'..='\u{1DFF}' | '\u{02B0}'..='\u{02FF}'
+        )
+    };
+    let starts: Vec<usize> = ipa
+        .char_indices()
+        .filter(|(index, c)| *index == 0 || !is_modifier(*c))
+        .map(|(index, _)| index)
+        .collect();
+    starts
+        .iter()
+        .zip(starts.iter().skip(1).chain([&ipa.len()]))
+        .map(|(start, end)| &ipa[*start..*end])
+        .collect()
+}
+
+/// The sounds that differ between two pronunciations a single edit apart.
+fn contrast(a: &[&str], b: &[&str]) -> Contrast {
+    let prefix = a.iter().zip(b).take_while(|(a, b)| a == b).count();
+    let suffix = a[prefix..]
+        .iter()
+        .rev()
+        .zip(b[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let mut sounds = [
+        a[prefix..a.len() - suffix].concat(),
+        b[prefix..b.len() - suffix].concat(),
+    ];
+    sounds.sort();
+    Contrast { sounds }
+}
+
+/// Which word to say, and which to offer alongside it, on the `attempt`th drill of a contrast. Goes through every pair,
+/// then through them all again with the other word of each pair said.
+fn nth_drill<T: Copy>(pairs: &[(T, T)], attempt: usize) -> (T, T) {
+    let (a, b) = pairs[attempt % pairs.len()];
+    if (attempt / pairs.len()) % 2 == 0 {
+        (a, b)
+    } else {
+        (b, a)
+    }
+}
+
+impl Deck {
+    /// Pronunciations the user has started learning to hear, in a stable order.
+    fn known_pronunciations(&self) -> Vec<Spur> {
+        let rodeo = &self.language_pack.rodeo;
+        let mut pronunciations: Vec<Spur> = self
+            .cards
+            .iter()
+            .filter(|(_, card_data)| {
+                card_data.fsrs_card.state != rs_fsrs::State::New && !card_data.suspended
+            })
+            .filter_map(|(indicator, _)| match indicator {
+                CardIndicator::ListeningHomophonous { pronunciation } => Some(*pronunciation),
+                CardIndicator::TargetLanguage { .. } | CardIndicator::NativeToTarget { .. } => None,
+            })
+            .collect();
+        pronunciations.sort_by_key(|pronunciation| rodeo.resolve(pronunciation));
+        pronunciations
+    }
+
+    /// Every pair of known pronunciations a single sound apart, grouped by what that sound is.
+    fn minimal_pairs(&self) -> BTreeMap<Contrast, Vec<(Spur, Spur)>> {
+        let rodeo = &self.language_pack.rodeo;
+        let pronunciations: Vec<(Spur, Vec<&str>)> = self
+            .known_pronunciations()
+            .into_iter()
+            .map(|pronunciation| (pronunciation, ipa_segments(rodeo.resolve(&pronunciation))))
+            .collect();
+        let mut pairs: BTreeMap<Contrast, Vec<(Spur, Spur)>> = BTreeMap::new();
+        for (i, (a, a_segments)) in pronunciations.iter().enumerate() {
+            for (b, b_segments) in &pronunciations[i + 1..] {
+                if a_segments.len().abs_diff(b_segments.len()) <= 1
+                    && sequence_edit_distance(a_segments, b_segments) == 1
+                {
+                    pairs
+                        .entry(contrast(a_segments, b_segments))
+                        .or_default()
+                        .push((*a, *b));
+                }
+            }
+        }
+        pairs
+    }
+
+    pub(crate) fn apply_minimal_pair_drill(&mut self, said: &str, other: &str, correct: bool) {
+        let contrast = contrast(&ipa_segments(said), &ipa_segments(other));
+        let attempts = self.contrast_attempts.entry(contrast).or_default();
+        attempts.attempts += 1;
+        attempts.correct += u32::from(correct);
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+impl Deck {
+    /// A drill on the contrast the user is worst at, or `None` if they don't know any minimal pairs yet.
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+    pub fn get_minimal_pair_drill(&self) -> Option<MinimalPair> {
+        let language_pack = &self.language_pack;
+        let rodeo = &language_pack.rodeo;
+        let no_attempts = ContrastAttempts::default();
+        let (contrast, pairs) = self.minimal_pairs().into_iter().min_by(|(a, _), (b, _)| {
+            let a = self.contrast_attempts.get(a).unwrap_or(&no_attempts);
+            let b = self.contrast_attempts.get(b).unwrap_or(&no_attempts);
+            a.smoothed_accuracy()
+                .total_cmp(&b.smoothed_accuracy())
+                .then(a.attempts.cmp(&b.attempts))
+        })?;
+
+        let attempts = self
+            .contrast_attempts
+            .get(&contrast)
+            .map_or(0, |attempts| attempts.attempts as usize);
+        let (said, other) = nth_drill(&pairs, attempts);
+
+        let word = |pronunciation: &Spur| -> Option<String> {
+            let words = language_pack.pronunciation_to_words.get(pronunciation)?;
+            Some(rodeo.resolve(words.first()?).to_string())
+        };
+        let said_word = word(&said)?;
+        let mut options = vec![said_word.clone(), word(&other)?];
+        options.sort();
+        let correct_option = options.iter().position(|option| *option == said_word)?;
+
+        Some(MinimalPair {
+            said: rodeo.resolve(&said).to_string(),
+            other: rodeo.resolve(&other).to_string(),
+            contrast,
+            audio: self.word_audio(said_word),
+            options,
+            correct_option,
+        })
+    }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+    pub fn answer_minimal_pair(&self, drill: MinimalPair, chosen_option: usize) -> DeckEvent {
+        DeckEvent::Language(LanguageEvent {
+            language: self.target_language,
+            content: LanguageEventContent::MinimalPairDrill {
+                said: drill.said,
+                other: drill.other,
+                correct: chosen_option == drill.correct_option,
+            },
+        })
+    }
+
+    /// How well the user tells apart each contrast they've drilled, worst first.
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+    pub fn get_contrast_accuracy(&self) -> Vec<ContrastAccuracy> {
+        let mut accuracy: Vec<(f64, ContrastAccuracy)> = self
+            .contrast_attempts
+            .iter()
+            .map(|(contrast, attempts)| {
+                (
+                    attempts.smoothed_accuracy(),
+                    ContrastAccuracy {
+                        contrast: contrast.clone(),
+                        attempts: attempts.attempts,
+                        correct: attempts.correct,
+                    },
+                )
+            })
+            .collect();
+        accuracy.sort_by(|(a, _), (b, _)| a.total_cmp(b));
+        accuracy.into_iter().map(|(_, accuracy)| accuracy).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Contrast, contrast, ipa_segments, nth_drill};
+    use crate::tests::{apply, test_deck};
+    use crate::{CardIndicator, LanguageEventContent};
+
+    fn sounds(a: &str, b: &str) -> [String; 2] {
+        contrast(&ipa_segments(a), &ipa_segments(b)).sounds
+    }
+
+    #[test]
+    fn contrasts() {
+        assert_eq!(ipa_segments("mɑ̃ʒ"), vec!["m", "ɑ̃", "ʒ"]);
+        assert_eq!(ipa_segments("tʰal"), vec!["tʰ", "a", "l"]);
+        assert_eq!(sounds("dəsy", "dəsu"), ["u", "y"]);
+        assert_eq!(sounds("t͈al", "tal"), ["t", "t͈"]);
+        assert_eq!(sounds("mɛ", "mɛʁ"), ["", "ʁ"]);
+    }
+
+    #[test]
+    fn every_word_of_every_pair_gets_said() {
+        let pairs = [("dəsy", "dəsu"), ("ʁy", "ʁu")];
+        let drills: Vec<_> = (0..4).map(|attempt| nth_drill(&pairs, attempt)).collect();
+        assert_eq!(
+            drills,
+            [
+                ("dəsy", "dəsu"),
+                ("ʁy", "ʁu"),
+                ("dəsu", "dəsy"),
+                ("ʁu", "ʁy")
+            ]
+        );
+    }
+
+    #[test]
+    fn minimal_pair_drills() {
+        let start = chrono::DateTime::parse_from_rfc3339("2025-01-01T12:00:00Z")
+            .unwrap()
+            .to_utc();
+        let listening = |pronunciation: &str| CardIndicator::ListeningHomophonous {
+            pronunciation: pronunciation.to_string(),
+        };
+        let deck = apply(
+            test_deck(),
+            &[(
+                start,
+                LanguageEventContent::MarkKnown {
+                    cards: vec![listening("ʃa"), listening("ʃe"), listening("lə")],
+                },
+            )],
+        );
+
+        let drill = deck.get_minimal_pair_drill().unwrap();
+        assert_eq!(
+            drill.contrast,
+            Contrast {
+                sounds: ["a".to_string(), "e".to_string()]
+            }
+        );
+        assert_eq!(drill.options, vec!["chat", "chez"]);
+        assert_eq!(drill.said, "ʃa");
+        let wrong = 1 - drill.correct_option;
+
+        let crate::DeckEvent::Language(event) = deck.answer_minimal_pair(drill, wrong);
+        let deck = apply(deck, &[(start, event.content)]);
+        let accuracy = deck.get_contrast_accuracy();
+        assert_eq!(accuracy.len(), 1);
+        assert_eq!((accuracy[0].attempts, accuracy[0].correct), (1, 0));
+
+        // The other word of the pair gets said next
+        assert_eq!(deck.get_minimal_pair_drill().unwrap().said, "ʃe");
+    }
+}
@@ -0,0 +1,358 @@
+//! Multiple-choice challenges, for when typing is a chore (on phones, mostly). Distractors are chosen to be
+//! plausible: words of similar frequency and the same part of speech, or for listening cards, words that sound
+//! nearly the same.
+//!
+//! Picking the right answer out of a few is easier than recalling it, so a correct answer only counts as `Hard`.
+
+use std::collections::BTreeSet;
+
+use chrono::{DateTime, Utc};
+use language_utils::{Lexeme, TtsProvider, TtsRequest};
+use lasso::Spur;
+use rs_fsrs::Rating;
+use wasm_bindgen::prelude::*;
+
+use crate::card_detail::ReviewSource;
+use crate::{
+    AudioRequest, CardIndicator, Challenge, Deck, DeckEvent, LanguageEvent, LanguageEventContent,
+    ReviewInfo,
+};
+
+const NUM_DISTRACTORS: usize = 3;
+/// How far up and down the frequency list to look for distractors.
+const DISTRACTOR_RANK_WINDOW: usize = 500;
+
+#[derive(tsify::Tsify, serde::Serialize, serde::Deserialize, Debug, Clone)]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+pub struct MultipleChoice<S> {
+    pub(crate) indicator: CardIndicator<S>,
+    /// What the user is asked about: the word for text cards, or the meaning for production cards. Listening cards
+    /// don't have one, since the audio is the prompt.
+    prompt: Option<String>,
+    pub(crate) audio: AudioRequest,
+    /// In alphabetical order, so the position of the right answer gives nothing away.
+    options: Vec<String>,
+    pub(crate) correct_option: usize,
+}
+
+impl MultipleChoice<Spur> {
+    pub(crate) fn resolve(&self, rodeo: &lasso::RodeoReader) -> MultipleChoice<String> {
+        MultipleChoice {
+            indicator: self.indicator.resolve(rodeo),
+            prompt: self.prompt.clone(),
+            audio: self.audio.clone(),
+            options: self.options.clone(),
+            correct_option: self.correct_option,
+        }
+    }
+}
+
+/// The Levenshtein distance between two strings, counted in chars.
+pub(crate) fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    sequence_edit_distance(&a, &b)
+}
+
+/// The Levenshtein distance between two sequences.
+pub(crate) fn sequence_edit_distance<T: PartialEq>(a: &[T], b: &[T]) -> usize {
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, a) in a.iter().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for (j, b) in b.iter().enumerate() {
+            current[j + 1] = (previous[j] + usize::from(a != b))
+                .min(previous[j + 1] + 1)
+                .min(current[j] + 1);
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+/// The correct answer plus up to `NUM_DISTRACTORS` other options, sorted, and the index of the correct one.
+fn sorted_options(
+    correct: String,
+    distractors: impl Iterator<Item = String>,
+) -> (Vec<String>, usize) {
+    let mut options = vec![correct.clone()];
+    for distractor in distractors {
+        if options.len() > NUM_DISTRACTORS {
+            break;
+        }
+        if !options.contains(&distractor) {
+            options.push(distractor);
+        }
+    }
+    options.sort();
+    let correct_option = options
+        .iter()
+        .position(|option| *option == correct)
+        .unwrap();
+    (options, correct_option)
+}
+
+impl Deck {
+    fn lexeme_text(&self, lexeme: &Lexeme<Spur>) -> String {
+        let rodeo = &self.language_pack.rodeo;
+        match lexeme {
+            Lexeme::Heteronym(heteronym) => rodeo.resolve(&heteronym.word).to_string(),
+            Lexeme::Multiword(term) => rodeo.resolve(term).to_string(),
+        }
+    }
+
+    fn lexeme_meaning(&self, lexeme: &Lexeme<Spur>) -> Option<String> {
+        match lexeme {
+            Lexeme::Heteronym(heteronym) => self
+                .language_pack
+                .dictionary
+                .get(heteronym)?
+                .definitions
+                .first()
+                .map(|definition| definition.native.clone()),
+            Lexeme::Multiword(term) => self
+                .language_pack
+                .phrasebook
+                .get(term)
+                .map(|entry| entry.meaning.clone()),
+        }
+    }
+
+    /// Lexemes of the same kind (and for words, the same part of speech) as `lexeme`, closest in frequency first.
+    fn similar_lexemes(&self, lexeme: &Lexeme<Spur>) -> impl Iterator<Item = &Lexeme<Spur>> {
+        let word_frequencies = &self.language_pack.word_frequencies;
+        let rank = word_frequencies.get_index_of(lexeme).unwrap_or(0);
+        (1..=DISTRACTOR_RANK_WINDOW)
+            .flat_map(move |distance| [rank.checked_sub(distance), Some(rank + distance)])
+            .flatten()
+            .filter_map(|rank| word_frequencies.get_index(rank).map(|(lexeme, _)| lexeme))
+            .filter(move |candidate| match (lexeme, candidate) {
+                (Lexeme::Heteronym(heteronym), Lexeme::Heteronym(candidate)) => {
+                    heteronym.pos == candidate.pos
+                }
+                (Lexeme::Multiword(_), Lexeme::Multiword(_)) => true,
+                _ => false,
+            })
+    }
+
+    pub(crate) fn word_audio(&self, text: String) -> AudioRequest {
+        AudioRequest {
+            request: TtsRequest {
+                text,
+                language: self.target_language,
+            },
+            provider: TtsProvider::Google,
+        }
+    }
+
+    pub(crate) fn get_multiple_choice(
+        &self,
+        indicator: &CardIndicator<Spur>,
+    ) -> Option<MultipleChoice<Spur>> {
+        let (prompt, audio, (options, correct_option)) = match indicator {
+            CardIndicator::TargetLanguage { lexeme } => {
+                let word = self.lexeme_text(lexeme);
+                let options = sorted_options(
+                    self.lexeme_meaning(lexeme)?,
+                    self.similar_lexemes(lexeme)
+                        .filter_map(|lexeme| self.lexeme_meaning(lexeme)),
+                );
+                (Some(word.clone()), self.word_audio(word), options)
+            }
+            CardIndicator::NativeToTarget { lexeme } => {
+                let word = self.lexeme_text(lexeme);
+                let options = sorted_options(
+                    word.clone(),
+                    self.similar_lexemes(lexeme)
+                        .map(|lexeme| self.lexeme_text(lexeme)),
+                );
+                (self.lexeme_meaning(lexeme), self.word_audio(word), options)
+            }
+            CardIndicator::ListeningHomophonous { pronunciation } => {
+                let language_pack = &self.language_pack;
+                let rodeo = &language_pack.rodeo;
+                let word = rodeo.resolve(
+                    language_pack
+                        .pronunciation_to_words
+                        .get(pronunciation)?
+                        .first()?,
+                );
+
+                // Other words that sound nearly the same, from among those about as common, so as not to compare
+                // against every pronunciation in the language. Exact homophones would also be right, so they're left
+                // out.
+                let ipa = rodeo.resolve(pronunciation);
+                let rank = self.frequency_rank(indicator).unwrap_or(0);
+                let others: BTreeSet<Spur> = (rank.saturating_sub(DISTRACTOR_RANK_WINDOW)
+                    ..=rank + DISTRACTOR_RANK_WINDOW)
+                    .filter_map(|rank| language_pack.word_frequencies.get_index(rank))
+                    .filter_map(|(lexeme, _)| match lexeme {
+                        Lexeme::Heteronym(heteronym) => {
+                            language_pack.word_to_pronunciation.get(&heteronym.word)
+                        }
+                        Lexeme::Multiword(_) => None,
+                    })
+                    .filter(|other| *other != pronunciation)
+                    .copied()
+                    .collect();
+                let mut near_homophones: Vec<(usize, &str)> = others
+                    .iter()
+                    .filter_map(|other| {
+                        let words = language_pack.pronunciation_to_words.get(other)?;
+                        Some((
+                            edit_distance(ipa, rodeo.resolve(other)),
+                            rodeo.resolve(words.first()?),
+                        ))
+                    })
+                    .collect();
+                near_homophones.sort();
+                let options = sorted_options(
+                    word.to_string(),
+                    near_homophones
+                        .into_iter()
+                        .map(|(_, word)| word.to_string()),
+                );
+                (None, self.word_audio(word.to_string()), options)
+            }
+        };
+        // With nothing to choose between, it isn't much of a challenge
+        if options.len() < 2 {
+            return None;
+        }
+        Some(MultipleChoice {
+            indicator: indicator.clone(),
+            prompt,
+            audio,
+            options,
+            correct_option,
+        })
+    }
+
+    pub(crate) fn apply_multiple_choice(
+        &mut self,
+        card: &CardIndicator<String>,
+        correct: bool,
+        timestamp: DateTime<Utc>,
+    ) {
+        let Some(card) = card.get_interned(&self.language_pack.rodeo) else {
+            return;
+        };
+        let rating = if correct { Rating::Hard } else { Rating::Again };
+        self.log_review(card, rating, timestamp, ReviewSource::MultipleChoice, None);
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+impl ReviewInfo {
+    /// Like `get_challenge_for_card`, but a multiple-choice challenge where possible. New cards still get a
+    /// flashcard, since the user hasn't seen them yet.
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+    pub fn get_multiple_choice_challenge_for_card(
+        &self,
+        deck: &Deck,
+        card_index: usize,
+    ) -> Option<Challenge<String>> {
+        let (indicator, card_data) = deck.cards.get_index(card_index)?;
+        if card_data.fsrs_card.state != rs_fsrs::State::New
+            && !card_data.suspended
+            && !card_data.is_buried(Utc::now())
+            && let Some(challenge) = deck.get_multiple_choice(indicator)
+        {
+            return Some(Challenge::MultipleChoice(
+                challenge.resolve(&deck.language_pack.rodeo),
+            ));
+        }
+        self.get_challenge_for_card(deck, card_index)
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+impl Deck {
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+    pub fn answer_multiple_choice(
+        &self,
+        challenge: MultipleChoice<String>,
+        chosen_option: usize,
+    ) -> Option<DeckEvent> {
+        Some(DeckEvent::Language(LanguageEvent {
+            language: self.target_language,
+            content: LanguageEventContent::MultipleChoiceChallenge {
+                card: challenge.indicator,
+                correct: chosen_option == challenge.correct_option,
+            },
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::card_detail::ReviewSource;
+    use crate::tests::{apply, lexeme_card, test_deck};
+    use crate::{CardIndicator, LanguageEventContent};
+
+    #[test]
+    fn edit_distance() {
+        assert_eq!(super::edit_distance("mɛ", "mɛ"), 0);
+        assert_eq!(super::edit_distance("ʃa", "ʃja"), 1);
+        assert_eq!(super::edit_distance("lə", "mɛ"), 2);
+        assert_eq!(super::edit_distance("", "abc"), 3);
+    }
+
+    #[test]
+    fn multiple_choice_distractors() {
+        let start = chrono::DateTime::parse_from_rfc3339("2025-01-01T12:00:00Z")
+            .unwrap()
+            .to_utc();
+        let listening = CardIndicator::ListeningHomophonous {
+            pronunciation: "mɛ".to_string(),
+        };
+        let cards = vec![lexeme_card("chat"), lexeme_card("le"), listening.clone()];
+        let mut events = vec![(
+            start,
+            LanguageEventContent::AddCards {
+                cards: cards.clone(),
+            },
+        )];
+        for card in cards {
+            events.push((
+                start,
+                LanguageEventContent::ReviewCard {
+                    reviewed: card,
+                    rating: "good".to_string(),
+                },
+            ));
+        }
+        let deck = apply(test_deck(), &events);
+        let rodeo = &deck.language_pack.rodeo;
+
+        // "chien" is the only other noun
+        let chat = lexeme_card("chat").get_interned(rodeo).unwrap();
+        let challenge = deck.get_multiple_choice(&chat).unwrap();
+        assert_eq!(challenge.prompt.as_deref(), Some("chat"));
+        assert_eq!(challenge.options, vec!["cat", "dog"]);
+        assert_eq!(challenge.correct_option, 0);
+
+        // Distractors for "mɛ" are the closest sounding words, and never its homophones
+        let challenge = deck
+            .get_multiple_choice(&listening.get_interned(rodeo).unwrap())
+            .unwrap();
+        assert_eq!(challenge.options.len(), 4);
+        let correct = &challenge.options[challenge.correct_option];
+        assert!(["mes", "mais"].contains(&correct.as_str()));
+        assert!(
+            challenge
+                .options
+                .iter()
+                .filter(|option| ["mes", "mais"].contains(&option.as_str()))
+                .count()
+                == 1
+        );
+
+        let challenge = deck.get_multiple_choice(&chat).unwrap().resolve(rodeo);
+        let crate::DeckEvent::Language(event) = deck.answer_multiple_choice(challenge, 0).unwrap();
+        let deck = apply(deck, &[(start + chrono::Duration::days(5), event.content)]);
+        let detail = deck.get_card_detail(lexeme_card("chat")).unwrap();
+        let review = detail.reviews.last().unwrap();
+        assert_eq!(review.source, ReviewSource::MultipleChoice);
+        assert_eq!(review.rating, "hard");
+    }
+}
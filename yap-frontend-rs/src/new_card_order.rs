@@ -0,0 +1,255 @@
+//! Strategies for the order new text cards are suggested in. Each one puts the language pack's frequency list into a
+//! different order, and `NextCardsIterator` walks that order instead of the frequency list itself.
+
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use language_utils::{Lexeme, PartOfSpeech};
+use lasso::Spur;
+#[cfg(target_arch = "wasm32")]
+use wasm_bindgen::prelude::*;
+
+use crate::deck_settings::NewCardOrder;
+use crate::{CardIndicator, Deck};
+
+pub(crate) trait NewCardOrdering {
+    /// Positions in the frequency list, in the order their words should be suggested. Words the user already has a
+    /// text card for may be left out.
+    fn order(&self, deck: &Deck) -> Vec<usize>;
+}
+
+/// The most common words first.
+pub(crate) struct FrequencyOrder;
+
+impl NewCardOrdering for FrequencyOrder {
+    fn order(&self, deck: &Deck) -> Vec<usize> {
+        (0..deck.language_pack.word_frequencies.len()).collect()
+    }
+}
+
+/// The words in a themed word pack first, then everything else, both most common first.
+pub(crate) struct WordPackOrder<'a> {
+    pub(crate) name: &'a str,
+}
+
+impl NewCardOrdering for WordPackOrder<'_> {
+    fn order(&self, deck: &Deck) -> Vec<usize> {
+        let Some(pack) = deck.language_pack.word_packs.get(self.name) else {
+            log::warn!("No word pack named {:?}", self.name);
+            return FrequencyOrder.order(deck);
+        };
+        let pack: HashSet<&Lexeme<Spur>> = pack.iter().collect();
+        preferred_first(deck, |lexeme| pack.contains(lexeme))
+    }
+}
+
+/// Roughly how much of a vocabulary each kind of word should make up. Anything else (articles, pronouns,
+/// prepositions, multiword terms, ...) gets the rest.
+const PART_OF_SPEECH_SHARES: [(PartOfSpeech, f64); 4] = [
+    (PartOfSpeech::Noun, 0.35),
+    (PartOfSpeech::Verb, 0.25),
+    (PartOfSpeech::Adj, 0.15),
+    (PartOfSpeech::Adv, 0.1),
+];
+const OTHER_SHARE: f64 = 0.15;
+
+/// Interleaves parts of speech so the deck keeps close to `PART_OF_SPEECH_SHARES`, most common first within each.
+pub(crate) struct PartOfSpeechBalancedOrder;
+
+impl PartOfSpeechBalancedOrder {
+    fn group(lexeme: &Lexeme<Spur>) -> usize {
+        lexeme
+            .heteronym()
+            .and_then(|heteronym| {
+                PART_OF_SPEECH_SHARES
+                    .iter()
+                    .position(|(pos, _)| *pos == heteronym.pos)
+            })
+            .unwrap_or(PART_OF_SPEECH_SHARES.len())
+    }
+}
+
+impl NewCardOrdering for PartOfSpeechBalancedOrder {
+    fn order(&self, deck: &Deck) -> Vec<usize> {
+        let shares: Vec<f64> = PART_OF_SPEECH_SHARES
+            .iter()
+            .map(|(_, share)| *share)
+            .chain([OTHER_SHARE])
+            .collect();
+        let mut taken = vec![0usize; shares.len()];
+        let mut groups: Vec<VecDeque<usize>> = vec![Default::default(); shares.len()];
+        for (position, (lexeme, _)) in deck.language_pack.word_frequencies.iter().enumerate() {
+            let group = Self::group(lexeme);
+            if deck
+                .cards
+                .contains_key(&CardIndicator::TargetLanguage { lexeme: *lexeme })
+            {
+                // Words already in the deck count towards the balance
+                taken[group] += 1;
+            } else {
+                groups[group].push_back(position);
+            }
+        }
+
+        let mut order = Vec::new();
+        // Take from whichever kind of word would be least over its share after taking one more (as in D'Hondt
+        // apportionment)
+        let priority = |taken: &[usize], group: usize| (taken[group] + 1) as f64 / shares[group];
+        while let Some(group) = (0..shares.len())
+            .filter(|group| !groups[*group].is_empty())
+            .min_by(|a, b| {
+                priority(&taken, *a)
+                    .total_cmp(&priority(&taken, *b))
+                    .then(groups[*a][0].cmp(&groups[*b][0]))
+            })
+        {
+            order.push(groups[group].pop_front().unwrap());
+            taken[group] += 1;
+        }
+        order
+    }
+}
+
+/// Words from sentences the user has already been shown first, most often seen first, so those sentences can be read
+/// without gaps. Then everything else, most common first.
+pub(crate) struct SeenSentencesOrder;
+
+impl NewCardOrdering for SeenSentencesOrder {
+    fn order(&self, deck: &Deck) -> Vec<usize> {
+        let language_pack = &deck.language_pack;
+        let mut sightings: HashMap<Lexeme<Spur>, u32> = HashMap::new();
+        for (sentence, times_reviewed) in &deck.sentences_reviewed {
+            for lexeme in language_pack
+                .sentences_to_all_lexemes
+                .get(sentence)
+                .into_iter()
+                .flatten()
+            {
+                *sightings.entry(*lexeme).or_default() += times_reviewed;
+            }
+        }
+        let mut order = preferred_first(deck, |lexeme| sightings.contains_key(lexeme));
+        let seen = order
+            .iter()
+            .take_while(|position| {
+                let (lexeme, _) = language_pack
+                    .word_frequencies
+                    .get_index(**position)
+                    .unwrap();
+                sightings.contains_key(lexeme)
+            })
+            .count();
+        // Stable, so words seen equally often stay in frequency order
+        order[..seen].sort_by_key(|position| {
+            let (lexeme, _) = language_pack.word_frequencies.get_index(*position).unwrap();
+            std::cmp::Reverse(sightings[lexeme])
+        });
+        order
+    }
+}
+
+/// The frequency list with the lexemes matching `preferred` moved to the front.
+fn preferred_first(deck: &Deck, preferred: impl Fn(&Lexeme<Spur>) -> bool) -> Vec<usize> {
+    let word_frequencies = &deck.language_pack.word_frequencies;
+    let (mut order, rest): (Vec<usize>, Vec<usize>) = (0..word_frequencies.len())
+        .partition(|position| preferred(word_frequencies.get_index(*position).unwrap().0));
+    order.extend(rest);
+    order
+}
+
+impl NewCardOrder {
+    pub(crate) fn strategy(&self) -> Box<dyn NewCardOrdering + '_> {
+        match self {
+            NewCardOrder::Frequency => Box::new(FrequencyOrder),
+            NewCardOrder::WordPack { name } => Box::new(WordPackOrder { name }),
+            NewCardOrder::PartOfSpeechBalanced => Box::new(PartOfSpeechBalancedOrder),
+            NewCardOrder::SeenSentences => Box::new(SeenSentencesOrder),
+        }
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+impl Deck {
+    /// The names of the language pack's themed word packs, for `NewCardOrder::WordPack`.
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+    pub fn get_word_packs(&self) -> Vec<String> {
+        self.language_pack.word_packs.keys().cloned().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use language_utils::Lexeme;
+
+    use crate::deck_settings::NewCardOrder;
+    use crate::tests::{apply, lexeme_card, test_deck};
+    use crate::{CardIndicator, CardType, Deck, LanguageEventContent};
+
+    fn suggestions(deck: &Deck) -> Vec<String> {
+        deck.next_unknown_cards(Some(CardType::TargetLanguage))
+            .map(|card| match card.resolve(&deck.language_pack.rodeo) {
+                CardIndicator::TargetLanguage {
+                    lexeme: Lexeme::Heteronym(heteronym),
+                } => heteronym.word,
+                CardIndicator::TargetLanguage {
+                    lexeme: Lexeme::Multiword(term),
+                } => term,
+                _ => unreachable!(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn new_card_orders() {
+        let start = chrono::DateTime::parse_from_rfc3339("2025-01-01T12:00:00Z")
+            .unwrap()
+            .to_utc();
+        let mut deck = test_deck();
+        deck.settings.new_cards.multiword_min_cards = 0;
+        assert_eq!(
+            suggestions(&deck),
+            [
+                "le", "chat", "mange", "chien", "mais", "mes", "chez", "manges", "il y a"
+            ]
+        );
+
+        deck.settings.new_cards.order = NewCardOrder::WordPack {
+            name: "animals".to_string(),
+        };
+        assert_eq!(
+            suggestions(&deck),
+            [
+                "chat", "chien", "le", "mange", "mais", "mes", "chez", "manges", "il y a"
+            ]
+        );
+
+        // With two nouns already in the deck, a verb comes before anything else
+        let mut nouns = apply(
+            deck.clone(),
+            &[(
+                start,
+                LanguageEventContent::AddCards {
+                    cards: vec![lexeme_card("chat"), lexeme_card("chien")],
+                },
+            )],
+        );
+        nouns.settings.new_cards.order = NewCardOrder::PartOfSpeechBalanced;
+        assert_eq!(
+            suggestions(&nouns),
+            ["mange", "le", "manges", "mais", "mes", "chez", "il y a"]
+        );
+
+        // Words from the sentences seen most go first
+        let rodeo = &deck.language_pack.rodeo;
+        deck.sentences_reviewed
+            .insert(rodeo.get("Le chien mange.").unwrap(), 2);
+        deck.sentences_reviewed
+            .insert(rodeo.get("Le chat mange.").unwrap(), 1);
+        deck.settings.new_cards.order = NewCardOrder::SeenSentences;
+        assert_eq!(
+            suggestions(&deck),
+            [
+                "le", "mange", "chien", "chat", "mais", "mes", "chez", "manges", "il y a"
+            ]
+        );
+    }
+}
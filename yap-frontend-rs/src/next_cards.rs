@@ -1,82 +1,197 @@
-use std::collections::BTreeSet;
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashSet, VecDeque};
 
 use language_utils::Lexeme;
 use lasso::Spur;
 
+use crate::deck_settings::NewCardSettings;
+use crate::new_card_order::NewCardOrdering;
 use crate::{CardIndicator, ChallengeType, Deck, LanguagePack};
 
+/// Suggests cards to add. Text cards follow the order from the deck's `NewCardOrdering`, while listening and
+/// production cards follow the frequency list (for words that already have a text card). Each kind of card keeps a
+/// cursor into its list, with everything before it already dealt with, so taking N cards is a single pass rather
+/// than N.
 pub struct NextCardsIterator<'a> {
-    pub cards: Vec<CardIndicator<Spur>>,
     pub permitted_types: Vec<ChallengeType>,
+    settings: NewCardSettings,
+    /// Lexemes with a text card, either already in the deck or suggested by this iterator.
+    text_lexemes: HashSet<Lexeme<Spur>>,
+    /// Pronunciations with a listening card.
+    pronunciations: HashSet<Spur>,
+    /// Lexemes with a production card.
+    production_lexemes: HashSet<Lexeme<Spur>>,
+    /// Lexemes whose text card has been reviewed at least once, so they're ready to be produced.
+    recognized_lexemes: HashSet<Lexeme<Spur>>,
+    total_count: usize,
+    text_count: usize,
+    listening_count: usize,
+
+    /// Positions in the frequency list, in the order text cards are suggested.
+    text_order: Vec<usize>,
+    text_cursor: usize,
+    /// Multiword terms passed over while the deck was too small for them, in the order they were passed over.
+    deferred_multiwords: VecDeque<usize>,
+    listening_cursor: usize,
+    /// Words behind `listening_cursor` that have since been given a text card, so are now eligible.
+    listening_backlog: BinaryHeap<Reverse<usize>>,
+    production_cursor: usize,
     language_pack: &'a LanguagePack,
 }
 
 impl<'a> NextCardsIterator<'a> {
     pub fn new(state: &'a Deck, permitted_types: Vec<ChallengeType>) -> Self {
-        Self {
-            cards: state.cards.keys().cloned().collect(),
+        let ordering = state.settings.new_cards.order.strategy();
+        Self::with_ordering(state, permitted_types, ordering.as_ref())
+    }
+
+    pub(crate) fn with_ordering(
+        state: &'a Deck,
+        permitted_types: Vec<ChallengeType>,
+        ordering: &dyn NewCardOrdering,
+    ) -> Self {
+        let mut iterator = Self {
             permitted_types,
+            settings: state.settings.new_cards.clone(),
+            text_lexemes: HashSet::new(),
+            pronunciations: HashSet::new(),
+            production_lexemes: HashSet::new(),
+            recognized_lexemes: HashSet::new(),
+            total_count: state.cards.len(),
+            text_count: 0,
+            listening_count: 0,
+            text_order: ordering.order(state),
+            text_cursor: 0,
+            deferred_multiwords: VecDeque::new(),
+            listening_cursor: 0,
+            listening_backlog: BinaryHeap::new(),
+            production_cursor: 0,
             language_pack: &state.language_pack,
+        };
+        for (card, card_data) in state.cards.iter() {
+            match card {
+                CardIndicator::TargetLanguage { lexeme } => {
+                    iterator.text_lexemes.insert(*lexeme);
+                    iterator.text_count += 1;
+                    if card_data.fsrs_card.state != rs_fsrs::State::New {
+                        iterator.recognized_lexemes.insert(*lexeme);
+                    }
+                }
+                CardIndicator::ListeningHomophonous { pronunciation } => {
+                    iterator.pronunciations.insert(*pronunciation);
+                    iterator.listening_count += 1;
+                }
+                CardIndicator::NativeToTarget { lexeme } => {
+                    iterator.production_lexemes.insert(*lexeme);
+                }
+            }
         }
+        iterator
     }
 
-    fn next_text_card(&self) -> Option<CardIndicator<Spur>> {
-        let known_words: BTreeSet<Lexeme<Spur>> = self
-            .cards
-            .iter()
-            .filter_map(CardIndicator::target_language)
-            .cloned()
-            .collect();
-        for lexeme in self.language_pack.word_frequencies.keys() {
-            if known_words.contains(lexeme) {
-                continue;
-            }
-            if self.cards.len() < 20 && lexeme.multiword().is_some() {
-                continue;
+    fn lexeme_at(&self, index: usize) -> Option<Lexeme<Spur>> {
+        self.language_pack
+            .word_frequencies
+            .get_index(index)
+            .map(|(lexeme, _)| *lexeme)
+    }
+
+    fn next_text_card(&mut self) -> Option<CardIndicator<Spur>> {
+        let multiwords_allowed = self.total_count >= self.settings.multiword_min_cards as usize;
+        let index = if let Some(index) = self
+            .deferred_multiwords
+            .front()
+            .filter(|_| multiwords_allowed)
+        {
+            let index = *index;
+            self.deferred_multiwords.pop_front();
+            index
+        } else {
+            loop {
+                let index = *self.text_order.get(self.text_cursor)?;
+                let lexeme = self.lexeme_at(index)?;
+                self.text_cursor += 1;
+                if self.text_lexemes.contains(&lexeme) {
+                    continue;
+                }
+                if !multiwords_allowed && lexeme.multiword().is_some() {
+                    self.deferred_multiwords.push_back(index);
+                    continue;
+                }
+                break index;
             }
-            return Some(CardIndicator::TargetLanguage { lexeme: *lexeme });
+        };
+        let lexeme = self.lexeme_at(index)?;
+        self.text_lexemes.insert(lexeme);
+        if index < self.listening_cursor {
+            self.listening_backlog.push(Reverse(index));
         }
-        None
+        Some(CardIndicator::TargetLanguage { lexeme })
+    }
+
+    /// The pronunciation of the word at `index` in the frequency list, if it still needs a listening card.
+    fn listening_candidate(&self, index: usize) -> Option<Spur> {
+        let lexeme = self.lexeme_at(index)?;
+        if !self.text_lexemes.contains(&lexeme) {
+            return None;
+        }
+        let heteronym = lexeme.heteronym()?;
+        let Some(&pronunciation) = self
+            .language_pack
+            .word_to_pronunciation
+            .get(&heteronym.word)
+        else {
+            log::error!(
+                "Word {heteronym:?} was in the deck, but was not found in word_to_pronunciation",
+                heteronym = heteronym.resolve(&self.language_pack.rodeo)
+            );
+            return None;
+        };
+        (!self.pronunciations.contains(&pronunciation)).then_some(pronunciation)
     }
 
-    fn next_listening_card(&self) -> Option<CardIndicator<Spur>> {
-        let known_pronunciations: BTreeSet<Spur> = self
-            .cards
-            .iter()
-            .filter_map(CardIndicator::listening_homophonous)
-            .copied()
-            .collect();
-        let known_words: BTreeSet<Lexeme<Spur>> = self
-            .cards
-            .iter()
-            .filter_map(CardIndicator::target_language)
-            .cloned()
-            .collect();
-        for lexeme in self.language_pack.word_frequencies.keys() {
-            if !known_words.contains(lexeme) {
+    fn next_listening_card(&mut self) -> Option<CardIndicator<Spur>> {
+        let pronunciation = loop {
+            if let Some(Reverse(index)) = self.listening_backlog.pop() {
+                if let Some(pronunciation) = self.listening_candidate(index) {
+                    break pronunciation;
+                }
                 continue;
             }
-            let heteronym = match lexeme.heteronym() {
-                Some(h) => h,
-                None => continue,
-            };
-            let Some(&pronunciation) = self
-                .language_pack
-                .word_to_pronunciation
-                .get(&heteronym.word)
-            else {
-                log::error!(
-                    "Word {heteronym:?} was in the deck, but was not found in word_to_pronunciation",
-                    heteronym = heteronym.resolve(&self.language_pack.rodeo)
-                );
-                continue;
-            };
-            if known_pronunciations.contains(&pronunciation) {
-                continue;
+            let index = self.listening_cursor;
+            self.lexeme_at(index)?;
+            self.listening_cursor += 1;
+            if let Some(pronunciation) = self.listening_candidate(index) {
+                break pronunciation;
             }
-            return Some(CardIndicator::ListeningHomophonous { pronunciation });
+        };
+        self.pronunciations.insert(pronunciation);
+        Some(CardIndicator::ListeningHomophonous { pronunciation })
+    }
+
+    fn next_production_card(&mut self) -> Option<CardIndicator<Spur>> {
+        loop {
+            let lexeme = self.lexeme_at(self.production_cursor)?;
+            self.production_cursor += 1;
+            if self.recognized_lexemes.contains(&lexeme) && self.production_lexemes.insert(lexeme) {
+                return Some(CardIndicator::NativeToTarget { lexeme });
+            }
+        }
+    }
+
+    fn next_card(&mut self, ty: &ChallengeType) -> Option<CardIndicator<Spur>> {
+        let card = match ty {
+            ChallengeType::Text => self.next_text_card(),
+            ChallengeType::Listening => self.next_listening_card(),
+            ChallengeType::Production => self.next_production_card(),
+        }?;
+        self.total_count += 1;
+        match card {
+            CardIndicator::TargetLanguage { .. } => self.text_count += 1,
+            CardIndicator::ListeningHomophonous { .. } => self.listening_count += 1,
+            CardIndicator::NativeToTarget { .. } => {}
         }
-        None
+        Some(card)
     }
 }
 
@@ -89,32 +204,16 @@ impl Iterator for NextCardsIterator<'_> {
         }
 
         if self.permitted_types.len() == 1 {
-            let card = match self.permitted_types[0] {
-                ChallengeType::Text => self.next_text_card(),
-                ChallengeType::Listening => self.next_listening_card(),
-            }?;
-            self.cards.push(card.clone());
-            return Some(card);
+            let ty = self.permitted_types[0].clone();
+            return self.next_card(&ty);
         }
 
-        if self.cards.len() < 20 {
-            let card = self.next_text_card()?;
-            self.cards.push(card.clone());
-            return Some(card);
+        if self.total_count < self.settings.text_only_cards as usize {
+            return self.next_card(&ChallengeType::Text);
         }
 
-        let text_count = self
-            .cards
-            .iter()
-            .filter(|c| matches!(c, CardIndicator::TargetLanguage { .. }))
-            .count();
-        let listening_count = self
-            .cards
-            .iter()
-            .filter(|c| matches!(c, CardIndicator::ListeningHomophonous { .. }))
-            .count();
-
-        let desired = if listening_count < text_count / 2 {
+        let text_per_listening = self.settings.text_cards_per_listening_card.max(1) as usize;
+        let desired = if self.listening_count < self.text_count / text_per_listening {
             ChallengeType::Listening
         } else {
             ChallengeType::Text
@@ -126,16 +225,49 @@ impl Iterator for NextCardsIterator<'_> {
             ChallengeType::Text
         };
 
-        for ty in [desired, other] {
-            let card = match ty {
-                ChallengeType::Text => self.next_text_card(),
-                ChallengeType::Listening => self.next_listening_card(),
-            };
-            if let Some(card) = card {
-                self.cards.push(card.clone());
-                return Some(card);
-            }
-        }
-        None
+        [desired, other].iter().find_map(|ty| self.next_card(ty))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::{apply, lexeme_card, test_deck};
+    use crate::{CardIndicator, CardType, LanguageEventContent};
+
+    #[test]
+    fn next_cards_follow_frequency() {
+        let start = chrono::DateTime::parse_from_rfc3339("2025-01-01T12:00:00Z")
+            .unwrap()
+            .to_utc();
+        let deck = apply(
+            test_deck(),
+            &[(
+                start,
+                LanguageEventContent::AddCards {
+                    cards: vec![lexeme_card("chat")],
+                },
+            )],
+        );
+        let rodeo = &deck.language_pack.rodeo;
+        let resolve = |cards: Vec<CardIndicator<lasso::Spur>>| -> Vec<CardIndicator<String>> {
+            cards.iter().map(|card| card.resolve(rodeo)).collect()
+        };
+
+        // With a small deck, multiword terms are held back and cards already in the deck are skipped
+        let text = resolve(
+            deck.next_unknown_cards(Some(CardType::TargetLanguage))
+                .collect(),
+        );
+        let words = ["le", "mange", "chien", "mais", "mes", "chez", "manges"];
+        assert_eq!(text, words.map(lexeme_card).to_vec());
+
+        // Listening cards are only suggested for words with a text card
+        let listening = resolve(deck.next_unknown_cards(Some(CardType::Listening)).collect());
+        assert_eq!(
+            listening,
+            vec![CardIndicator::ListeningHomophonous {
+                pronunciation: "ʃa".to_string()
+            }]
+        );
     }
 }
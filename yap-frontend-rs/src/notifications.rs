@@ -123,8 +123,19 @@ impl Deck {
         // so we need to subtract it to get local time
         let local_now = now - chrono::Duration::minutes(timezone_offset_minutes as i64);
 
-        // Get all cards sorted by due date
-        let cards = self.get_all_cards_summary();
+        // Get all cards sorted by due date. Suspended cards never come due, and buried cards
+        // come due once they're unburied.
+        let cards: Vec<CardSummary> = self
+            .get_all_cards_summary()
+            .into_iter()
+            .filter(|card| !card.suspended)
+            .map(|card| CardSummary {
+                due_timestamp_ms: card
+                    .due_timestamp_ms
+                    .max(card.buried_until_ms.unwrap_or(f64::MIN)),
+                ..card
+            })
+            .collect();
 
         // Find cards that are due
         let due_cards: Vec<&CardSummary> = cards
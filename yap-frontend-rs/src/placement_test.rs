@@ -0,0 +1,285 @@
+//! An adaptive vocabulary placement test for users who already know some of the language.
+//!
+//! We model the user as knowing every word more frequent than some "frontier" rank, with a soft falloff around it.
+//! Each answer updates a posterior over the frontier (a grid of ranks, evenly spaced in log space), and the next word
+//! asked is the one nearest the current median, so the test behaves like a noisy binary search across frequency bands.
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use language_utils::{Language, Lexeme};
+use wasm_bindgen::prelude::*;
+
+use crate::{CardIndicator, Deck, DeckEvent, LanguageEvent, LanguageEventContent, LanguagePack};
+
+/// How sharply the probability of knowing a word falls off around the frontier (per unit of log rank).
+const SLOPE: f64 = 3.0;
+/// Users sometimes don't recognize a word they know, or think they know one they don't.
+const SLIP: f64 = 0.05;
+const GRID_SIZE: usize = 100;
+/// The estimate counts as confident once this much of the posterior is within `CONFIDENCE_RATIO` of it.
+const CONFIDENCE_RATIO: f64 = 1.5;
+const TARGET_CONFIDENCE: f64 = 0.8;
+const MAX_QUESTIONS: usize = 40;
+/// Ask slightly above and below the estimate in turn, so that we don't keep asking about the same band.
+const OFFSETS: [f64; 3] = [1.0, 0.75, 1.33];
+
+#[derive(Clone, Debug, tsify::Tsify, serde::Serialize, serde::Deserialize)]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+pub struct PlacementQuestion {
+    pub lexeme: Lexeme<String>,
+    /// The word's position in the frequency list, 0 being the most frequent.
+    pub rank: usize,
+}
+
+#[derive(Clone, Debug, PartialEq, tsify::Tsify, serde::Serialize, serde::Deserialize)]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+pub struct PlacementEstimate {
+    /// The user probably knows the words ranked below this.
+    pub frontier: usize,
+    /// How much of the probability is within a factor of 1.5 of `frontier`, from 0 to 1.
+    pub confidence: f64,
+    pub questions_answered: usize,
+    pub finished: bool,
+}
+
+#[derive(Clone, Debug)]
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+pub struct PlacementTest {
+    language_pack: Arc<LanguagePack>,
+    target_language: Language,
+    /// Rank to whether the user said they knew the word.
+    responses: BTreeMap<usize, bool>,
+}
+
+/// The candidate frontiers, from 1 to `num_ranks`, evenly spaced in log space.
+fn grid(num_ranks: usize) -> Vec<f64> {
+    let max = (num_ranks.max(2) as f64).ln();
+    (0..GRID_SIZE)
+        .map(|i| (max * i as f64 / (GRID_SIZE - 1) as f64).exp())
+        .collect()
+}
+
+fn probability_known(rank: usize, frontier: f64) -> f64 {
+    let p = 1.0 / (1.0 + (SLOPE * (((rank + 1) as f64).ln() - frontier.ln())).exp());
+    SLIP + (1.0 - 2.0 * SLIP) * p
+}
+
+fn posterior(responses: &BTreeMap<usize, bool>, grid: &[f64]) -> Vec<f64> {
+    let log_likelihoods: Vec<f64> = grid
+        .iter()
+        .map(|&frontier| {
+            responses
+                .iter()
+                .map(|(&rank, &known)| {
+                    let p = probability_known(rank, frontier);
+                    if known { p.ln() } else { (1.0 - p).ln() }
+                })
+                .sum()
+        })
+        .collect();
+    let max = log_likelihoods
+        .iter()
+        .copied()
+        .fold(f64::NEG_INFINITY, f64::max);
+    let weights: Vec<f64> = log_likelihoods.iter().map(|l| (l - max).exp()).collect();
+    let total: f64 = weights.iter().sum();
+    weights.into_iter().map(|w| w / total).collect()
+}
+
+fn estimate(responses: &BTreeMap<usize, bool>, num_ranks: usize) -> PlacementEstimate {
+    let grid = grid(num_ranks);
+    let posterior = posterior(responses, &grid);
+
+    let mut cumulative = 0.0;
+    let median = grid
+        .iter()
+        .zip(&posterior)
+        .find(|(_, p)| {
+            cumulative += *p;
+            cumulative >= 0.5
+        })
+        .map(|(frontier, _)| *frontier)
+        .unwrap_or(1.0);
+    let confidence = grid
+        .iter()
+        .zip(&posterior)
+        .filter(|(frontier, _)| (*frontier / median).max(median / *frontier) <= CONFIDENCE_RATIO)
+        .map(|(_, p)| p)
+        .sum::<f64>();
+
+    let questions_answered = responses.len();
+    PlacementEstimate {
+        frontier: (median.round() as usize).min(num_ranks),
+        confidence,
+        questions_answered,
+        finished: questions_answered >= MAX_QUESTIONS
+            || (questions_answered > 0 && confidence >= TARGET_CONFIDENCE),
+    }
+}
+
+/// Of the `candidates` we haven't asked about yet, the one closest to where we'd like to ask next.
+fn next_rank(
+    responses: &BTreeMap<usize, bool>,
+    num_ranks: usize,
+    candidates: impl Iterator<Item = usize>,
+) -> Option<usize> {
+    let estimate = estimate(responses, num_ranks);
+    let target = (estimate.frontier as f64 * OFFSETS[responses.len() % OFFSETS.len()])
+        .max(1.0)
+        .ln();
+    candidates
+        .filter(|rank| !responses.contains_key(rank))
+        .min_by(|a, b| {
+            let a = (((a + 1) as f64).ln() - target).abs();
+            let b = (((b + 1) as f64).ln() - target).abs();
+            a.total_cmp(&b)
+        })
+}
+
+impl PlacementTest {
+    fn num_ranks(&self) -> usize {
+        self.language_pack.word_frequencies.len()
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+impl Deck {
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+    pub fn start_placement_test(&self) -> PlacementTest {
+        PlacementTest {
+            language_pack: self.language_pack.clone(),
+            target_language: self.target_language,
+            responses: BTreeMap::new(),
+        }
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+impl PlacementTest {
+    /// The next word to ask the user about, or `None` once we're confident enough (or out of words).
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+    pub fn next_question(&self) -> Option<PlacementQuestion> {
+        if self.estimate().finished {
+            return None;
+        }
+        let word_frequencies = &self.language_pack.word_frequencies;
+        let rank = next_rank(
+            &self.responses,
+            self.num_ranks(),
+            word_frequencies
+                .keys()
+                .enumerate()
+                // Multiword terms are harder to judge at a glance, so we only ask about single words
+                .filter(|(_, lexeme)| lexeme.heteronym().is_some())
+                .map(|(rank, _)| rank),
+        )?;
+        let (lexeme, _) = word_frequencies.get_index(rank)?;
+        Some(PlacementQuestion {
+            lexeme: lexeme.resolve(&self.language_pack.rodeo),
+            rank,
+        })
+    }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+    pub fn answer(&mut self, rank: usize, known: bool) {
+        if rank < self.num_ranks() {
+            self.responses.insert(rank, known);
+        }
+    }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+    pub fn estimate(&self) -> PlacementEstimate {
+        estimate(&self.responses, self.num_ranks())
+    }
+
+    /// The events that seed `deck` with everything below the estimated frontier: the cards are added, and all but
+    /// the ones the user said they didn't know are marked as known.
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+    pub fn finish(&self, deck: &Deck) -> Vec<DeckEvent> {
+        let frontier = self.estimate().frontier;
+        let rodeo = &self.language_pack.rodeo;
+        let cards_below_frontier = self
+            .language_pack
+            .word_frequencies
+            .keys()
+            .take(frontier)
+            .enumerate()
+            .map(|(rank, lexeme)| (rank, CardIndicator::TargetLanguage { lexeme: *lexeme }));
+
+        let mut to_add = Vec::new();
+        let mut to_mark_known = Vec::new();
+        for (rank, card) in cards_below_frontier {
+            if !deck.cards.contains_key(&card) {
+                to_add.push(card.resolve(rodeo));
+            }
+            if self.responses.get(&rank) != Some(&false) && deck.is_unknown(&card) {
+                to_mark_known.push(card.resolve(rodeo));
+            }
+        }
+
+        [
+            (!to_add.is_empty()).then_some(LanguageEventContent::AddCards { cards: to_add }),
+            (!to_mark_known.is_empty()).then_some(LanguageEventContent::MarkKnown {
+                cards: to_mark_known,
+            }),
+        ]
+        .into_iter()
+        .flatten()
+        .map(|content| {
+            DeckEvent::Language(LanguageEvent {
+                language: self.target_language,
+                content,
+            })
+        })
+        .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use crate::tests::{apply, lexeme_card, test_deck};
+
+    #[test]
+    fn placement_test_finds_the_frontier() {
+        let num_ranks = 20_000;
+        let frontier = 2_000;
+        let mut responses = BTreeMap::new();
+        let estimate = loop {
+            let estimate = super::estimate(&responses, num_ranks);
+            if estimate.finished {
+                break estimate;
+            }
+            let rank = super::next_rank(&responses, num_ranks, 0..num_ranks).unwrap();
+            responses.insert(rank, rank < frontier);
+        };
+        assert!(estimate.questions_answered < super::MAX_QUESTIONS);
+        assert!(estimate.confidence >= super::TARGET_CONFIDENCE);
+        let ratio = estimate.frontier as f64 / frontier as f64;
+        assert!((0.67..=1.5).contains(&ratio), "{estimate:?}");
+
+        // The user knows the three most frequent words, but not "chat"
+        let deck = test_deck();
+        let mut test = deck.start_placement_test();
+        while let Some(question) = test.next_question() {
+            let known = question.rank < 3 && question.lexeme.heteronym().unwrap().word != "chat";
+            test.answer(question.rank, known);
+        }
+        let events = test.finish(&deck);
+        let now = chrono::Utc::now();
+        let deck = apply(
+            deck,
+            &events
+                .into_iter()
+                .map(|crate::DeckEvent::Language(event)| (now, event.content))
+                .collect::<Vec<_>>(),
+        );
+        let le = deck.get_card_detail(lexeme_card("le")).unwrap();
+        assert_eq!(le.state, "review");
+        let chat = deck.get_card_detail(lexeme_card("chat")).unwrap();
+        assert_eq!(chat.state, "new");
+        assert!(deck.get_card_detail(lexeme_card("mes")).is_none());
+    }
+}
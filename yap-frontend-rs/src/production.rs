@@ -0,0 +1,289 @@
+//! Production practice: the user is given something in their native language and has to come up with the target
+//! language. Production has its own cards (`CardIndicator::NativeToTarget`), and so its own FSRS state for each
+//! lexeme, since being able to recognize a word is no guarantee of being able to produce it.
+
+use chrono::{DateTime, Utc};
+use language_utils::{Language, Lexeme, Literal, TtsProvider, TtsRequest, autograde};
+use lasso::Spur;
+use wasm_bindgen::prelude::*;
+
+use crate::utils::hit_ai_server;
+use crate::{
+    AudioRequest, Deck, DeckEvent, LanguageEvent, LanguageEventContent, SentenceReviewIndicator,
+    SentenceReviewResult,
+};
+
+/// Translate a native language sentence into the target language. The sentences are the same ones used for
+/// `TranslateComprehensibleSentence`, so every word needed is one the user can at least recognize.
+#[derive(tsify::Tsify, serde::Serialize, serde::Deserialize, Debug, Clone)]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+pub struct TranslateToTargetLanguage<S> {
+    pub(crate) audio: AudioRequest,
+    native_language: S,
+    /// A correct answer, to show once the user has submitted theirs.
+    pub(crate) target_language: S,
+    target_language_literals: Vec<Literal<S>>,
+    primary_expression: Lexeme<S>,
+    unique_target_language_lexemes: Vec<Lexeme<S>>,
+}
+
+impl TranslateToTargetLanguage<Spur> {
+    pub(crate) fn resolve(&self, rodeo: &lasso::RodeoReader) -> TranslateToTargetLanguage<String> {
+        TranslateToTargetLanguage {
+            audio: self.audio.clone(),
+            native_language: rodeo.resolve(&self.native_language).to_string(),
+            target_language: rodeo.resolve(&self.target_language).to_string(),
+            target_language_literals: self
+                .target_language_literals
+                .iter()
+                .map(|l| l.resolve(rodeo))
+                .collect(),
+            primary_expression: self.primary_expression.resolve(rodeo),
+            unique_target_language_lexemes: self
+                .unique_target_language_lexemes
+                .iter()
+                .map(|l| l.resolve(rodeo))
+                .collect(),
+        }
+    }
+}
+
+impl Deck {
+    pub(crate) fn get_production_challenge(
+        &self,
+        lexeme: &Lexeme<Spur>,
+        now: DateTime<Utc>,
+    ) -> Option<TranslateToTargetLanguage<Spur>> {
+        let sentence = self.get_comprehensible_sentence_containing(
+            lexeme,
+            &self.sentences_reviewed,
+            &self.language_pack,
+            now,
+        )?;
+        Some(TranslateToTargetLanguage {
+            audio: AudioRequest {
+                request: TtsRequest {
+                    text: self
+                        .language_pack
+                        .rodeo
+                        .resolve(&sentence.target_language)
+                        .to_string(),
+                    language: self.target_language,
+                },
+                provider: TtsProvider::ElevenLabs,
+            },
+            native_language: *sentence.native_languages.first()?,
+            target_language: sentence.target_language,
+            target_language_literals: sentence.target_language_literals,
+            primary_expression: *lexeme,
+            unique_target_language_lexemes: sentence.unique_target_language_lexemes,
+        })
+    }
+
+    fn production_event(
+        &self,
+        challenge_sentence: String,
+        result: SentenceReviewResult,
+    ) -> DeckEvent {
+        DeckEvent::Language(LanguageEvent {
+            language: self.target_language,
+            content: LanguageEventContent::TranslationChallenge {
+                review: SentenceReviewIndicator::NativeToTarget {
+                    challenge_sentence,
+                    result,
+                },
+            },
+        })
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+impl Deck {
+    /// `challenge_sentence` is the target language sentence (`TranslateToTargetLanguage::target_language`).
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+    pub fn produce_sentence_perfect(&self, challenge_sentence: String) -> Option<DeckEvent> {
+        Some(self.production_event(challenge_sentence, SentenceReviewResult::Perfect {}))
+    }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+    pub fn produce_sentence_wrong(
+        &self,
+        challenge_sentence: String,
+        submission: String,
+        words_remembered: Vec<Lexeme<String>>,
+        words_forgotten: Vec<Lexeme<String>>,
+    ) -> Option<DeckEvent> {
+        Some(self.production_event(
+            challenge_sentence,
+            SentenceReviewResult::Wrong {
+                submission,
+                lexemes_remembered: words_remembered.into_iter().collect(),
+                lexemes_forgotten: words_forgotten.into_iter().collect(),
+            },
+        ))
+    }
+}
+
+/// The words of a sentence, ignoring case and punctuation.
+fn normalized_words(sentence: &str) -> Vec<String> {
+    sentence
+        .split_whitespace()
+        .map(|word| {
+            word.trim_matches(|c: char| c.is_ascii_punctuation() || c == '¿' || c == '¡')
+                .to_lowercase()
+        })
+        .filter(|word| !word.is_empty())
+        .collect()
+}
+
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+pub async fn autograde_production(
+    challenge_sentence: String,
+    reference_sentence: String,
+    user_sentence: String,
+    primary_expression: Lexeme<String>,
+    lexemes: Vec<Lexeme<String>>,
+    access_token: Option<String>,
+    language: Language,
+) -> Result<autograde::AutoGradeTranslationResponse, JsValue> {
+    // Matching the reference translation is always right, so there's no need to ask the server
+    if normalized_words(&user_sentence) == normalized_words(&reference_sentence) {
+        return Ok(autograde::AutoGradeTranslationResponse {
+            explanation: None,
+            primary_expression_status: autograde::Remembered::Remembered,
+            expressions_remembered: lexemes,
+            expressions_forgot: Vec::new(),
+        });
+    }
+
+    let request = autograde::AutoGradeProductionRequest {
+        challenge_sentence,
+        reference_sentence,
+        user_sentence,
+        primary_expression: primary_expression.clone(),
+        lexemes,
+        language,
+    };
+
+    let response = hit_ai_server("/autograde-production", request, access_token.as_ref())
+        .await
+        .map_err(|e| JsValue::from_str(&format!("Request error: {e:?}")))?;
+
+    if !response.ok() {
+        return Err(JsValue::from_str(&format!(
+            "HTTP error: {}",
+            response.status()
+        )));
+    }
+
+    let response: autograde::AutoGradeTranslationResponse = response
+        .json()
+        .await
+        .map_err(|e| JsValue::from_str(&format!("Response parsing error: {e:?}")))?;
+
+    Ok(crate::include_primary_expression(
+        response,
+        primary_expression,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::card_detail::ReviewSource;
+    use crate::tests::{apply, lexeme_card, test_deck};
+    use crate::{CardIndicator, CardType, Challenge, LanguageEventContent};
+
+    fn production_card(word: &str) -> CardIndicator<String> {
+        let CardIndicator::TargetLanguage { lexeme } = lexeme_card(word) else {
+            unreachable!()
+        };
+        CardIndicator::NativeToTarget { lexeme }
+    }
+
+    #[test]
+    fn production_cards_have_their_own_schedule() {
+        let start = chrono::DateTime::parse_from_rfc3339("2025-01-01T12:00:00Z")
+            .unwrap()
+            .to_utc();
+        let words = ["le", "chat", "mange"];
+        let mut events = vec![(
+            start,
+            LanguageEventContent::AddCards {
+                cards: words.map(lexeme_card).to_vec(),
+            },
+        )];
+        for word in words {
+            events.push((
+                start,
+                LanguageEventContent::ReviewCard {
+                    reviewed: lexeme_card(word),
+                    rating: "easy".to_string(),
+                },
+            ));
+        }
+        let deck = apply(test_deck(), &events);
+
+        // Only words the user has already started learning are offered for production, most frequent first
+        let next = deck.add_next_unknown_cards(Some(CardType::NativeToTarget), 10);
+        let Some(crate::DeckEvent::Language(next)) = next else {
+            unreachable!()
+        };
+        let LanguageEventContent::AddCards { cards } = &next.content else {
+            unreachable!()
+        };
+        assert_eq!(cards, &words.map(production_card).to_vec());
+
+        let later = start + chrono::Duration::days(1);
+        let deck = apply(deck, &[(later, next.content)]);
+        let chat = production_card("chat");
+
+        // A new production card is a flashcard with the meaning on the front
+        let index = deck
+            .cards
+            .get_index_of(&chat.get_interned(&deck.language_pack.rodeo).unwrap())
+            .unwrap();
+        assert!(matches!(
+            deck.get_review_info(vec![])
+                .get_challenge_for_card(&deck, index),
+            Some(Challenge::FlashCardReview {
+                is_production: true,
+                ..
+            })
+        ));
+
+        let crate::CardIndicator::NativeToTarget { lexeme } =
+            chat.get_interned(&deck.language_pack.rodeo).unwrap()
+        else {
+            unreachable!()
+        };
+        let challenge = deck
+            .get_production_challenge(&lexeme, chrono::Utc::now())
+            .unwrap();
+        assert_eq!(
+            deck.language_pack.rodeo.resolve(&challenge.native_language),
+            "The cat eats."
+        );
+
+        let crate::DeckEvent::Language(event) = deck
+            .produce_sentence_wrong(
+                "Le chat mange.".to_string(),
+                "Le chien mange.".to_string(),
+                vec![],
+                vec![lexeme.resolve(&deck.language_pack.rodeo)],
+            )
+            .unwrap();
+        let deck = apply(deck, &[(later, event.content)]);
+
+        let production = deck.get_card_detail(chat).unwrap();
+        assert_eq!(production.reviews.len(), 1);
+        assert_eq!(
+            production.reviews[0].source,
+            ReviewSource::ProductionChallenge
+        );
+        assert_eq!(production.reviews[0].rating, "again");
+        // The recognition card isn't affected
+        let recognition = deck.get_card_detail(lexeme_card("chat")).unwrap();
+        assert_eq!(recognition.reviews.len(), 1);
+        assert_eq!(recognition.state, "review");
+    }
+}
@@ -0,0 +1,169 @@
+//! Scoring for which comprehensible sentence to use in a sentence challenge. The best sentences review several due
+//! cards at once, haven't been seen lately, and are neither a single word nor a paragraph.
+
+use crate::deck_settings::SentenceSelectionSettings;
+
+/// What's known about a sentence when deciding whether to use it.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct SentenceCandidate {
+    /// How many words the sentence has.
+    pub(crate) length: usize,
+    /// How many due cards, besides the one being reviewed, the sentence contains.
+    pub(crate) other_due_cards: usize,
+    pub(crate) times_reviewed: u32,
+    /// Days since the sentence was last shown, or `None` if it never has been.
+    pub(crate) days_since_seen: Option<f64>,
+    /// How many words in the sentence aren't mature yet (at most one, and only when that's allowed).
+    pub(crate) unmature_words: usize,
+}
+
+impl SentenceSelectionSettings {
+    /// Higher is better.
+    pub(crate) fn score(&self, candidate: &SentenceCandidate) -> f64 {
+        let due_cards = self.due_card_weight.into_inner() * candidate.other_due_cards as f64;
+        let review_count = self.review_count_weight.into_inner() * candidate.times_reviewed as f64;
+        let recency = candidate.days_since_seen.map_or(0.0, |days| {
+            self.recency_weight.into_inner() * 0.5f64.powf(days.max(0.0))
+        });
+        let length = self.length_weight.into_inner()
+            * (candidate.length.max(1) as f64 / self.ideal_length.max(1) as f64)
+                .ln()
+                .abs();
+        let unmature = self.unmature_word_weight.into_inner() * candidate.unmature_words as f64;
+        due_cards - review_count - recency - length - unmature
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ordered_float::NotNan;
+
+    use super::SentenceCandidate;
+    use crate::deck_settings::SentenceSelectionSettings;
+    use crate::tests::{apply, lexeme_card, test_deck};
+    use crate::{
+        CardIndicator, LanguageEventContent, SentenceReviewIndicator, SentenceReviewResult,
+    };
+
+    fn candidate() -> SentenceCandidate {
+        SentenceCandidate {
+            length: 8,
+            ..SentenceCandidate::default()
+        }
+    }
+
+    #[test]
+    fn sentence_scores() {
+        let settings = SentenceSelectionSettings::default();
+        let score = |candidate: SentenceCandidate| settings.score(&candidate);
+        assert_eq!(score(candidate()), 0.0);
+
+        // Covering more due cards is better
+        let more_due = SentenceCandidate {
+            other_due_cards: 2,
+            ..candidate()
+        };
+        assert!(score(more_due) > score(candidate()));
+
+        // Seeing a sentence yesterday is worse than last week, which is worse than never
+        let seen = |days| SentenceCandidate {
+            days_since_seen: Some(days),
+            ..candidate()
+        };
+        assert!(score(seen(1.0)) < score(seen(7.0)));
+        assert!(score(seen(7.0)) < score(candidate()));
+
+        // Too short and too long are both penalized, equally so for half and double the ideal length
+        let length = |length| SentenceCandidate {
+            length,
+            ..candidate()
+        };
+        assert!(score(length(2)) < score(length(6)));
+        assert!(score(length(20)) < score(length(10)));
+        assert!((score(length(4)) - score(length(16))).abs() < 1e-9);
+
+        // A sentence reviewed less often wins, all else being equal
+        let reviewed = SentenceCandidate {
+            times_reviewed: 3,
+            ..candidate()
+        };
+        assert!(score(reviewed) < score(candidate()));
+
+        let unmature = SentenceCandidate {
+            unmature_words: 1,
+            ..candidate()
+        };
+        assert!(score(unmature.clone()) < score(candidate()));
+
+        // Weights can turn considerations off entirely
+        let settings = SentenceSelectionSettings {
+            length_weight: NotNan::new(0.0).unwrap(),
+            unmature_word_weight: NotNan::new(0.0).unwrap(),
+            ..settings
+        };
+        assert_eq!(settings.score(&length(30)), 0.0);
+        assert_eq!(settings.score(&unmature), 0.0);
+    }
+
+    #[test]
+    fn recently_seen_sentences_are_avoided_until_they_wear_off() {
+        let start = chrono::DateTime::parse_from_rfc3339("2025-01-01T12:00:00Z")
+            .unwrap()
+            .to_utc();
+        let words = ["le", "chat", "mange", "chien", "mais"];
+        let mut events = vec![(
+            start,
+            LanguageEventContent::AddCards {
+                cards: words.map(lexeme_card).to_vec(),
+            },
+        )];
+        for word in words {
+            events.push((
+                start,
+                LanguageEventContent::ReviewCard {
+                    reviewed: lexeme_card(word),
+                    rating: "easy".to_string(),
+                },
+            ));
+        }
+        let seen = start + chrono::Duration::days(1);
+        events.push((
+            seen,
+            LanguageEventContent::TranslationChallenge {
+                review: SentenceReviewIndicator::TargetToNative {
+                    challenge_sentence: "Le chat mange mais le chien mange.".to_string(),
+                    result: SentenceReviewResult::Perfect {},
+                },
+            },
+        ));
+        let deck = apply(test_deck(), &events);
+
+        let rodeo = &deck.language_pack.rodeo;
+        let CardIndicator::TargetLanguage { lexeme: mange } =
+            lexeme_card("mange").get_interned(rodeo).unwrap()
+        else {
+            unreachable!()
+        };
+        let sentence_at = |now| {
+            let sentence = deck
+                .get_comprehensible_sentence_containing(
+                    &mange,
+                    &deck.sentences_reviewed,
+                    &deck.language_pack,
+                    now,
+                )
+                .unwrap();
+            rodeo.resolve(&sentence.target_language).to_string()
+        };
+
+        // The long sentence is the best fit, but not an hour after it was shown
+        assert_ne!(
+            sentence_at(seen + chrono::Duration::hours(1)),
+            "Le chat mange mais le chien mange."
+        );
+        assert_eq!(
+            sentence_at(seen + chrono::Duration::days(7)),
+            "Le chat mange mais le chien mange."
+        );
+    }
+}
@@ -0,0 +1,198 @@
+//! Sibling burying. A word's text card, production card and listening card all test the same word, and a sentence
+//! challenge for one of them usually exercises the others too. So once one is reviewed, the rest are buried until
+//! the next day (see `BurySettings`). This happens while replaying reviews, so every device agrees on what's buried.
+
+use chrono::{DateTime, Utc};
+use language_utils::Lexeme;
+use lasso::Spur;
+
+use crate::{CardIndicator, Deck};
+
+impl Deck {
+    /// The other cards in the deck for the same word as `card`: text and production cards for every heteronym spelled
+    /// the same way, and the listening card for how it's pronounced. A listening card's siblings are the cards for
+    /// every word it could be.
+    pub(crate) fn siblings(&self, card: &CardIndicator<Spur>) -> Vec<CardIndicator<Spur>> {
+        let language_pack = &self.language_pack;
+        let mut siblings = Vec::new();
+        let words: Vec<Spur> = match card {
+            CardIndicator::TargetLanguage { lexeme } | CardIndicator::NativeToTarget { lexeme } => {
+                match lexeme {
+                    Lexeme::Heteronym(heteronym) => vec![heteronym.word],
+                    Lexeme::Multiword(_) => {
+                        siblings.push(CardIndicator::TargetLanguage { lexeme: *lexeme });
+                        siblings.push(CardIndicator::NativeToTarget { lexeme: *lexeme });
+                        vec![]
+                    }
+                }
+            }
+            CardIndicator::ListeningHomophonous { pronunciation } => language_pack
+                .pronunciation_to_words
+                .get(pronunciation)
+                .cloned()
+                .unwrap_or_default(),
+        };
+        for word in words {
+            for heteronym in language_pack
+                .words_to_heteronyms
+                .get(&word)
+                .into_iter()
+                .flatten()
+            {
+                let lexeme = Lexeme::Heteronym(*heteronym);
+                siblings.push(CardIndicator::TargetLanguage { lexeme });
+                siblings.push(CardIndicator::NativeToTarget { lexeme });
+            }
+            if let Some(pronunciation) = language_pack.word_to_pronunciation.get(&word) {
+                siblings.push(CardIndicator::ListeningHomophonous {
+                    pronunciation: *pronunciation,
+                });
+            }
+        }
+        siblings.sort();
+        siblings.dedup();
+        siblings.retain(|sibling| sibling != card && self.cards.contains_key(sibling));
+        siblings
+    }
+
+    /// Bury the siblings of `card`, which was just reviewed at `timestamp`, until the start of the next day. Siblings
+    /// still in their learning or relearning steps are left alone, so a step due in ten minutes isn't put off until
+    /// tomorrow.
+    pub(crate) fn bury_siblings(&mut self, card: &CardIndicator<Spur>, timestamp: DateTime<Utc>) {
+        let settings = self.settings.burying;
+        if !settings.bury_new_siblings && !settings.bury_review_siblings {
+            return;
+        }
+        let buried_until = self.settings.day.next_day_start(timestamp);
+        for sibling in self.siblings(card) {
+            let Some(card_data) = self.cards.get_mut(&sibling) else {
+                continue;
+            };
+            let bury = match card_data.fsrs_card.state {
+                rs_fsrs::State::New => settings.bury_new_siblings,
+                rs_fsrs::State::Review => settings.bury_review_siblings,
+                rs_fsrs::State::Learning | rs_fsrs::State::Relearning => false,
+            };
+            if bury {
+                card_data.buried_until = Some(
+                    card_data
+                        .buried_until
+                        .map_or(buried_until, |until| until.max(buried_until)),
+                );
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{DateTime, Utc};
+
+    use crate::deck_settings::{BurySettings, LanguageSettingsEventContent};
+    use crate::tests::{apply, lexeme_card, test_deck, test_deck_with_settings};
+    use crate::{CardIndicator, Deck, LanguageEventContent};
+
+    fn buried_until(deck: &Deck, card: CardIndicator<String>) -> Option<DateTime<Utc>> {
+        let card = card.get_interned(&deck.language_pack.rodeo).unwrap();
+        deck.cards.get(&card).unwrap().buried_until
+    }
+
+    #[test]
+    fn siblings_are_buried() {
+        let start = chrono::DateTime::parse_from_rfc3339("2025-01-01T12:00:00Z")
+            .unwrap()
+            .to_utc();
+        let listening = |pronunciation: &str| CardIndicator::ListeningHomophonous {
+            pronunciation: pronunciation.to_string(),
+        };
+        let rate = |card: CardIndicator<String>, rating: &str| LanguageEventContent::ReviewCard {
+            reviewed: card,
+            rating: rating.to_string(),
+        };
+        let review = |card: CardIndicator<String>| rate(card, "good");
+        let add = (
+            start,
+            LanguageEventContent::AddCards {
+                cards: vec![
+                    lexeme_card("chat"),
+                    lexeme_card("le"),
+                    lexeme_card("mais"),
+                    lexeme_card("mes"),
+                    listening("ʃa"),
+                    listening("mɛ"),
+                ],
+            },
+        );
+        let added = apply(test_deck(), std::slice::from_ref(&add));
+
+        let deck = apply(
+            added.clone(),
+            &[
+                (start, review(lexeme_card("chat"))),
+                (start, review(listening("mɛ"))),
+            ],
+        );
+        // Until the next day starts, at 4am
+        let tomorrow = chrono::DateTime::parse_from_rfc3339("2025-01-02T04:00:00Z")
+            .unwrap()
+            .to_utc();
+        assert_eq!(buried_until(&deck, listening("ʃa")), Some(tomorrow));
+        assert_eq!(buried_until(&deck, lexeme_card("chat")), None);
+        assert_eq!(buried_until(&deck, lexeme_card("le")), None);
+        // A listening card's siblings are all the words it could be
+        assert_eq!(buried_until(&deck, lexeme_card("mais")), Some(tomorrow));
+        assert_eq!(buried_until(&deck, lexeme_card("mes")), Some(tomorrow));
+
+        // A sibling that's just been failed keeps its learning step
+        let deck = apply(
+            added.clone(),
+            &[
+                (start, rate(lexeme_card("mes"), "again")),
+                (
+                    start + chrono::Duration::minutes(1),
+                    review(listening("mɛ")),
+                ),
+            ],
+        );
+        assert_eq!(buried_until(&deck, lexeme_card("mes")), None);
+        assert_eq!(buried_until(&deck, lexeme_card("mais")), Some(tomorrow));
+
+        // A buried card that's failed anyway is unburied, so its relearning step comes up today
+        let failed = start + chrono::Duration::hours(1);
+        let deck = apply(
+            added,
+            &[
+                (start, rate(listening("ʃa"), "easy")),
+                (start, rate(lexeme_card("chat"), "easy")),
+                (failed, rate(listening("ʃa"), "again")),
+            ],
+        );
+        let card = listening("ʃa")
+            .get_interned(&deck.language_pack.rodeo)
+            .unwrap();
+        let card_data = deck.cards.get(&card).unwrap();
+        assert_eq!(card_data.fsrs_card.state, rs_fsrs::State::Relearning);
+        assert_eq!(card_data.buried_until, None);
+        assert!(card_data.fsrs_card.due < tomorrow);
+
+        // Turning off burying new siblings only applies from when it's turned off
+        let turned_off = start + chrono::Duration::hours(1);
+        let deck = test_deck_with_settings(&[(
+            turned_off,
+            LanguageSettingsEventContent::SetBurySettings(BurySettings {
+                bury_new_siblings: false,
+                ..BurySettings::default()
+            }),
+        )]);
+        let deck = apply(
+            deck,
+            &[
+                add,
+                (start, review(lexeme_card("mes"))),
+                (turned_off, review(lexeme_card("chat"))),
+            ],
+        );
+        assert_eq!(buried_until(&deck, listening("mɛ")), Some(tomorrow));
+        assert_eq!(buried_until(&deck, listening("ʃa")), None);
+    }
+}
@@ -1,10 +1,169 @@
-use crate::{Challenge, Deck, TranscribeComprehensibleSentence, TranslateComprehensibleSentence};
-use chrono::{Duration, Utc};
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+use crate::card_detail::ReviewSource;
+use crate::speaking::grade_transcript;
+use crate::{
+    BlankGrade, CardIndicator, Challenge, ClozePart, Deck, TranscribeComprehensibleSentence,
+    TranslateComprehensibleSentence,
+};
+use chrono::{DateTime, Duration, Utc};
 use language_utils::transcription_challenge;
+use rs_fsrs::Rating;
+#[cfg(target_arch = "wasm32")]
+use wasm_bindgen::prelude::*;
 use weapon::AppState;
 use weapon::data_model::Timestamped;
 
+#[derive(tsify::Tsify, serde::Serialize, serde::Deserialize, Debug, Clone)]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+pub struct SimulationOptions {
+    /// How many cards to add at the start of each day.
+    pub new_cards_per_day: u32,
+    /// How many days to simulate.
+    pub days: u32,
+    /// The same seed (with the same deck and options) always gives the same simulation.
+    pub seed: u32,
+    /// How far the chance of remembering a card can stray from what FSRS predicts, up or down, from 0 to 0.5.
+    pub recall_noise: f64,
+}
+
+#[derive(tsify::Tsify, serde::Serialize, serde::Deserialize, Debug, Clone, PartialEq)]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+pub struct SimulatedDay {
+    /// Days from today, starting at 0.
+    pub day: u32,
+    pub new_cards: u32,
+    /// Reviews of cards that had been seen before, including relearning ones that were forgotten.
+    pub reviews: u32,
+    /// Of those, how many were forgotten.
+    pub lapses: u32,
+    /// The number of words the user would be expected to remember at the end of the day.
+    pub expected_words_known: f64,
+    /// The fraction of words in typical text the user would be expected to know at the end of the day.
+    pub expected_coverage: f64,
+}
+
+/// SplitMix64, which is plenty for simulating reviews and doesn't need a dependency.
+struct SimulationRng(u64);
+
+impl SimulationRng {
+    fn next_f64(&mut self) -> f64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^= z >> 31;
+        (z >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
 impl Deck {
+    /// How much of the language the user would be expected to know at `now`, as (words, fraction of typical text).
+    fn expected_knowledge(&self, now: DateTime<Utc>) -> (f64, f64) {
+        let mut words = 0.0;
+        let mut occurrences = 0.0;
+        for (card, card_data) in self.cards.iter() {
+            let CardIndicator::TargetLanguage { lexeme } = card else {
+                continue;
+            };
+            let retrievability = card_data.fsrs_card.get_retrievability(now);
+            words += retrievability;
+            if let Some(frequency) = self.language_pack.word_frequencies.get(lexeme) {
+                occurrences += retrievability * frequency.count as f64;
+            }
+        }
+        (
+            words,
+            occurrences / self.language_pack.total_word_count as f64,
+        )
+    }
+
+    /// Simulate the user adding `options.new_cards_per_day` cards every day and reviewing everything that comes due.
+    /// Whether each card is remembered is drawn from its FSRS retrievability, nudged by up to `options.recall_noise`
+    /// either way. Daily review limits are ignored, since the point is to see the whole workload.
+    pub(crate) fn simulate_workload_from(
+        &self,
+        options: &SimulationOptions,
+        start: DateTime<Utc>,
+    ) -> Vec<SimulatedDay> {
+        let mut deck = self.clone();
+        let mut rng = SimulationRng(options.seed as u64);
+        let noise = options.recall_noise.clamp(0.0, 0.5);
+        let mut index = 0usize;
+
+        let mut days = Vec::new();
+        for day in 0..options.days {
+            let day_start = start + Duration::days(day as i64);
+            let day_end = day_start + Duration::days(1);
+            let cards_before = deck.cards.len();
+            if let Some(event) =
+                deck.add_next_unknown_cards(None, options.new_cards_per_day as usize)
+            {
+                deck = deck.apply_event(&Timestamped {
+                    timestamp: day_start,
+                    within_device_events_index: index,
+                    event,
+                });
+                index += 1;
+            }
+            let new_cards = (deck.cards.len() - cards_before) as u32;
+
+            // Review in order of when cards come due, coming back to any that are due again later in the day. New cards
+            // are seen the day they're added.
+            let mut due: BinaryHeap<Reverse<(DateTime<Utc>, usize)>> = deck
+                .cards
+                .iter()
+                .enumerate()
+                .filter(|(_, (_, card_data))| !card_data.suspended)
+                .filter_map(|(index, (_, card_data))| {
+                    let due_at = if card_data.fsrs_card.state == rs_fsrs::State::New {
+                        day_start
+                    } else {
+                        card_data.fsrs_card.due
+                    };
+                    (due_at < day_end).then_some(Reverse((due_at, index)))
+                })
+                .collect();
+            let (mut reviews, mut lapses) = (0, 0);
+            while let Some(Reverse((due_at, card_index))) = due.pop() {
+                let now = due_at.max(day_start);
+                let (card, card_data) = deck.cards.get_index(card_index).unwrap();
+                let card = card.clone();
+                let rating = if card_data.fsrs_card.state == rs_fsrs::State::New {
+                    Rating::Good
+                } else {
+                    reviews += 1;
+                    let recall = (card_data.fsrs_card.get_retrievability(now)
+                        + noise * (2.0 * rng.next_f64() - 1.0))
+                        .clamp(0.0, 1.0);
+                    if rng.next_f64() < recall {
+                        Rating::Good
+                    } else {
+                        lapses += 1;
+                        Rating::Again
+                    }
+                };
+                deck.log_review(card, rating, now, ReviewSource::Flashcard, None);
+                let next_due = deck.cards.get_index(card_index).unwrap().1.fsrs_card.due;
+                if next_due < day_end {
+                    due.push(Reverse((next_due, card_index)));
+                }
+            }
+
+            let (expected_words_known, expected_coverage) = deck.expected_knowledge(day_end);
+            days.push(SimulatedDay {
+                day,
+                new_cards,
+                reviews,
+                lapses,
+                expected_words_known,
+                expected_coverage,
+            });
+        }
+        days
+    }
+
     /// Simulate `days` of reviews, calling `on_challenge` for each generated challenge.
     /// The simulation answers every challenge perfectly, adds 10 new cards at the end of each day,
     /// and advances the time by one day.
@@ -31,6 +190,29 @@ impl Deck {
                                     target_language, ..
                                 },
                             ) => deck.translate_sentence_perfect(target_language),
+                            Challenge::TranslateToTargetLanguage(challenge) => {
+                                deck.produce_sentence_perfect(challenge.target_language)
+                            }
+                            Challenge::ClozeComprehensibleSentence(challenge) => {
+                                let grades = challenge
+                                    .parts
+                                    .iter()
+                                    .filter(|part| matches!(part, ClozePart::Blank { .. }))
+                                    .map(|_| BlankGrade::Perfect {})
+                                    .collect();
+                                deck.cloze_sentence(challenge, grades)
+                            }
+                            Challenge::MultipleChoice(challenge) => {
+                                let correct_option = challenge.correct_option;
+                                deck.answer_multiple_choice(challenge, correct_option)
+                            }
+                            Challenge::SpeakComprehensibleSentence(challenge) => {
+                                let parts = grade_transcript(
+                                    &challenge.target_language_literals,
+                                    &challenge.target_language,
+                                );
+                                deck.speak_sentence(challenge, parts)
+                            }
                             Challenge::TranscribeComprehensibleSentence(
                                 TranscribeComprehensibleSentence { parts, .. },
                             ) => {
@@ -96,3 +278,55 @@ impl Deck {
         }
     }
 }
+
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+impl Deck {
+    /// Forecast the daily workload and progress for a pace of new cards, starting from now, so the user can pick one
+    /// they can keep up.
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+    pub fn simulate_workload(&self, options: SimulationOptions) -> Vec<SimulatedDay> {
+        self.simulate_workload_from(&options, Utc::now())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SimulationOptions;
+    use crate::tests::test_deck;
+
+    #[test]
+    fn workload_simulation() {
+        let start = chrono::DateTime::parse_from_rfc3339("2025-01-01T12:00:00Z")
+            .unwrap()
+            .to_utc();
+        let deck = test_deck();
+        let options = SimulationOptions {
+            new_cards_per_day: 2,
+            days: 30,
+            seed: 7,
+            recall_noise: 0.1,
+        };
+        let days = deck.simulate_workload_from(&options, start);
+        assert_eq!(days.len(), 30);
+        assert_eq!(days[0].new_cards, 2);
+        assert!(days.iter().map(|day| day.reviews).sum::<u32>() > 0);
+        assert!(days[29].expected_words_known > days[0].expected_words_known);
+        assert!(days[29].expected_coverage > 0.5 && days[29].expected_coverage <= 1.0);
+
+        // Seeded, so the same options give the same forecast
+        assert_eq!(deck.simulate_workload_from(&options, start), days);
+
+        // A faster pace means more reviews (while the small test pack lasts)
+        let faster = deck.simulate_workload_from(
+            &SimulationOptions {
+                new_cards_per_day: 4,
+                ..options.clone()
+            },
+            start,
+        );
+        let total_reviews = |days: &[super::SimulatedDay]| -> u32 {
+            days.iter().take(2).map(|day| day.reviews).sum()
+        };
+        assert!(total_reviews(&faster) > total_reviews(&days));
+    }
+}
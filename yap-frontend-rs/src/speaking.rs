@@ -0,0 +1,438 @@
+//! Speaking practice: the user reads a sentence aloud, the recording goes off to the server to be transcribed, and
+//! the transcript is lined up word by word against the sentence to see which words came out right.
+//!
+//! Saying a word right shows the user knows how it sounds, so the reviews go to the listening cards.
+//! Speech recognition makes mistakes too, so a mispronounced word only counts as `Hard`.
+
+use base64::Engine;
+use chrono::{DateTime, Utc};
+use language_utils::{
+    AsrRequest, AsrResponse, Heteronym, Language, Literal, TtsProvider, TtsRequest,
+};
+use lasso::Spur;
+use rs_fsrs::Rating;
+use std::collections::BTreeMap;
+use wasm_bindgen::prelude::*;
+
+use crate::card_detail::ReviewSource;
+use crate::utils::hit_ai_server;
+use crate::{
+    AudioRequest, CardIndicator, Challenge, Deck, DeckEvent, LanguageEvent, LanguageEventContent,
+    ReviewInfo,
+};
+
+#[derive(tsify::Tsify, serde::Serialize, serde::Deserialize, Debug, Clone)]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+pub struct SpeakComprehensibleSentence<S> {
+    /// So the user can hear it said properly, once they've had a go.
+    pub(crate) audio: AudioRequest,
+    pub(crate) target_language: S,
+    native_translation: S,
+    pub(crate) target_language_literals: Vec<Literal<S>>,
+}
+
+impl SpeakComprehensibleSentence<Spur> {
+    pub(crate) fn resolve(
+        &self,
+        rodeo: &lasso::RodeoReader,
+    ) -> SpeakComprehensibleSentence<String> {
+        SpeakComprehensibleSentence {
+            audio: self.audio.clone(),
+            target_language: rodeo.resolve(&self.target_language).to_string(),
+            native_translation: rodeo.resolve(&self.native_translation).to_string(),
+            target_language_literals: self
+                .target_language_literals
+                .iter()
+                .map(|literal| literal.resolve(rodeo))
+                .collect(),
+        }
+    }
+}
+
+#[derive(tsify::Tsify, serde::Serialize, serde::Deserialize, Debug, Clone, PartialEq)]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+pub enum SpokenPart {
+    /// Punctuation and the like, which isn't said out loud.
+    Unspoken { part: Literal<String> },
+    Spoken {
+        part: Literal<String>,
+        /// What the recognizer heard in its place, if anything.
+        heard: Option<String>,
+        correct: bool,
+    },
+}
+
+/// The words of `text` as they'd be said, ignoring case and punctuation. Elisions like "l'homme" are split in two,
+/// since recognizers don't agree on whether they're one word or two.
+pub(crate) fn spoken_words(text: &str) -> Vec<String> {
+    text.split(|c: char| c.is_whitespace() || c == '\'' || c == '’')
+        .map(|word| {
+            word.trim_matches(|c: char| !c.is_alphanumeric())
+                .to_lowercase()
+        })
+        .filter(|word| !word.is_empty())
+        .collect()
+}
+
+/// Line up `heard` against `expected` with the fewest insertions, deletions and substitutions, returning the index
+/// in `heard` that each expected word lines up with.
+fn align(expected: &[String], heard: &[String]) -> Vec<Option<usize>> {
+    // cost[i][j] is the cost of aligning expected[..i] with heard[..j]
+    let mut cost = vec![vec![0usize; heard.len() + 1]; expected.len() + 1];
+    for (i, row) in cost.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for j in 0..=heard.len() {
+        cost[0][j] = j;
+    }
+    for i in 1..=expected.len() {
+        for j in 1..=heard.len() {
+            let substitution = usize::from(expected[i - 1] != heard[j - 1]);
+            cost[i][j] = (cost[i - 1][j - 1] + substitution)
+                .min(cost[i - 1][j] + 1)
+                .min(cost[i][j - 1] + 1);
+        }
+    }
+
+    // Walk back through the cheapest alignment. Where there's a tie, words that were heard correctly are lined up
+    // first, then anything extra is set aside, so a filler word ("euh") doesn't get lined up in place of another.
+    let mut alignment = vec![None; expected.len()];
+    let (mut i, mut j) = (expected.len(), heard.len());
+    while i > 0 {
+        if j > 0 && expected[i - 1] == heard[j - 1] && cost[i][j] == cost[i - 1][j - 1] {
+            alignment[i - 1] = Some(j - 1);
+            i -= 1;
+            j -= 1;
+        } else if j > 0 && cost[i][j] == cost[i][j - 1] + 1 {
+            // Something extra was heard
+            j -= 1;
+        } else if j > 0 && cost[i][j] == cost[i - 1][j - 1] + 1 {
+            // Something else was heard instead
+            alignment[i - 1] = Some(j - 1);
+            i -= 1;
+            j -= 1;
+        } else {
+            // Skipped
+            i -= 1;
+        }
+    }
+    alignment
+}
+
+/// Grade each literal of a sentence against what the recognizer heard.
+pub(crate) fn grade_transcript(literals: &[Literal<String>], transcript: &str) -> Vec<SpokenPart> {
+    let heard = spoken_words(transcript);
+    // A literal can be more than one spoken word ("aujourd'hui"), so keep track of which literal each came from
+    let (expected, literal_indices): (Vec<String>, Vec<usize>) = literals
+        .iter()
+        .enumerate()
+        .flat_map(|(index, literal)| {
+            spoken_words(&literal.text)
+                .into_iter()
+                .map(move |word| (word, index))
+        })
+        .unzip();
+    let alignment = align(&expected, &heard);
+
+    literals
+        .iter()
+        .enumerate()
+        .map(|(index, literal)| {
+            let words: Vec<usize> = (0..expected.len())
+                .filter(|word| literal_indices[*word] == index)
+                .collect();
+            if words.is_empty() {
+                return SpokenPart::Unspoken {
+                    part: literal.clone(),
+                };
+            }
+            let heard_words: Vec<&str> = words
+                .iter()
+                .filter_map(|word| alignment[*word].map(|heard_index| heard[heard_index].as_str()))
+                .collect();
+            let correct = words.iter().all(|word| {
+                alignment[*word].is_some_and(|heard_index| heard[heard_index] == expected[*word])
+            });
+            SpokenPart::Spoken {
+                part: literal.clone(),
+                heard: (!heard_words.is_empty()).then(|| heard_words.join(" ")),
+                correct,
+            }
+        })
+        .collect()
+}
+
+impl Deck {
+    /// A sentence to read aloud that contains a word pronounced `pronunciation`.
+    pub(crate) fn get_speaking_challenge(
+        &self,
+        pronunciation: Spur,
+        now: DateTime<Utc>,
+    ) -> Option<SpeakComprehensibleSentence<Spur>> {
+        let language_pack = &self.language_pack;
+        let sentence = language_pack
+            .pronunciation_to_words
+            .get(&pronunciation)?
+            .iter()
+            .filter_map(|word| language_pack.words_to_heteronyms.get(word))
+            .flatten()
+            .find_map(|heteronym| {
+                self.get_comprehensible_sentence_containing(
+                    &language_utils::Lexeme::Heteronym(*heteronym),
+                    &self.sentences_reviewed,
+                    language_pack,
+                    now,
+                )
+            })?;
+        Some(SpeakComprehensibleSentence {
+            audio: AudioRequest {
+                request: TtsRequest {
+                    text: language_pack
+                        .rodeo
+                        .resolve(&sentence.target_language)
+                        .to_string(),
+                    language: self.target_language,
+                },
+                provider: TtsProvider::ElevenLabs,
+            },
+            target_language: sentence.target_language,
+            native_translation: *sentence.native_languages.first()?,
+            target_language_literals: sentence.target_language_literals,
+        })
+    }
+
+    pub(crate) fn apply_speaking_challenge(
+        &mut self,
+        challenge_sentence: &str,
+        pronounced: &[Heteronym<String>],
+        mispronounced: &[Heteronym<String>],
+        timestamp: DateTime<Utc>,
+    ) {
+        let rodeo = &self.language_pack.rodeo;
+        let challenge_sentence = rodeo.get(challenge_sentence);
+        // Homophones share a listening card, which is only as good as the worst attempt at it
+        let mut ratings: BTreeMap<Spur, Rating> = BTreeMap::new();
+        for (heteronyms, rating) in [(pronounced, Rating::Good), (mispronounced, Rating::Hard)] {
+            for heteronym in heteronyms {
+                if let Some(pronunciation) = rodeo
+                    .get(&heteronym.word)
+                    .and_then(|word| self.language_pack.word_to_pronunciation.get(&word))
+                {
+                    ratings.insert(*pronunciation, rating);
+                }
+            }
+        }
+        if let Some(challenge_sentence) = challenge_sentence {
+            *self
+                .sentences_reviewed
+                .entry(challenge_sentence)
+                .or_insert(0) += 1;
+        }
+        for (pronunciation, rating) in ratings {
+            self.log_review(
+                CardIndicator::ListeningHomophonous { pronunciation },
+                rating,
+                timestamp,
+                ReviewSource::SpeakingChallenge,
+                challenge_sentence,
+            );
+        }
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+impl ReviewInfo {
+    /// Like `get_challenge_for_card`, but a sentence to read aloud where possible. Only listening cards that have
+    /// been reviewed before get one.
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+    pub fn get_speaking_challenge_for_card(
+        &self,
+        deck: &Deck,
+        card_index: usize,
+    ) -> Option<Challenge<String>> {
+        let now = Utc::now();
+        let (indicator, card_data) = deck.cards.get_index(card_index)?;
+        if let CardIndicator::ListeningHomophonous { pronunciation } = indicator
+            && card_data.fsrs_card.state != rs_fsrs::State::New
+            && !card_data.suspended
+            && !card_data.is_buried(now)
+            && let Some(challenge) = deck.get_speaking_challenge(*pronunciation, now)
+        {
+            return Some(Challenge::SpeakComprehensibleSentence(
+                challenge.resolve(&deck.language_pack.rodeo),
+            ));
+        }
+        self.get_challenge_for_card(deck, card_index)
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+impl Deck {
+    /// Grade what the user said (as transcribed by `transcribe_speech`) against the sentence.
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+    pub fn grade_speech(
+        &self,
+        challenge: SpeakComprehensibleSentence<String>,
+        transcript: String,
+    ) -> Vec<SpokenPart> {
+        grade_transcript(&challenge.target_language_literals, &transcript)
+    }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+    pub fn speak_sentence(
+        &self,
+        challenge: SpeakComprehensibleSentence<String>,
+        parts: Vec<SpokenPart>,
+    ) -> Option<DeckEvent> {
+        let mut pronounced = Vec::new();
+        let mut mispronounced = Vec::new();
+        for part in parts {
+            if let SpokenPart::Spoken {
+                part:
+                    Literal {
+                        heteronym: Some(heteronym),
+                        ..
+                    },
+                correct,
+                ..
+            } = part
+            {
+                if correct {
+                    pronounced.push(heteronym);
+                } else {
+                    mispronounced.push(heteronym);
+                }
+            }
+        }
+        Some(DeckEvent::Language(LanguageEvent {
+            language: self.target_language,
+            content: LanguageEventContent::SpeakingChallenge {
+                challenge_sentence: challenge.target_language,
+                pronounced,
+                mispronounced,
+            },
+        }))
+    }
+}
+
+/// Send a recording of the user speaking to the server to be transcribed.
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+pub async fn transcribe_speech(
+    audio: Vec<u8>,
+    access_token: Option<String>,
+    language: Language,
+) -> Result<String, JsValue> {
+    let request = AsrRequest {
+        audio: base64::engine::general_purpose::STANDARD.encode(&audio),
+        language,
+    };
+
+    let response = hit_ai_server("/asr", request, access_token.as_ref())
+        .await
+        .map_err(|e| JsValue::from_str(&format!("Request error: {e:?}")))?;
+
+    if !response.ok() {
+        return Err(JsValue::from_str(&format!(
+            "HTTP error: {}",
+            response.status()
+        )));
+    }
+
+    let response: AsrResponse = response
+        .json()
+        .await
+        .map_err(|e| JsValue::from_str(&format!("Response parsing error: {e:?}")))?;
+
+    Ok(response.transcript)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SpokenPart;
+    use crate::card_detail::ReviewSource;
+    use crate::tests::{apply, lexeme_card, test_deck};
+    use crate::{CardIndicator, LanguageEventContent};
+
+    #[test]
+    fn spoken_words() {
+        assert_eq!(
+            super::spoken_words("L'homme mange, aujourd’hui!"),
+            vec!["l", "homme", "mange", "aujourd", "hui"]
+        );
+    }
+
+    #[test]
+    fn speaking_challenges_grade_listening_cards() {
+        let start = chrono::DateTime::parse_from_rfc3339("2025-01-01T12:00:00Z")
+            .unwrap()
+            .to_utc();
+        let listening = |pronunciation: &str| CardIndicator::ListeningHomophonous {
+            pronunciation: pronunciation.to_string(),
+        };
+        let mut cards = ["le", "chat", "mange"].map(lexeme_card).to_vec();
+        cards.extend(["lə", "ʃa", "mɑ̃ʒ"].map(listening));
+        let mut events = vec![(
+            start,
+            LanguageEventContent::AddCards {
+                cards: cards.clone(),
+            },
+        )];
+        for card in cards {
+            events.push((
+                start,
+                LanguageEventContent::ReviewCard {
+                    reviewed: card,
+                    rating: "easy".to_string(),
+                },
+            ));
+        }
+        let deck = apply(test_deck(), &events);
+        let rodeo = &deck.language_pack.rodeo;
+
+        let challenge = deck
+            .get_speaking_challenge(rodeo.get("ʃa").unwrap(), chrono::Utc::now())
+            .unwrap()
+            .resolve(rodeo);
+        assert_eq!(challenge.target_language, "Le chat mange.");
+
+        // "chat" was heard as "chien", and a stray "euh" doesn't throw off the rest
+        let parts = deck.grade_speech(challenge.clone(), "le chien euh mange".to_string());
+        let graded: Vec<(&str, Option<&str>, Option<bool>)> = parts
+            .iter()
+            .map(|part| match part {
+                SpokenPart::Unspoken { part } => (part.text.as_str(), None, None),
+                SpokenPart::Spoken {
+                    part,
+                    heard,
+                    correct,
+                } => (part.text.as_str(), heard.as_deref(), Some(*correct)),
+            })
+            .collect();
+        assert_eq!(
+            graded,
+            vec![
+                ("Le", Some("le"), Some(true)),
+                ("chat", Some("chien"), Some(false)),
+                ("mange", Some("mange"), Some(true)),
+                (".", None, None),
+            ]
+        );
+
+        let crate::DeckEvent::Language(event) = deck.speak_sentence(challenge, parts).unwrap();
+        let deck = apply(deck, &[(start + chrono::Duration::days(5), event.content)]);
+        let review = |card| {
+            deck.get_card_detail(card)
+                .unwrap()
+                .reviews
+                .last()
+                .cloned()
+                .unwrap()
+        };
+        let chat = review(listening("ʃa"));
+        assert_eq!(chat.source, ReviewSource::SpeakingChallenge);
+        assert_eq!(chat.rating, "hard");
+        assert_eq!(review(listening("mɑ̃ʒ")).rating, "good");
+        // The recognition cards aren't affected
+        assert_eq!(review(lexeme_card("chat")).source, ReviewSource::Flashcard);
+    }
+}
@@ -0,0 +1,311 @@
+//! Learning statistics. These are all computed from the deck, which is itself built from the event stream, so every
+//! device shows the same numbers.
+
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, NaiveDate, Utc};
+use rs_fsrs::{Rating, State};
+use wasm_bindgen::prelude::*;
+
+use crate::card_detail::ReviewSource;
+use crate::{CardIndicator, Deck};
+
+/// A card counts as mature once its stability reaches this many days.
+const MATURE_STABILITY: f32 = 21.0;
+
+/// Card age buckets for retention stats, as (label, minimum age in days).
+const AGE_BUCKETS: [(&str, i64); 4] = [
+    ("under a week", 0),
+    ("1-4 weeks", 7),
+    ("1-3 months", 30),
+    ("over 3 months", 90),
+];
+
+#[derive(Clone, Debug, PartialEq, tsify::Tsify, serde::Serialize, serde::Deserialize)]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+pub struct DailyCount {
+    pub day: NaiveDate,
+    pub count: u32,
+}
+
+/// How often the user remembered cards when they were tested on a day after they last saw them.
+#[derive(Clone, Debug, PartialEq, tsify::Tsify, serde::Serialize, serde::Deserialize)]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+pub struct RetentionStats {
+    pub label: String,
+    pub reviews: u32,
+    pub recalled: u32,
+    /// `recalled / reviews`, or `None` if there haven't been any reviews.
+    pub retention: Option<f64>,
+}
+
+impl RetentionStats {
+    fn new(label: &str) -> Self {
+        Self {
+            label: label.to_string(),
+            reviews: 0,
+            recalled: 0,
+            retention: None,
+        }
+    }
+
+    fn record(&mut self, rating: Rating) {
+        self.reviews += 1;
+        if rating != Rating::Again {
+            self.recalled += 1;
+        }
+        self.retention = Some(self.recalled as f64 / self.reviews as f64);
+    }
+}
+
+/// How many cards were in each FSRS state at the end of a day.
+#[derive(Clone, Debug, Default, PartialEq, tsify::Tsify, serde::Serialize, serde::Deserialize)]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+pub struct DailyStateCounts {
+    pub day: NaiveDate,
+    pub new: u32,
+    pub learning: u32,
+    pub review: u32,
+    pub relearning: u32,
+}
+
+#[derive(Clone, Debug, tsify::Tsify, serde::Serialize, serde::Deserialize)]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+pub struct LearningStats {
+    /// Only days with at least one review are included.
+    pub reviews_per_day: Vec<DailyCount>,
+    pub retention_by_card_type: Vec<RetentionStats>,
+    pub retention_by_card_age: Vec<RetentionStats>,
+    /// Every day from the first card being added until today.
+    pub states_over_time: Vec<DailyStateCounts>,
+    /// Among cards that have matured (reached a stability of 21 days), the average number of days it took.
+    pub average_days_to_mature: Option<f64>,
+    /// How many cards are due on each of the next few days, starting today. Overdue cards are counted as due today.
+    pub due_forecast: Vec<DailyCount>,
+}
+
+fn state_index(state: State) -> usize {
+    match state {
+        State::New => 0,
+        State::Learning => 1,
+        State::Review => 2,
+        State::Relearning => 3,
+    }
+}
+
+impl Deck {
+    pub(crate) fn learning_stats(&self, now: DateTime<Utc>, forecast_days: u32) -> LearningStats {
+        let days = &self.settings.day;
+        let today = days.day_of(now);
+
+        let mut reviews_per_day: BTreeMap<NaiveDate, u32> = BTreeMap::new();
+        let mut text_retention = RetentionStats::new("text");
+        let mut listening_retention = RetentionStats::new("listening");
+        let mut production_retention = RetentionStats::new("production");
+        let mut age_retention = AGE_BUCKETS.map(|(label, _)| RetentionStats::new(label));
+        let mut days_to_mature = Vec::new();
+
+        for (indicator, card_data) in self.cards.iter() {
+            let Some(first) = card_data.reviews.first() else {
+                continue;
+            };
+            for review in &card_data.reviews {
+                if review.source != ReviewSource::MarkedKnown {
+                    *reviews_per_day
+                        .entry(days.day_of(review.timestamp))
+                        .or_insert(0) += 1;
+                }
+            }
+
+            for pair in card_data.reviews.windows(2) {
+                let [previous, review] = pair else {
+                    unreachable!()
+                };
+                // Same-day reviews test short-term memory, which isn't what we're interested in
+                if days.day_of(review.timestamp) <= days.day_of(previous.timestamp) {
+                    continue;
+                }
+                match indicator {
+                    CardIndicator::TargetLanguage { .. } => text_retention.record(review.rating),
+                    CardIndicator::ListeningHomophonous { .. } => {
+                        listening_retention.record(review.rating)
+                    }
+                    CardIndicator::NativeToTarget { .. } => {
+                        production_retention.record(review.rating)
+                    }
+                }
+                let age = (review.timestamp - first.timestamp).num_days();
+                let bucket = AGE_BUCKETS
+                    .iter()
+                    .rposition(|(_, minimum_age)| age >= *minimum_age)
+                    .unwrap_or(0);
+                age_retention[bucket].record(review.rating);
+            }
+
+            // Cards marked as known start out mature, so they don't tell us anything here
+            if first.source != ReviewSource::MarkedKnown
+                && let Some(matured) = card_data
+                    .reviews
+                    .iter()
+                    .find(|review| review.stability_after >= MATURE_STABILITY)
+            {
+                let days = (matured.timestamp - first.timestamp).num_seconds() as f64 / 86400.0;
+                days_to_mature.push(days);
+            }
+        }
+
+        LearningStats {
+            reviews_per_day: reviews_per_day
+                .into_iter()
+                .map(|(day, count)| DailyCount { day, count })
+                .collect(),
+            retention_by_card_type: vec![text_retention, listening_retention, production_retention],
+            retention_by_card_age: age_retention.to_vec(),
+            states_over_time: self.states_over_time(today),
+            due_forecast: self.due_forecast(today, forecast_days),
+            average_days_to_mature: (!days_to_mature.is_empty())
+                .then(|| days_to_mature.iter().sum::<f64>() / days_to_mature.len() as f64),
+        }
+    }
+
+    fn states_over_time(&self, today: NaiveDate) -> Vec<DailyStateCounts> {
+        let days = &self.settings.day;
+        let Some(first_day) = self
+            .cards
+            .values()
+            .map(|card_data| days.day_of(card_data.added_at))
+            .min()
+        else {
+            return Vec::new();
+        };
+        let num_days = ((today - first_day).num_days().max(0) + 1) as usize;
+
+        // For each state, the change in the number of cards in it from one day to the next
+        let mut changes = vec![[0i64; 4]; num_days + 1];
+        for card_data in self.cards.values() {
+            let transitions = std::iter::once((card_data.added_at, State::New)).chain(
+                card_data
+                    .reviews
+                    .iter()
+                    .map(|review| (review.timestamp, review.state_after)),
+            );
+            let mut current: Option<(usize, State)> = None;
+            for (timestamp, state) in transitions {
+                let day =
+                    ((days.day_of(timestamp) - first_day).num_days().max(0) as usize).min(num_days);
+                if let Some((since, previous)) = current {
+                    changes[since][state_index(previous)] += 1;
+                    changes[day][state_index(previous)] -= 1;
+                }
+                current = Some((day, state));
+            }
+            if let Some((since, state)) = current {
+                changes[since][state_index(state)] += 1;
+                changes[num_days][state_index(state)] -= 1;
+            }
+        }
+
+        let mut counts = [0i64; 4];
+        (0..num_days)
+            .map(|offset| {
+                for (count, change) in counts.iter_mut().zip(changes[offset]) {
+                    *count += change;
+                }
+                DailyStateCounts {
+                    day: first_day + chrono::Days::new(offset as u64),
+                    new: counts[0] as u32,
+                    learning: counts[1] as u32,
+                    review: counts[2] as u32,
+                    relearning: counts[3] as u32,
+                }
+            })
+            .collect()
+    }
+
+    fn due_forecast(&self, today: NaiveDate, num_days: u32) -> Vec<DailyCount> {
+        let mut forecast: Vec<DailyCount> = (0..num_days)
+            .map(|offset| DailyCount {
+                day: today + chrono::Days::new(offset as u64),
+                count: 0,
+            })
+            .collect();
+        for card_data in self.cards.values() {
+            if card_data.suspended {
+                continue;
+            }
+            let due = card_data
+                .buried_until
+                .map_or(card_data.fsrs_card.due, |buried_until| {
+                    buried_until.max(card_data.fsrs_card.due)
+                });
+            let offset = (self.settings.day.day_of(due) - today).num_days().max(0) as usize;
+            if let Some(day) = forecast.get_mut(offset) {
+                day.count += 1;
+            }
+        }
+        forecast
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+impl Deck {
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+    pub fn get_learning_stats(&self, forecast_days: u32) -> LearningStats {
+        self.learning_stats(Utc::now(), forecast_days)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Duration;
+
+    use crate::LanguageEventContent;
+    use crate::tests::{apply, lexeme_card, test_deck};
+
+    #[test]
+    fn learning_stats() {
+        let start = chrono::DateTime::parse_from_rfc3339("2025-01-01T12:00:00Z")
+            .unwrap()
+            .to_utc();
+        let review = |word: &str, rating: &str| LanguageEventContent::ReviewCard {
+            reviewed: lexeme_card(word),
+            rating: rating.to_string(),
+        };
+        let deck = apply(
+            test_deck(),
+            &[
+                (
+                    start,
+                    LanguageEventContent::AddCards {
+                        cards: vec![lexeme_card("le"), lexeme_card("chat")],
+                    },
+                ),
+                (start, review("le", "easy")),
+                (start, review("chat", "easy")),
+                (start + Duration::days(40), review("le", "good")),
+                (start + Duration::days(40), review("chat", "again")),
+            ],
+        );
+        let now = start + Duration::days(41);
+        let stats = deck.learning_stats(now, 7);
+
+        assert_eq!(stats.reviews_per_day.len(), 2);
+        assert_eq!(stats.reviews_per_day[0].count, 2);
+        let text = &stats.retention_by_card_type[0];
+        assert_eq!((text.reviews, text.recalled), (2, 1));
+        assert_eq!(text.retention, Some(0.5));
+        assert_eq!(stats.retention_by_card_age[2].reviews, 2);
+
+        assert_eq!(stats.states_over_time.len(), 42);
+        let first_day = &stats.states_over_time[0];
+        assert_eq!(first_day.day, start.date_naive());
+        assert_eq!(first_day.new + first_day.learning, 0);
+        assert_eq!(first_day.review + first_day.relearning, 2);
+        let last_day = stats.states_over_time.last().unwrap();
+        assert_eq!((last_day.review, last_day.relearning), (1, 1));
+
+        assert!(stats.average_days_to_mature.is_some());
+        assert_eq!(stats.due_forecast.len(), 7);
+        assert_eq!(stats.due_forecast[0].count, 1);
+    }
+}
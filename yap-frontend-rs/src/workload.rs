@@ -0,0 +1,257 @@
+//! Keeping the daily workload manageable: caps on how many new cards and reviews are shown per day, and load balancing
+//! that spreads reviews out so that cards added together don't all come due on the same day.
+//!
+//! Everything here is derived from the event history, so replaying it gives the same schedule on every device.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use chrono::NaiveDate;
+use lasso::Spur;
+
+use crate::CardIndicator;
+use crate::deck_settings::{DaySettings, WorkloadSettings};
+
+/// How far (as a fraction of the interval) load balancing may move a review.
+const BALANCING_SPREAD: f64 = 0.05;
+/// Reviews scheduled this soon aren't moved, since a day either way matters a lot for them.
+const MIN_BALANCED_INTERVAL: i64 = 3;
+
+/// How many cards in the review state are due on each day.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct DueForecast {
+    days: BTreeMap<NaiveDate, u32>,
+}
+
+impl DueForecast {
+    pub(crate) fn add(&mut self, card: &rs_fsrs::Card, days: &DaySettings) {
+        if card.state == rs_fsrs::State::Review {
+            *self.days.entry(days.day_of(card.due)).or_insert(0) += 1;
+        }
+    }
+
+    pub(crate) fn remove(&mut self, card: &rs_fsrs::Card, days: &DaySettings) {
+        if card.state != rs_fsrs::State::Review {
+            return;
+        }
+        let day = days.day_of(card.due);
+        if let Some(count) = self.days.get_mut(&day) {
+            *count -= 1;
+            if *count == 0 {
+                self.days.remove(&day);
+            }
+        }
+    }
+
+    pub(crate) fn due_on(&self, day: NaiveDate) -> u32 {
+        self.days.get(&day).copied().unwrap_or(0)
+    }
+
+    /// Move `card`'s due date to whichever nearby day has the fewest reviews already due. Ties go to the day closest
+    /// to the original due date, then to the earlier day.
+    pub(crate) fn balance(
+        &self,
+        card: &mut rs_fsrs::Card,
+        maximum_interval: i32,
+        days: &DaySettings,
+    ) {
+        let interval = card.scheduled_days;
+        if card.state != rs_fsrs::State::Review || interval < MIN_BALANCED_INTERVAL {
+            return;
+        }
+        let spread = ((interval as f64 * BALANCING_SPREAD).round() as i64).max(1);
+        let Some(best) = (interval - spread..=interval + spread)
+            .filter(|offset| *offset >= 1 && *offset <= maximum_interval as i64)
+            .min_by_key(|offset| {
+                let due = card.last_review + chrono::Duration::days(*offset);
+                (
+                    self.due_on(days.day_of(due)),
+                    (offset - interval).abs(),
+                    *offset,
+                )
+            })
+        else {
+            return;
+        };
+        card.scheduled_days = best;
+        card.due = card.last_review + chrono::Duration::days(best);
+    }
+}
+
+/// Which cards were reviewed on the most recent day with any reviews, for enforcing the daily limits.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct DailyCounts {
+    day: Option<NaiveDate>,
+    new_cards: BTreeSet<CardIndicator<Spur>>,
+    reviewed_cards: BTreeSet<CardIndicator<Spur>>,
+}
+
+impl DailyCounts {
+    /// Record a review of `card`, which was in `state` before it was reviewed. Learning and relearning steps don't
+    /// count towards either limit.
+    pub(crate) fn record(
+        &mut self,
+        card: &CardIndicator<Spur>,
+        state: rs_fsrs::State,
+        day: NaiveDate,
+    ) {
+        if self.day.is_some_and(|current| current > day) {
+            // An old event from another device, which can't affect today's limits anymore
+            return;
+        }
+        if self.day != Some(day) {
+            *self = DailyCounts {
+                day: Some(day),
+                ..Default::default()
+            };
+        }
+        match state {
+            rs_fsrs::State::New => {
+                self.new_cards.insert(card.clone());
+            }
+            rs_fsrs::State::Review if !self.new_cards.contains(card) => {
+                self.reviewed_cards.insert(card.clone());
+            }
+            _ => {}
+        }
+    }
+
+    /// How many more (new cards, reviews) can be shown on `today`.
+    pub(crate) fn remaining(&self, settings: &WorkloadSettings, today: NaiveDate) -> (u32, u32) {
+        if self.day != Some(today) {
+            return (settings.new_cards_per_day, settings.reviews_per_day);
+        }
+        (
+            settings
+                .new_cards_per_day
+                .saturating_sub(self.new_cards.len() as u32),
+            settings
+                .reviews_per_day
+                .saturating_sub(self.reviewed_cards.len() as u32),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::LanguageEventContent;
+    use crate::deck_settings::{LanguageSettingsEventContent, WorkloadSettings};
+    use crate::tests::{apply, lexeme_card, test_deck_with_settings, test_language_pack};
+    use crate::{Deck, Language};
+
+    #[test]
+    fn daily_limits_and_load_balancing() {
+        let now = chrono::Utc::now();
+        let settings = LanguageSettingsEventContent::SetWorkloadSettings(WorkloadSettings {
+            new_cards_per_day: 2,
+            ..WorkloadSettings::recommended()
+        });
+        let mut deck = Deck::new(
+            test_language_pack(),
+            Language::French,
+            [weapon::data_model::Timestamped {
+                timestamp: now - chrono::Duration::days(1),
+                within_device_events_index: 0,
+                event: settings,
+            }]
+            .into(),
+        );
+        let words = ["le", "chat", "mange", "chien", "mais", "mes"];
+        let mut events = vec![(
+            now,
+            LanguageEventContent::AddCards {
+                cards: words.map(lexeme_card).to_vec(),
+            },
+        )];
+        let review_info = apply(deck.clone(), &events).get_review_info(vec![]);
+        assert_eq!(review_info.due_count(), 2);
+        assert_eq!(review_info.due_over_limit_count(), 4);
+
+        for word in words {
+            events.push((
+                now,
+                LanguageEventContent::ReviewCard {
+                    reviewed: lexeme_card(word),
+                    rating: "easy".to_string(),
+                },
+            ));
+        }
+        deck = apply(deck, &events);
+        let review_info = deck.get_review_info(vec![]);
+        assert_eq!(review_info.due_count(), 0);
+        assert_eq!(review_info.due_over_limit_count(), 0);
+
+        // Every card got the same rating at the same time, so without balancing they'd all be due on the same day
+        let due_days = deck
+            .cards
+            .values()
+            .map(|card_data| card_data.fsrs_card.due.date_naive())
+            .collect::<std::collections::BTreeSet<_>>();
+        assert!(due_days.len() > 1, "{due_days:?}");
+
+        // Without a workload settings event, the same history replays without limits or balancing
+        let deck = apply(
+            Deck::new(test_language_pack(), Language::French, [].into()),
+            &events,
+        );
+        assert!(!deck.daily_limits_enabled());
+        let due_days = deck
+            .cards
+            .values()
+            .map(|card_data| card_data.fsrs_card.due.date_naive())
+            .collect::<std::collections::BTreeSet<_>>();
+        assert_eq!(due_days.len(), 1, "{due_days:?}");
+    }
+
+    #[test]
+    fn learning_steps_are_not_held_back_by_the_review_limit() {
+        let now = chrono::Utc::now();
+        let long_ago = now - chrono::Duration::days(30);
+        let deck = test_deck_with_settings(&[(
+            long_ago,
+            LanguageSettingsEventContent::SetWorkloadSettings(WorkloadSettings {
+                reviews_per_day: 0,
+                ..WorkloadSettings::recommended()
+            }),
+        )]);
+        let review = |word: &str| LanguageEventContent::ReviewCard {
+            reviewed: lexeme_card(word),
+            rating: "good".to_string(),
+        };
+        let introduced = now - chrono::Duration::minutes(20);
+        let deck = apply(
+            deck,
+            &[
+                (
+                    long_ago,
+                    LanguageEventContent::AddCards {
+                        cards: vec![lexeme_card("chat"), lexeme_card("le")],
+                    },
+                ),
+                (long_ago, review("chat")),
+                (long_ago, review("le")),
+                (long_ago + chrono::Duration::minutes(10), review("chat")),
+                (long_ago + chrono::Duration::minutes(10), review("le")),
+                (
+                    introduced,
+                    LanguageEventContent::AddCards {
+                        cards: vec![lexeme_card("mange")],
+                    },
+                ),
+                (introduced, review("mange")),
+            ],
+        );
+
+        // The overdue reviews are over the limit, but the card added today still comes back for its next step
+        let review_info = deck.get_review_info(vec![]);
+        assert_eq!(review_info.due_over_limit_count(), 2);
+        let due: Vec<_> = review_info
+            .due_cards
+            .iter()
+            .map(|index| {
+                let (card, _) = deck.cards.get_index(*index).unwrap();
+                card.resolve(&deck.language_pack.rodeo)
+            })
+            .collect();
+        assert_eq!(due, [lexeme_card("mange")]);
+    }
+}
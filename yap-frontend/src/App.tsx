@@ -1,6 +1,6 @@
 import { useState, useEffect, Profiler, useMemo, useCallback, useSyncExternalStore } from 'react'
 import { BrowserRouter, Routes, Route } from 'react-router-dom'
-import { CardSummary, Deck, type CardType, type Challenge, type ChallengeType, type Language, type Lexeme, type /* comes from TranscriptionChallenge */ PartGraded } from '../../yap-frontend-rs/pkg'
+import { CardSummary, Deck, get_mnemonic, type BlankGrade, type SpokenPart, type MinimalPair, type CardType, type Challenge, type ChallengeType, type Language, type Lexeme, type /* comes from TranscriptionChallenge */ PartGraded } from '../../yap-frontend-rs/pkg'
 import { Button } from "@/components/ui/button.tsx"
 import { Progress } from "@/components/ui/progress.tsx"
 import { ThemeProvider } from "@/components/theme-provider"
@@ -17,6 +17,7 @@ import { ForgotPassword } from '@/pages/forgot-password'
 import { playSoundEffect } from '@/lib/sound-effects'
 import { registerSW } from 'virtual:pwa-register'
 import { NoCardsDue } from '@/components/no-cards-due'
+import { CustomStudy } from '@/components/custom-study'
 
 import type { Dispatch, SetStateAction } from 'react'
 import type { RegisterSWOptions } from 'vite-plugin-pwa/types'
@@ -29,6 +30,12 @@ declare module 'virtual:pwa-register/react' {
 }
 import { useRegisterSW } from 'virtual:pwa-register/react'
 import { TranscriptionChallenge } from './components/challenges/TranscriptionChallenge'
+import { ClozeChallenge } from './components/challenges/ClozeChallenge'
+import { ProductionChallenge, type ProductionGrade } from './components/challenges/ProductionChallenge'
+import { MultipleChoiceChallenge } from './components/challenges/MultipleChoiceChallenge'
+import { SpeakingChallenge } from './components/challenges/SpeakingChallenge'
+import { MinimalPairDrill } from './components/challenges/MinimalPairDrill'
+import { useIsTouchDevice } from './hooks/use-is-touch-device'
 import { LanguageSelector } from './components/LanguageSelector'
 import { WeaponProvider, useAsyncMemo, useWeapon, useWeaponState, useWeaponSupport, type WeaponToken } from './weapon'
 import { Header } from '@/components/header'
@@ -199,6 +206,12 @@ function AppContent({ userInfo, accessToken }: { userInfo: UserInfo | undefined,
 
   const [requestedLanguageChange, setRequestedLanguageChange] = useState(false);
 
+  // Needs a microphone and somewhere the user can talk, so it's off until they turn it on
+  const [speakingPractice, setSpeakingPractice] = useState(() => localStorage.getItem('yap-speaking-practice') === 'true');
+  useEffect(() => {
+    localStorage.setItem('yap-speaking-practice', speakingPractice ? 'true' : 'false');
+  }, [speakingPractice]);
+
   return (
     <Profiler id="App" onRender={profilerOnRender}>
       <div>
@@ -213,6 +226,22 @@ function AppContent({ userInfo, accessToken }: { userInfo: UserInfo | undefined,
                     setRequestedLanguageChange(true)
                   } : undefined}
                   showSignupNag={deck?.type === 'deck' && deck.deck !== null}
+                  dailyLimitsEnabled={deck?.type === 'deck' ? deck.deck?.daily_limits_enabled() : undefined}
+                  onToggleDailyLimits={deck?.type === 'deck' && deck.deck ? () => {
+                    const currentDeck = deck.deck
+                    if (currentDeck) {
+                      weapon.add_deck_settings_event(currentDeck.set_daily_limits(!currentDeck.daily_limits_enabled()))
+                    }
+                  } : undefined}
+                  catchUpEnabled={deck?.type === 'deck' ? deck.deck?.catch_up_enabled() : undefined}
+                  onToggleCatchUp={deck?.type === 'deck' && deck.deck ? () => {
+                    const currentDeck = deck.deck
+                    if (currentDeck) {
+                      weapon.add_deck_settings_event(currentDeck.set_catch_up(!currentDeck.catch_up_enabled()))
+                    }
+                  } : undefined}
+                  speakingPracticeEnabled={speakingPractice}
+                  onToggleSpeakingPractice={deck?.type === 'deck' ? () => setSpeakingPractice(!speakingPractice) : undefined}
                 />
                 {
                   match(deck)
@@ -227,6 +256,8 @@ function AppContent({ userInfo, accessToken }: { userInfo: UserInfo | undefined,
                           accessToken={accessToken}
                           deck={deck}
                           targetLanguage={language}
+                          speakingPractice={speakingPractice}
+                          onCantSpeak={() => setSpeakingPractice(false)}
                         /> :
                         <LanguageSelector
                           skipOnboarding={true}
@@ -280,11 +311,13 @@ interface ReviewProps {
   accessToken: string | undefined
   deck: Deck
   targetLanguage: Language
+  speakingPractice: boolean
+  onCantSpeak: () => void
 }
 
 
 
-function Review({ userInfo, accessToken, deck, targetLanguage }: ReviewProps) {
+function Review({ userInfo, accessToken, deck, targetLanguage, speakingPractice, onCantSpeak }: ReviewProps) {
   const weapon = useWeapon()
 
   const CANT_LISTEN_DURATION_MS = 15 * 60 * 1000;
@@ -305,6 +338,14 @@ function Review({ userInfo, accessToken, deck, targetLanguage }: ReviewProps) {
     }
   }, [deck, userInfo?.id, accessToken])
 
+  // Keep the deck's idea of when the user's days start in sync with their timezone, so streaks follow them when they travel
+  useEffect(() => {
+    const event = deck.update_utc_offset(-new Date().getTimezoneOffset())
+    if (event) {
+      weapon.add_deck_settings_event(event)
+    }
+  }, [deck, weapon])
+
   // Schedule re-render when next card becomes due
   useEffect(() => {
     const next_due_timestamp_ms = nextDueCard?.due_timestamp_ms;
@@ -365,12 +406,19 @@ function Review({ userInfo, accessToken, deck, targetLanguage }: ReviewProps) {
     return deck.get_review_info(bannedChallengeTypes)
   }, [deck, bannedChallengeTypes, cardsBecameDue]);
 
+  // Typing on a phone is a chore, so reviews there are multiple choice where possible
+  const isTouchDevice = useIsTouchDevice();
+
   const { currentChallenge, currentCardId, addCardOptions } = useMemo(() => {
     const currentCardId = reviewInfo.get_next_review_card();
-    const currentChallenge: Challenge<string> | undefined = currentCardId !== undefined ? reviewInfo.get_challenge_for_card(deck, currentCardId) : undefined;
+    const speakingChallenge = currentCardId !== undefined && speakingPractice ? reviewInfo.get_speaking_challenge_for_card(deck, currentCardId) : undefined;
+    const currentChallenge: Challenge<string> | undefined = currentCardId === undefined ? undefined
+      : speakingChallenge && 'SpeakComprehensibleSentence' in speakingChallenge ? speakingChallenge
+      : isTouchDevice ? reviewInfo.get_multiple_choice_challenge_for_card(deck, currentCardId)
+      : reviewInfo.get_challenge_for_card(deck, currentCardId);
     const addCardOptions = deck.add_card_options();
     return { currentChallenge, currentCardId, addCardOptions };
-  }, [deck, reviewInfo])
+  }, [deck, reviewInfo, isTouchDevice, speakingPractice])
 
   useEffect(() => {
     const abortController = new AbortController();
@@ -383,6 +431,14 @@ function Review({ userInfo, accessToken, deck, targetLanguage }: ReviewProps) {
   }, [deck, accessToken, reviewInfo])
 
 
+  // Practice that sits outside the usual reviews, picked from the no cards due screen
+  const [practice, setPractice] = useState<"minimalPairs" | "customStudy" | null>(null)
+  const canDrillMinimalPairs = useMemo(() => deck.get_minimal_pair_drill() !== undefined, [deck])
+
+  const handleMinimalPairAnswer = useCallback((drill: MinimalPair, chosenOption: number) => {
+    weapon.add_deck_event(deck.answer_minimal_pair(drill, chosenOption));
+  }, [deck, weapon])
+
   const addNextCards = useCallback(async (card_type: CardType | undefined, count: number) => {
     const event = deck.add_next_unknown_cards(card_type, count);
     if (event) {
@@ -471,6 +527,65 @@ function Review({ userInfo, accessToken, deck, targetLanguage }: ReviewProps) {
     setShowAnswer(false)
   }, [deck, currentChallenge, weapon])
 
+  const handleClozeComplete = useCallback((grades: BlankGrade[]) => {
+    if (!currentChallenge || !('ClozeComprehensibleSentence' in currentChallenge)) {
+      console.error("handleClozeComplete called with no current challenge or no ClozeComprehensibleSentence in current challenge");
+      return
+    };
+
+    playSoundEffect(grades.some(grade => 'Incorrect' in grade) ? 'fail' : 'success'); // Don't await - play in background
+
+    const event = deck.cloze_sentence(currentChallenge.ClozeComprehensibleSentence, grades);
+    if (event) {
+      weapon.add_deck_event(event);
+    }
+    setShowAnswer(false)
+  }, [deck, currentChallenge, weapon])
+
+  const handleProductionComplete = useCallback((grade: ProductionGrade, submission: string) => {
+    if (!currentChallenge || !('TranslateToTargetLanguage' in currentChallenge)) {
+      console.error("handleProductionComplete called with no current challenge or no TranslateToTargetLanguage in current challenge");
+      return
+    };
+
+    playSoundEffect('forgotten' in grade && grade.forgotten.length > 0 ? 'fail' : 'success'); // Don't await - play in background
+
+    const challengeSentence = currentChallenge.TranslateToTargetLanguage.target_language;
+    const event = 'perfect' in grade
+      ? deck.produce_sentence_perfect(challengeSentence)
+      : deck.produce_sentence_wrong(challengeSentence, submission, grade.remembered, grade.forgotten);
+    if (event) {
+      weapon.add_deck_event(event);
+    }
+    setShowAnswer(false)
+  }, [deck, currentChallenge, weapon])
+
+  const handleMultipleChoiceComplete = useCallback((chosenOption: number) => {
+    if (!currentChallenge || !('MultipleChoice' in currentChallenge)) {
+      console.error("handleMultipleChoiceComplete called with no current challenge or no MultipleChoice in current challenge");
+      return
+    };
+
+    const event = deck.answer_multiple_choice(currentChallenge.MultipleChoice, chosenOption);
+    if (event) {
+      weapon.add_deck_event(event);
+    }
+    setShowAnswer(false)
+  }, [deck, currentChallenge, weapon])
+
+  const handleSpeakingComplete = useCallback((parts: SpokenPart[]) => {
+    if (!currentChallenge || !('SpeakComprehensibleSentence' in currentChallenge)) {
+      console.error("handleSpeakingComplete called with no current challenge or no SpeakComprehensibleSentence in current challenge");
+      return
+    };
+
+    const event = deck.speak_sentence(currentChallenge.SpeakComprehensibleSentence, parts);
+    if (event) {
+      weapon.add_deck_event(event);
+    }
+    setShowAnswer(false)
+  }, [deck, currentChallenge, weapon])
+
   const toggleAnswer = () => {
     setShowAnswer(!showAnswer)
   }
@@ -490,6 +605,10 @@ function Review({ userInfo, accessToken, deck, targetLanguage }: ReviewProps) {
         return;
       }
 
+      if (practice !== null) {
+        return;
+      }
+
       if (event.code === 'Space' || event.code === 'Enter') {
         if (deck.num_cards() === 0) {
           event.preventDefault();
@@ -506,13 +625,20 @@ function Review({ userInfo, accessToken, deck, targetLanguage }: ReviewProps) {
     return () => {
       window.removeEventListener('keydown', handleKeyPress);
     };
-  }, [addNextCards, deck, reviewInfo, currentChallenge, addCardOptions.smart_add]);
+  }, [addNextCards, deck, reviewInfo, currentChallenge, addCardOptions.smart_add, practice]);
 
   return (
     <>
       {/* main content */}
       <div className="flex flex-col flex-1">
 
+        {reviewInfo.catch_up && practice === null && (
+          <div className="mb-2 rounded-lg border bg-muted/30 px-3 py-2 text-sm text-muted-foreground">
+            Catching up: {reviewInfo.catch_up.backlog} overdue reviews, {reviewInfo.catch_up.reviews_today} of them today.
+            {' '}About {reviewInfo.catch_up.days_to_clear} {reviewInfo.catch_up.days_to_clear === 1 ? 'day' : 'days'} to go at this pace.
+          </div>
+        )}
+
         {deck.num_cards() === 0 ? (
           <div className="bg-card text-card-foreground rounded-lg p-12 text-center border">
             <p className="text-lg mb-2">You don't have any flashcards yet!</p>
@@ -523,6 +649,21 @@ function Review({ userInfo, accessToken, deck, targetLanguage }: ReviewProps) {
               Add a word to my deck
             </Button>
           </div>
+        ) : practice === "minimalPairs" ? (
+          <MinimalPairDrill
+            deck={deck}
+            onAnswer={handleMinimalPairAnswer}
+            onExit={() => setPractice(null)}
+            accessToken={accessToken}
+            targetLanguage={targetLanguage}
+          />
+        ) : practice === "customStudy" ? (
+          <CustomStudy
+            deck={deck}
+            accessToken={accessToken}
+            targetLanguage={targetLanguage}
+            onExit={() => setPractice(null)}
+          />
         ) : reviewInfo.due_count === 0 && !currentChallenge ? (
           <NoCardsDue
             nextDueCard={nextDueCard}
@@ -530,6 +671,8 @@ function Review({ userInfo, accessToken, deck, targetLanguage }: ReviewProps) {
             showEngagementPrompts={reviewInfo.total_count > 5 && network.online === true && userInfo !== undefined}
             addCardOptions={addCardOptions}
             targetLanguage={targetLanguage}
+            onDrillMinimalPairs={canDrillMinimalPairs ? () => setPractice("minimalPairs") : undefined}
+            onCustomStudy={() => setPractice("customStudy")}
           />
         ) : currentChallenge ? (
           ('FlashCardReview' in currentChallenge) ? (
@@ -547,6 +690,11 @@ function Review({ userInfo, accessToken, deck, targetLanguage }: ReviewProps) {
               onCantListen={handleCantListen}
               targetLanguage={targetLanguage}
               listeningPrefix={currentChallenge.FlashCardReview.listening_prefix}
+              isProduction={currentChallenge.FlashCardReview.is_production}
+              getMnemonic={currentChallenge.FlashCardReview.is_leech ? () => {
+                const request = deck.get_mnemonic_request(currentChallenge.FlashCardReview.indicator)
+                return request ? get_mnemonic(request, accessToken) : Promise.reject(new Error("No mnemonic for this card"))
+              } : undefined}
             />
           ) : ('TranslateComprehensibleSentence' in currentChallenge) ? (
             <TranslationChallenge
@@ -559,7 +707,51 @@ function Review({ userInfo, accessToken, deck, targetLanguage }: ReviewProps) {
               unique_target_language_lexeme_definitions={currentChallenge.TranslateComprehensibleSentence.unique_target_language_lexeme_definitions}
               targetLanguage={targetLanguage}
             />
-          ) : (
+          ) : ('ClozeComprehensibleSentence' in currentChallenge) ? (
+            <ClozeChallenge
+              challenge={currentChallenge.ClozeComprehensibleSentence}
+              deck={deck}
+              onComplete={handleClozeComplete}
+              dueCount={reviewInfo.due_count || 0}
+              totalCount={reviewInfo.total_count}
+              accessToken={accessToken}
+              key={currentCardId}
+              targetLanguage={targetLanguage}
+            />
+          ) : ('TranslateToTargetLanguage' in currentChallenge) ? (
+            <ProductionChallenge
+              challenge={currentChallenge.TranslateToTargetLanguage}
+              onComplete={handleProductionComplete}
+              dueCount={reviewInfo.due_count || 0}
+              totalCount={reviewInfo.total_count}
+              accessToken={accessToken}
+              key={currentCardId}
+              targetLanguage={targetLanguage}
+            />
+          ) : ('MultipleChoice' in currentChallenge) ? (
+            <MultipleChoiceChallenge
+              challenge={currentChallenge.MultipleChoice}
+              onComplete={handleMultipleChoiceComplete}
+              dueCount={reviewInfo.due_count || 0}
+              totalCount={reviewInfo.total_count}
+              accessToken={accessToken}
+              key={currentCardId}
+              onCantListen={handleCantListen}
+              targetLanguage={targetLanguage}
+            />
+          ) : ('SpeakComprehensibleSentence' in currentChallenge) ? (
+            <SpeakingChallenge
+              challenge={currentChallenge.SpeakComprehensibleSentence}
+              deck={deck}
+              onComplete={handleSpeakingComplete}
+              onCantSpeak={onCantSpeak}
+              dueCount={reviewInfo.due_count || 0}
+              totalCount={reviewInfo.total_count}
+              accessToken={accessToken}
+              key={currentCardId}
+              targetLanguage={targetLanguage}
+            />
+          ) : ('TranscribeComprehensibleSentence' in currentChallenge) ? (
             <TranscriptionChallenge
               challenge={currentChallenge.TranscribeComprehensibleSentence}
               onComplete={handleTranscriptionComplete}
@@ -570,7 +762,7 @@ function Review({ userInfo, accessToken, deck, targetLanguage }: ReviewProps) {
               onCantListen={handleCantListen}
               targetLanguage={targetLanguage}
             />
-          )
+          ) : <div>Unsupported challenge: {JSON.stringify(currentChallenge)}</div>
         ) : <div>Unexpected challenge state. This is a bug. currentChallenge: {JSON.stringify(currentChallenge)}</div>}
       </div>
       {/* /main content */}
@@ -600,6 +792,7 @@ function useDeck(): { type: "deck", language: Language, deck: Deck | null } | {
 
   useEffect(() => {
     weapon.request_deck_selection()
+    weapon.request_deck_settings()
     weapon.request_reviews()
   }, [weapon])
 
@@ -607,10 +800,11 @@ function useDeck(): { type: "deck", language: Language, deck: Deck | null } | {
     try {
       const num_reviews = weapon.get_stream_num_events("reviews")
       const num_deck_selection = weapon.get_stream_num_events("deck_selection")
-      if (num_reviews === undefined || num_deck_selection === undefined) {
+      const num_settings = weapon.get_stream_num_events("settings")
+      if (num_reviews === undefined || num_deck_selection === undefined || num_settings === undefined) {
         return null
       }
-      return num_reviews + num_deck_selection
+      return num_reviews + num_deck_selection + num_settings
     } catch {
       return null
     }
@@ -619,10 +813,12 @@ function useDeck(): { type: "deck", language: Language, deck: Deck | null } | {
   const subscribe = (callback: () => void) => {
     const handle_reviews = weapon.subscribe_to_stream("reviews", () => { callback() })
     const handle_deck_selection = weapon.subscribe_to_stream("deck_selection", () => { callback() })
+    const handle_settings = weapon.subscribe_to_stream("settings", () => { callback() })
 
     return () => {
       weapon.unsubscribe(handle_reviews)
       weapon.unsubscribe(handle_deck_selection)
+      weapon.unsubscribe(handle_settings)
     }
   }
 
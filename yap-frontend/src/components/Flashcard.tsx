@@ -31,10 +31,20 @@ interface FlashcardProps {
   isNew: boolean
   targetLanguage: Language
   listeningPrefix?: string
+  /** Only given for cards the user keeps forgetting */
+  getMnemonic?: () => Promise<string>
+  /** Production cards show the meaning on the front, and the word is the answer */
+  isProduction?: boolean
 }
 
-const CardFront = ({ content, listeningPrefix }: { content: CardContent<string>, listeningPrefix?: string }) => {
-  if ("Listening" in content) {
+const CardFront = ({ content, listeningPrefix, isProduction }: { content: CardContent<string>, listeningPrefix?: string, isProduction?: boolean }) => {
+  if (isProduction && "Heteronym" in content) {
+    return (<h2 className="text-3xl font-semibold">{content.Heteronym[1].map(def => def.native).join(" / ")}</h2>)
+  }
+  else if (isProduction && "Multiword" in content) {
+    return (<h2 className="text-3xl font-semibold">{content.Multiword[1].meaning}</h2>)
+  }
+  else if ("Listening" in content) {
     const prefix = listeningPrefix || "Le mot est"
     return (
       <h2 className="text-3xl font-semibold flex items-center gap-3 flex-wrap justify-center text-center">
@@ -51,7 +61,7 @@ const CardFront = ({ content, listeningPrefix }: { content: CardContent<string>,
   }
 }
 
-const CardFrontSubtitle = ({ content }: { content: CardContent<string> }) => {
+const CardFrontSubtitle = ({ content, isProduction, targetLanguage }: { content: CardContent<string>, isProduction?: boolean, targetLanguage: Language }) => {
   if ("Listening" in content) {
     return <span className="text-sm text-muted-foreground"> Fill in the blank!</span>
   }
@@ -75,9 +85,17 @@ const CardFrontSubtitle = ({ content }: { content: CardContent<string> }) => {
                                   content.Heteronym[0].pos == "VERB" ? "Verb" :
                                     content.Heteronym[0].pos == "X" ? "Unknown" : "Unknown")
     : "Multiword";
+  if (isProduction) {
+    return <span className="text-sm text-muted-foreground">({partOfSpeech}) How do you say it in {targetLanguage}?</span>
+  }
   return <span className="text-sm text-muted-foreground">({partOfSpeech})</span>
 }
 
+const ProductionAnswer = ({ content }: { content: CardContent<string> }) => {
+  const word = "Heteronym" in content ? content.Heteronym[0].word : "Multiword" in content ? content.Multiword[0] : undefined
+  return word ? <div className="text-3xl font-medium">{word}</div> : null
+}
+
 const CardBack = ({ content }: { content: CardContent<string> }) => {
   if ("Listening" in content) {
     const possible_words: [boolean, string][] = content.Listening.possible_words;
@@ -142,7 +160,43 @@ const CardBack = ({ content }: { content: CardContent<string> }) => {
   }
 }
 
-export const Flashcard = memo(function Flashcard({ audioRequest, content, showAnswer, onToggle, dueCount, totalCount, onRating, accessToken, onCantListen, isNew, targetLanguage, listeningPrefix }: FlashcardProps) {
+const MnemonicButton = ({ getMnemonic }: { getMnemonic: () => Promise<string> }) => {
+  const [mnemonic, setMnemonic] = useState<string | null>(null)
+  const [loading, setLoading] = useState(false)
+
+  if (mnemonic) {
+    return (
+      <div className="text-left bg-amber-500/10 border border-amber-500/20 rounded-lg p-4 text-sm">
+        {mnemonic}
+      </div>
+    )
+  }
+
+  return (
+    <Button
+      variant="outline"
+      size="sm"
+      disabled={loading}
+      onClick={async (e) => {
+        // Don't flip the card
+        e.stopPropagation()
+        setLoading(true)
+        try {
+          setMnemonic(await getMnemonic())
+        } catch (error) {
+          console.error("Failed to get mnemonic:", error)
+          toast.error("Couldn't come up with a memory aid. Please try again.")
+        } finally {
+          setLoading(false)
+        }
+      }}
+    >
+      {loading ? "Thinking..." : "You keep forgetting this one. Get a memory aid?"}
+    </Button>
+  )
+}
+
+export const Flashcard = memo(function Flashcard({ audioRequest, content, showAnswer, onToggle, dueCount, totalCount, onRating, accessToken, onCantListen, isNew, targetLanguage, listeningPrefix, getMnemonic, isProduction }: FlashcardProps) {
   const x = useMotionValue(0)
   const controls = useAnimation()
   const [isDragging, setIsDragging] = useState(false)
@@ -324,13 +378,18 @@ export const Flashcard = memo(function Flashcard({ audioRequest, content, showAn
           <div className="text-center relative z-10">
             <div className="mb-4 justify-center gap-2 flex flex-col items-center w-full">
               <div className="flex items-center justify-between w-full" onClick={(e) => e.stopPropagation()}>
-                <AudioButton
-                  audioRequest={audioRequest}
-                  accessToken={accessToken}
-                  autoPlay={true}
-                />
+                {/* Saying the word would give away the answer to a production card */}
+                {!isProduction || showAnswer ? (
+                  <AudioButton
+                    audioRequest={audioRequest}
+                    accessToken={accessToken}
+                    autoPlay={true}
+                  />
+                ) : (
+                  <div className="w-10" />
+                )}
 
-                <CardFront content={content} listeningPrefix={listeningPrefix} />
+                <CardFront content={content} listeningPrefix={listeningPrefix} isProduction={isProduction} />
 
                 {onRating ? (
                   <DropdownMenu>
@@ -358,7 +417,7 @@ export const Flashcard = memo(function Flashcard({ audioRequest, content, showAn
                   <div className="w-8" /> /* Spacer to keep word centered */
                 )}
               </div>
-              <CardFrontSubtitle content={content} />
+              <CardFrontSubtitle content={content} isProduction={isProduction} targetLanguage={targetLanguage} />
             </div>
 
             <hr className="my-4" />
@@ -370,7 +429,9 @@ export const Flashcard = memo(function Flashcard({ audioRequest, content, showAn
                 animate={{ opacity: 1, y: 0 }}
                 transition={{ duration: 0.2 }}
               >
+                {isProduction && <ProductionAnswer content={content} />}
                 <CardBack content={content} />
+                {getMnemonic && <MnemonicButton getMnemonic={getMnemonic} />}
               </motion.div>)
               : (
                 <div>
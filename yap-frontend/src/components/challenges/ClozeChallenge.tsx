@@ -0,0 +1,202 @@
+import { useState, useEffect, useRef, useMemo, useCallback } from 'react'
+import { type ClozeComprehensibleSentence, type BlankGrade, type Deck, type Language } from '../../../../yap-frontend-rs/pkg/yap_frontend_rs'
+import { Button } from "@/components/ui/button"
+import { Input } from "@/components/ui/input"
+import { AudioButton } from "../AudioButton"
+import { playSoundEffect } from '@/lib/sound-effects'
+import { motion } from "framer-motion"
+import { CardsRemaining } from "../CardsRemaining"
+import { AnimatedCard } from "../AnimatedCard"
+import { AccentedCharacterKeyboard } from "../AccentedCharacterKeyboard"
+
+interface ClozeChallengeProps {
+  challenge: ClozeComprehensibleSentence<string>
+  deck: Deck
+  onComplete: (grades: BlankGrade[]) => void
+  dueCount: number
+  totalCount: number
+  accessToken: string | undefined
+  targetLanguage: Language
+}
+
+export function ClozeChallenge({
+  challenge,
+  deck,
+  onComplete,
+  dueCount,
+  totalCount,
+  accessToken,
+  targetLanguage
+}: ClozeChallengeProps) {
+  const [userInputs, setUserInputs] = useState<Map<number, string>>(new Map())
+  const [grades, setGrades] = useState<BlankGrade[] | null>(null)
+  const [focusedInputIndex, setFocusedInputIndex] = useState<number | null>(null)
+  const inputRefs = useRef<(HTMLInputElement | null)[]>([])
+
+  const blankIndices: number[] = useMemo(() => {
+    const blankIndices: number[] = [];
+    challenge.parts.forEach((item, index) => {
+      if ("Blank" in item) {
+        blankIndices.push(index)
+      }
+    })
+    return blankIndices;
+  }, [challenge])
+
+  useEffect(() => {
+    const firstBlankIndex = blankIndices[0]
+    if (firstBlankIndex !== undefined) {
+      setTimeout(() => {
+        inputRefs.current[firstBlankIndex]?.focus()
+      }, 100)
+    }
+  }, [blankIndices])
+
+  const handleInputChange = (index: number, value: string) => {
+    const newInputs = new Map(userInputs)
+    newInputs.set(index, value)
+    setUserInputs(newInputs)
+  }
+
+  const handleCharacterInsert = (char: string) => {
+    const targetIndex = focusedInputIndex !== null ? focusedInputIndex : blankIndices[0]
+    if (targetIndex !== undefined) {
+      handleInputChange(targetIndex, (userInputs.get(targetIndex) || '') + char)
+      inputRefs.current[targetIndex]?.focus()
+    }
+  }
+
+  const allBlanksFilledOut = blankIndices.every((index) => (userInputs.get(index)?.trim() || '') !== '')
+
+  const handleSubmit = useCallback(() => {
+    if (grades !== null) return
+    const submissions = blankIndices.map(index => userInputs.get(index)?.trim() || '')
+    const graded = deck.grade_cloze(challenge, submissions)
+    setGrades(graded)
+    if (graded.every(grade => 'Perfect' in grade)) {
+      playSoundEffect('perfect')
+    }
+  }, [grades, blankIndices, userInputs, deck, challenge])
+
+  useEffect(() => {
+    const handleKeyDown = (e: KeyboardEvent) => {
+      if (e.key !== 'Enter') return
+      e.preventDefault()
+      if (grades !== null) {
+        onComplete(grades)
+        return
+      }
+      const currentIndex = inputRefs.current.findIndex(ref => ref === document.activeElement)
+      const nextBlankIndex = blankIndices[blankIndices.findIndex(index => index === currentIndex) + 1]
+      if (currentIndex !== -1 && nextBlankIndex !== undefined) {
+        inputRefs.current[nextBlankIndex]?.focus()
+      } else if (allBlanksFilledOut) {
+        handleSubmit()
+      }
+    }
+
+    window.addEventListener('keydown', handleKeyDown)
+    return () => window.removeEventListener('keydown', handleKeyDown)
+  }, [grades, onComplete, blankIndices, allBlanksFilledOut, handleSubmit])
+
+  const getInputClassName = (index: number) => {
+    if (grades !== null) {
+      const grade = grades[blankIndices.findIndex((blankIndex) => blankIndex === index)]
+      if ('Perfect' in grade) {
+        return 'border-green-500 bg-green-50 dark:bg-green-950'
+      } else if ('WrongAccents' in grade || 'WrongForm' in grade) {
+        return 'border-yellow-500 bg-yellow-50 dark:bg-yellow-950'
+      } else {
+        return 'border-red-500 bg-red-50 dark:bg-red-950'
+      }
+    }
+    return 'border-muted-foreground/30'
+  }
+
+  return (
+    <div className="flex flex-col flex-1 justify-between">
+      <div>
+        <AnimatedCard className="bg-card text-card-foreground rounded-lg p-3 border">
+          <div className="space-y-6">
+            <div className="text-center pt-4 space-y-4">
+              <p className="text-lg text-muted-foreground">{challenge.native_translation}</p>
+              <div className="text-2xl font-semibold leading-relaxed">
+                {challenge.parts.map((item, index) => {
+                  if ("Blank" in item) {
+                    return (
+                      <span key={index}>
+                        <Input
+                          ref={el => { inputRefs.current[index] = el }}
+                          type="text"
+                          value={userInputs.get(index) || ''}
+                          onChange={(e) => handleInputChange(index, e.target.value)}
+                          onFocus={() => setFocusedInputIndex(index)}
+                          disabled={grades !== null}
+                          className={`inline-block w-32 mx-1 text-center text-2xl font-semibold ${getInputClassName(index)}`}
+                        />
+                        <span>{item.Blank.answer.whitespace}</span>
+                      </span>
+                    )
+                  }
+                  return <span key={index}>{item.Provided.part.text}{item.Provided.part.whitespace}</span>
+                })}
+              </div>
+            </div>
+
+            {grades !== null && (
+              <motion.div
+                initial={{ opacity: 0, y: 10 }}
+                animate={{ opacity: 1, y: 0 }}
+                transition={{ duration: 0.2 }}
+                className="rounded-lg p-4 border bg-green-500/10 border-green-500/20"
+              >
+                <div className="flex items-center gap-2">
+                  <p className="text-lg font-medium">{challenge.target_language}</p>
+                  <AudioButton
+                    audioRequest={challenge.audio}
+                    accessToken={accessToken}
+                    autoPlay={true}
+                    size="icon"
+                    variant="ghost"
+                  />
+                </div>
+                {grades.some(grade => 'WrongAccents' in grade) && (
+                  <p className="text-sm text-muted-foreground mt-1">Watch the accents!</p>
+                )}
+                {grades.some(grade => 'WrongForm' in grade) && (
+                  <p className="text-sm text-muted-foreground mt-1">Right word, wrong form.</p>
+                )}
+              </motion.div>
+            )}
+          </div>
+        </AnimatedCard>
+
+        {grades === null && (targetLanguage === 'French' || targetLanguage === 'Spanish') && (
+          <AccentedCharacterKeyboard
+            onCharacterInsert={handleCharacterInsert}
+            language={targetLanguage}
+            className="hidden md:flex mt-3 p-3 border rounded-lg bg-muted/30"
+          />
+        )}
+
+        <CardsRemaining
+          dueCount={dueCount}
+          totalCount={totalCount}
+          className="mt-2"
+        />
+      </div>
+
+      <div className="mt-4 flex flex-col gap-2">
+        <Button
+          onClick={grades !== null ? () => onComplete(grades) : handleSubmit}
+          disabled={grades === null && !allBlanksFilledOut}
+          className="w-full h-14"
+          size="lg"
+        >
+          {grades === null ? "Check Answer" : grades.every(grade => 'Perfect' in grade) ? "Nailed it!" : "Continue"}
+          <span className="ml-2 text-sm text-muted-foreground">(⏎)</span>
+        </Button>
+      </div>
+    </div>
+  )
+}
@@ -0,0 +1,139 @@
+import { useState, useEffect, useMemo } from 'react'
+import { type Language, type MinimalPair, Deck } from '../../../../yap-frontend-rs/pkg/yap_frontend_rs'
+import { Button } from "@/components/ui/button"
+import { AudioButton } from "../AudioButton"
+import { playSoundEffect } from '@/lib/sound-effects'
+import { AnimatedCard } from "../AnimatedCard"
+
+interface MinimalPairDrillProps {
+  deck: Deck
+  onAnswer: (drill: MinimalPair, chosenOption: number) => void
+  onExit: () => void
+  accessToken: string | undefined
+  targetLanguage: Language
+}
+
+export function MinimalPairDrill({
+  deck,
+  onAnswer,
+  onExit,
+  accessToken,
+  targetLanguage
+}: MinimalPairDrillProps) {
+  // A new drill each time an answer lands in the deck
+  const drill = useMemo(() => deck.get_minimal_pair_drill(), [deck])
+  const accuracy = useMemo(() => deck.get_contrast_accuracy(), [deck])
+  const [chosen, setChosen] = useState<number | null>(null)
+  // So the audio plays again even if the next drill is the same word
+  const [round, setRound] = useState(0)
+
+  const choose = (option: number) => {
+    if (!drill || chosen !== null) return
+    setChosen(option)
+    playSoundEffect(option === drill.correct_option ? 'success' : 'fail')
+  }
+
+  const next = () => {
+    if (!drill || chosen === null) return
+    onAnswer(drill, chosen)
+    setChosen(null)
+    setRound(round + 1)
+  }
+
+  useEffect(() => {
+    const handleKeyDown = (e: KeyboardEvent) => {
+      if (!drill) return
+      if (chosen === null) {
+        const option = parseInt(e.key) - 1
+        if (option >= 0 && option < drill.options.length) {
+          e.preventDefault()
+          choose(option)
+        }
+      } else if (e.key === 'Enter') {
+        e.preventDefault()
+        next()
+      }
+    }
+
+    window.addEventListener('keydown', handleKeyDown)
+    return () => window.removeEventListener('keydown', handleKeyDown)
+  })
+
+  if (!drill) {
+    return (
+      <div className="bg-card text-card-foreground rounded-lg p-12 text-center border space-y-4">
+        <p className="text-muted-foreground">
+          Once you know some {targetLanguage} words that sound nearly alike, you can practice telling them apart here.
+        </p>
+        <Button onClick={onExit} variant="outline">Back</Button>
+      </div>
+    )
+  }
+
+  const optionVariant = (option: number) => {
+    if (chosen === null) return "outline"
+    if (option === drill.correct_option) return "default"
+    return option === chosen ? "destructive" : "outline"
+  }
+
+  return (
+    <div className="flex flex-col flex-1 justify-between">
+      <div>
+        <AnimatedCard className="bg-card text-card-foreground rounded-lg p-3 border">
+          <div className="text-center pt-4 space-y-4">
+            <p className="text-sm text-muted-foreground">Which word do you hear?</p>
+            <AudioButton
+              key={round}
+              audioRequest={drill.audio}
+              accessToken={accessToken}
+              autoPlay={true}
+              size="lg"
+              variant="outline"
+              className="h-14 w-14"
+            />
+            <p className="text-sm text-muted-foreground">
+              /{drill.contrast.sounds[0] || '∅'}/ or /{drill.contrast.sounds[1] || '∅'}/
+            </p>
+          </div>
+        </AnimatedCard>
+
+        <div className="mt-3 grid grid-cols-2 gap-2">
+          {drill.options.map((option, index) => (
+            <Button
+              key={option}
+              onClick={() => choose(index)}
+              variant={optionVariant(index)}
+              size="lg"
+              className="h-16 text-xl"
+            >
+              {option}
+            </Button>
+          ))}
+        </div>
+
+        {accuracy.length > 0 && (
+          <div className="mt-4 text-sm text-muted-foreground space-y-1">
+            <p>Sounds you mix up most:</p>
+            {accuracy.slice(0, 3).map(({ contrast, attempts, correct }) => (
+              <p key={contrast.sounds.join('/')}>
+                /{contrast.sounds[0] || '∅'}/ vs /{contrast.sounds[1] || '∅'}/: {correct} of {attempts} right
+              </p>
+            ))}
+          </div>
+        )}
+      </div>
+
+      <div className="mt-4 flex flex-col gap-2">
+        {chosen !== null && (
+          <Button onClick={next} className="w-full h-14" size="lg">
+            Next
+            <span className="ml-2 text-sm text-muted-foreground">(⏎)</span>
+          </Button>
+        )}
+        <Button onClick={onExit} variant="outline" className="w-full h-12">
+          Done
+        </Button>
+      </div>
+    </div>
+  )
+}
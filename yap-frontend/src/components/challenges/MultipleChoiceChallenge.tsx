@@ -0,0 +1,122 @@
+import { useState, useEffect } from 'react'
+import { type MultipleChoice, type Language } from '../../../../yap-frontend-rs/pkg/yap_frontend_rs'
+import { Button } from "@/components/ui/button"
+import { AudioButton } from "../AudioButton"
+import { playSoundEffect } from '@/lib/sound-effects'
+import { CardsRemaining } from "../CardsRemaining"
+import { AnimatedCard } from "../AnimatedCard"
+import { CantListenButton } from "../CantListenButton"
+
+interface MultipleChoiceChallengeProps {
+  challenge: MultipleChoice<string>
+  onComplete: (chosenOption: number) => void
+  dueCount: number
+  totalCount: number
+  accessToken: string | undefined
+  onCantListen?: () => void
+  targetLanguage: Language
+}
+
+export function MultipleChoiceChallenge({
+  challenge,
+  onComplete,
+  dueCount,
+  totalCount,
+  accessToken,
+  onCantListen,
+  targetLanguage
+}: MultipleChoiceChallengeProps) {
+  const [chosen, setChosen] = useState<number | null>(null)
+  const isListening = 'ListeningHomophonous' in challenge.indicator
+
+  const choose = (option: number) => {
+    if (chosen !== null) return
+    setChosen(option)
+    playSoundEffect(option === challenge.correct_option ? 'success' : 'fail')
+  }
+
+  useEffect(() => {
+    const handleKeyDown = (e: KeyboardEvent) => {
+      if (chosen === null) {
+        const option = parseInt(e.key) - 1
+        if (option >= 0 && option < challenge.options.length) {
+          e.preventDefault()
+          choose(option)
+        }
+      } else if (e.key === 'Enter') {
+        e.preventDefault()
+        onComplete(chosen)
+      }
+    }
+
+    window.addEventListener('keydown', handleKeyDown)
+    return () => window.removeEventListener('keydown', handleKeyDown)
+  })
+
+  const optionVariant = (option: number) => {
+    if (chosen === null) return "outline"
+    if (option === challenge.correct_option) return "default"
+    return option === chosen ? "destructive" : "outline"
+  }
+
+  return (
+    <div className="flex flex-col flex-1 justify-between">
+      <div>
+        <AnimatedCard className="bg-card text-card-foreground rounded-lg p-3 border">
+          <div className="text-center pt-4 space-y-4">
+            <p className="text-sm text-muted-foreground">
+              {isListening ? `Which ${targetLanguage} word do you hear?` : 'Pick the right answer:'}
+            </p>
+            <div className="flex items-center justify-center gap-2">
+              {challenge.prompt && (
+                <p className="text-2xl font-semibold">{challenge.prompt}</p>
+              )}
+              {(isListening || chosen !== null) && (
+                <AudioButton
+                  audioRequest={challenge.audio}
+                  accessToken={accessToken}
+                  autoPlay={true}
+                  size="icon"
+                  variant="ghost"
+                />
+              )}
+            </div>
+            {isListening && chosen === null && onCantListen && (
+              <CantListenButton onClick={onCantListen} />
+            )}
+          </div>
+        </AnimatedCard>
+
+        <div className="mt-3 grid gap-2">
+          {challenge.options.map((option, index) => (
+            <Button
+              key={option}
+              onClick={() => choose(index)}
+              variant={optionVariant(index)}
+              size="lg"
+              className="h-auto min-h-12 whitespace-normal text-lg"
+            >
+              {option}
+              <span className="ml-2 text-sm text-muted-foreground hidden md:inline">({index + 1})</span>
+            </Button>
+          ))}
+        </div>
+
+        <CardsRemaining
+          dueCount={dueCount}
+          totalCount={totalCount}
+          className="mt-2"
+        />
+      </div>
+
+      {chosen !== null && (
+        <div className="mt-4">
+          <Button onClick={() => onComplete(chosen)} className="w-full h-14" size="lg">
+            Continue
+            <span className="ml-2 text-sm text-muted-foreground">(⏎)</span>
+          </Button>
+        </div>
+      )}
+    </div>
+  )
+}
@@ -0,0 +1,186 @@
+import { useState, useEffect, useRef, useCallback } from 'react'
+import { type TranslateToTargetLanguage, type Lexeme, type Language, autograde_production } from '../../../../yap-frontend-rs/pkg/yap_frontend_rs'
+import { Button } from "@/components/ui/button"
+import { Input } from "@/components/ui/input"
+import { AudioButton } from "../AudioButton"
+import { playSoundEffect } from '@/lib/sound-effects'
+import { motion } from "framer-motion"
+import { CardsRemaining } from "../CardsRemaining"
+import { AnimatedCard } from "../AnimatedCard"
+import { AccentedCharacterKeyboard } from "../AccentedCharacterKeyboard"
+
+export type ProductionGrade = { perfect: true } | { remembered: Lexeme<string>[], forgotten: Lexeme<string>[] }
+
+interface ProductionChallengeProps {
+  challenge: TranslateToTargetLanguage<string>
+  onComplete: (grade: ProductionGrade, submission: string) => void
+  dueCount: number
+  totalCount: number
+  accessToken: string | undefined
+  targetLanguage: Language
+}
+
+function lexemeText(lexeme: Lexeme<string>): string {
+  return "Heteronym" in lexeme ? lexeme.Heteronym.word : lexeme.Multiword
+}
+
+export function ProductionChallenge({
+  challenge,
+  onComplete,
+  dueCount,
+  totalCount,
+  accessToken,
+  targetLanguage
+}: ProductionChallengeProps) {
+  const [submission, setSubmission] = useState('')
+  // null until submitted, then "grading" until the server answers. If it can't, the user grades themselves.
+  const [grade, setGrade] = useState<ProductionGrade | "grading" | "selfGrade" | null>(null)
+  const [explanation, setExplanation] = useState<string | null>(null)
+  const inputRef = useRef<HTMLInputElement | null>(null)
+
+  useEffect(() => {
+    setTimeout(() => inputRef.current?.focus(), 100)
+  }, [])
+
+  const handleSubmit = useCallback(async () => {
+    if (grade !== null || submission.trim() === '') return
+    setGrade("grading")
+    try {
+      const response = await autograde_production(
+        challenge.native_language,
+        challenge.target_language,
+        submission.trim(),
+        challenge.primary_expression,
+        challenge.unique_target_language_lexemes,
+        accessToken,
+        targetLanguage
+      )
+      setExplanation(response.explanation ?? null)
+      if (response.expressions_forgot.length === 0) {
+        playSoundEffect('perfect')
+        setGrade({ perfect: true })
+      } else {
+        setGrade({ remembered: response.expressions_remembered, forgotten: response.expressions_forgot })
+      }
+    } catch (error) {
+      console.error('Autograde failed:', error)
+      setGrade("selfGrade")
+    }
+  }, [grade, submission, challenge, accessToken, targetLanguage])
+
+  const selfGrade = (correct: boolean) => {
+    onComplete(correct ? { perfect: true } : { remembered: [], forgotten: [challenge.primary_expression] }, submission.trim())
+  }
+
+  useEffect(() => {
+    const handleKeyDown = (e: KeyboardEvent) => {
+      if (e.key !== 'Enter') return
+      e.preventDefault()
+      if (grade === null) {
+        handleSubmit()
+      } else if (grade !== "grading" && grade !== "selfGrade") {
+        onComplete(grade, submission.trim())
+      }
+    }
+
+    window.addEventListener('keydown', handleKeyDown)
+    return () => window.removeEventListener('keydown', handleKeyDown)
+  }, [grade, handleSubmit, onComplete, submission])
+
+  const graded = grade !== null && grade !== "grading"
+  const result = typeof grade === "object" ? grade : null
+
+  return (
+    <div className="flex flex-col flex-1 justify-between">
+      <div>
+        <AnimatedCard className="bg-card text-card-foreground rounded-lg p-3 border">
+          <div className="space-y-6">
+            <div className="text-center pt-4 space-y-4">
+              <p className="text-sm text-muted-foreground">Say this in {targetLanguage}:</p>
+              <p className="text-2xl font-semibold leading-relaxed">{challenge.native_language}</p>
+              <Input
+                ref={inputRef}
+                type="text"
+                value={submission}
+                onChange={(e) => setSubmission(e.target.value)}
+                disabled={grade !== null}
+                className="text-center text-xl"
+              />
+            </div>
+
+            {graded && (
+              <motion.div
+                initial={{ opacity: 0, y: 10 }}
+                animate={{ opacity: 1, y: 0 }}
+                transition={{ duration: 0.2 }}
+                className={`rounded-lg p-4 border ${result && "perfect" in result ? "bg-green-500/10 border-green-500/20" : "bg-muted/30 border-muted/20"}`}
+              >
+                <div className="flex items-center gap-2">
+                  <p className="text-lg font-medium">{challenge.target_language}</p>
+                  <AudioButton
+                    audioRequest={challenge.audio}
+                    accessToken={accessToken}
+                    autoPlay={true}
+                    size="icon"
+                    variant="ghost"
+                  />
+                </div>
+                {explanation && (
+                  <p className="text-sm text-muted-foreground mt-1">{explanation}</p>
+                )}
+                {result && "forgotten" in result && result.forgotten.length > 0 && (
+                  <p className="text-sm text-muted-foreground mt-1">
+                    Words to work on: {result.forgotten.map(lexemeText).join(", ")}
+                  </p>
+                )}
+                {grade === "selfGrade" && (
+                  <p className="text-sm text-muted-foreground mt-1">Couldn't check your answer automatically. Did you get it right?</p>
+                )}
+              </motion.div>
+            )}
+          </div>
+        </AnimatedCard>
+
+        {grade === null && (targetLanguage === 'French' || targetLanguage === 'Spanish') && (
+          <AccentedCharacterKeyboard
+            onCharacterInsert={(char) => {
+              setSubmission(submission + char)
+              inputRef.current?.focus()
+            }}
+            language={targetLanguage}
+            className="hidden md:flex mt-3 p-3 border rounded-lg bg-muted/30"
+          />
+        )}
+
+        <CardsRemaining
+          dueCount={dueCount}
+          totalCount={totalCount}
+          className="mt-2"
+        />
+      </div>
+
+      <div className="mt-4 flex flex-col gap-2">
+        {grade === "selfGrade" ? (
+          <div className="grid grid-cols-2 gap-2">
+            <Button onClick={() => selfGrade(false)} variant="destructive" size="lg" className="h-14">
+              I got it wrong
+            </Button>
+            <Button onClick={() => selfGrade(true)} variant="default" size="lg" className="h-14">
+              I got it right
+            </Button>
+          </div>
+        ) : (
+          <Button
+            onClick={grade === null ? handleSubmit : () => { if (result) onComplete(result, submission.trim()) }}
+            disabled={grade === "grading" || (grade === null && submission.trim() === '')}
+            className="w-full h-14"
+            size="lg"
+          >
+            {grade === null ? "Check Answer" : grade === "grading" ? "Checking..." : result && "perfect" in result ? "Nailed it!" : "Continue"}
+            <span className="ml-2 text-sm text-muted-foreground">(⏎)</span>
+          </Button>
+        )}
+      </div>
+    </div>
+  )
+}
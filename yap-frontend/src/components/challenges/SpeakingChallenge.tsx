@@ -0,0 +1,187 @@
+import { useState, useEffect, useRef, useCallback } from 'react'
+import { type SpeakComprehensibleSentence, type SpokenPart, type Language, Deck, transcribe_speech } from '../../../../yap-frontend-rs/pkg/yap_frontend_rs'
+import { Button } from "@/components/ui/button"
+import { Mic, MicOff, Square } from "lucide-react"
+import { AudioButton } from "../AudioButton"
+import { AudioVisualizer } from "../AudioVisualizer"
+import { playSoundEffect } from '@/lib/sound-effects'
+import { motion } from "framer-motion"
+import { CardsRemaining } from "../CardsRemaining"
+import { AnimatedCard } from "../AnimatedCard"
+import { toast } from 'sonner'
+
+interface SpeakingChallengeProps {
+  challenge: SpeakComprehensibleSentence<string>
+  deck: Deck
+  onComplete: (parts: SpokenPart[]) => void
+  onCantSpeak: () => void
+  dueCount: number
+  totalCount: number
+  accessToken: string | undefined
+  targetLanguage: Language
+}
+
+export function SpeakingChallenge({
+  challenge,
+  deck,
+  onComplete,
+  onCantSpeak,
+  dueCount,
+  totalCount,
+  accessToken,
+  targetLanguage
+}: SpeakingChallengeProps) {
+  const [stage, setStage] = useState<"ready" | "recording" | "transcribing" | "graded">("ready")
+  const [parts, setParts] = useState<SpokenPart[]>([])
+  const [heard, setHeard] = useState<string | null>(null)
+  const recorderRef = useRef<MediaRecorder | null>(null)
+
+  // Don't leave the microphone on if the user moves on mid-recording
+  useEffect(() => {
+    return () => {
+      recorderRef.current?.stream.getTracks().forEach(track => track.stop())
+    }
+  }, [])
+
+  const startRecording = useCallback(async () => {
+    let stream: MediaStream
+    try {
+      stream = await navigator.mediaDevices.getUserMedia({ audio: true })
+    } catch (error) {
+      console.error('Could not get microphone:', error)
+      toast.error("Couldn't use the microphone", {
+        description: 'Allow microphone access to practice speaking.',
+      })
+      return
+    }
+
+    const chunks: Blob[] = []
+    const recorder = new MediaRecorder(stream)
+    recorder.ondataavailable = (e) => chunks.push(e.data)
+    recorder.onstop = async () => {
+      stream.getTracks().forEach(track => track.stop())
+      setStage("transcribing")
+      try {
+        const audio = new Uint8Array(await new Blob(chunks, { type: recorder.mimeType }).arrayBuffer())
+        const transcript = await transcribe_speech(audio, accessToken, targetLanguage)
+        const graded = deck.grade_speech(challenge, transcript)
+        const allCorrect = graded.every(part => !('Spoken' in part) || part.Spoken.correct)
+        playSoundEffect(allCorrect ? 'perfect' : 'fail')
+        setHeard(transcript)
+        setParts(graded)
+        setStage("graded")
+      } catch (error) {
+        console.error('Transcription failed:', error)
+        toast.error("Couldn't make out the recording. Try again?")
+        setStage("ready")
+      }
+    }
+    recorderRef.current = recorder
+    recorder.start()
+    setStage("recording")
+  }, [accessToken, targetLanguage, deck, challenge])
+
+  const stopRecording = () => {
+    recorderRef.current?.stop()
+  }
+
+  useEffect(() => {
+    const handleKeyDown = (e: KeyboardEvent) => {
+      if (e.key !== 'Enter') return
+      e.preventDefault()
+      if (stage === "ready") {
+        startRecording()
+      } else if (stage === "recording") {
+        stopRecording()
+      } else if (stage === "graded") {
+        onComplete(parts)
+      }
+    }
+
+    window.addEventListener('keydown', handleKeyDown)
+    return () => window.removeEventListener('keydown', handleKeyDown)
+  }, [stage, parts, startRecording, onComplete])
+
+  return (
+    <div className="flex flex-col flex-1 justify-between">
+      <div>
+        <AnimatedCard className="bg-card text-card-foreground rounded-lg p-3 border">
+          <div className="text-center pt-4 space-y-4">
+            <p className="text-sm text-muted-foreground">Read this aloud:</p>
+            <p className="text-2xl font-semibold leading-relaxed">
+              {stage === "graded" ? parts.map((part, i) => (
+                'Spoken' in part ? (
+                  <span key={i} className={part.Spoken.correct ? "text-green-600 dark:text-green-400" : "text-red-600 dark:text-red-400"}>
+                    {part.Spoken.part.text}{part.Spoken.part.whitespace}
+                  </span>
+                ) : (
+                  <span key={i}>{part.Unspoken.part.text}{part.Unspoken.part.whitespace}</span>
+                )
+              )) : challenge.target_language}
+            </p>
+            <p className="text-sm text-muted-foreground">{challenge.native_translation}</p>
+            {stage === "recording" && <AudioVisualizer />}
+
+            {stage === "graded" && (
+              <motion.div
+                initial={{ opacity: 0, y: 10 }}
+                animate={{ opacity: 1, y: 0 }}
+                transition={{ duration: 0.2 }}
+                className="rounded-lg p-4 border bg-muted/30 border-muted/20 text-left"
+              >
+                <div className="flex items-center gap-2">
+                  <p className="text-sm text-muted-foreground">How it should sound</p>
+                  <AudioButton
+                    audioRequest={challenge.audio}
+                    accessToken={accessToken}
+                    autoPlay={true}
+                    size="icon"
+                    variant="ghost"
+                  />
+                </div>
+                {heard !== null && (
+                  <p className="text-sm text-muted-foreground mt-1">We heard: "{heard}"</p>
+                )}
+              </motion.div>
+            )}
+          </div>
+        </AnimatedCard>
+
+        <CardsRemaining
+          dueCount={dueCount}
+          totalCount={totalCount}
+          className="mt-2"
+        />
+      </div>
+
+      <div className="mt-4 flex flex-col gap-2">
+        {stage === "graded" ? (
+          <Button onClick={() => onComplete(parts)} className="w-full h-14" size="lg">
+            Continue
+            <span className="ml-2 text-sm text-muted-foreground">(⏎)</span>
+          </Button>
+        ) : (
+          <>
+            <Button
+              onClick={stage === "recording" ? stopRecording : startRecording}
+              disabled={stage === "transcribing"}
+              variant={stage === "recording" ? "destructive" : "default"}
+              className="w-full h-14"
+              size="lg"
+            >
+              {stage === "recording" ? <Square className="mr-2 h-5 w-5" /> : <Mic className="mr-2 h-5 w-5" />}
+              {stage === "recording" ? "Done" : stage === "transcribing" ? "Listening..." : "Start speaking"}
+              <span className="ml-2 text-sm text-muted-foreground">(⏎)</span>
+            </Button>
+            {stage === "ready" && (
+              <Button onClick={onCantSpeak} variant="outline" className="w-full h-12 text-base font-medium">
+                <MicOff className="mr-2 h-5 w-5" />
+                Can't speak now
+              </Button>
+            )}
+          </>
+        )}
+      </div>
+    </div>
+  )
+}
@@ -240,7 +240,7 @@ export function TranscriptionChallenge({
                 // The accent keyboard will refocus when clicked
               }}
               disabled={gradingState !== null}
-              className={`inline-block w-32 mx-1 text-center text-2xl font-semibold ${getInputClassName(index)}`}
+              className={`inline-block ${asked_to_transcribe.parts.length > 1 ? 'w-full' : 'w-32'} mx-1 text-center text-2xl font-semibold ${getInputClassName(index)}`}
               placeholder=""
             />
             <span>{end_whitespace}</span>
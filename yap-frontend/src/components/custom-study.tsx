@@ -0,0 +1,158 @@
+import { useState, useMemo } from 'react'
+import { type CardIndicator, type CustomStudyFilter, type Language, type PartOfSpeech, Deck } from '../../../yap-frontend-rs/pkg'
+import { Button } from "@/components/ui/button"
+import { Input } from "@/components/ui/input"
+import { Label } from "@/components/ui/label"
+import { Switch } from "@/components/ui/switch"
+import { Flashcard } from "./Flashcard"
+import { AnimatedCard } from "./AnimatedCard"
+import { playSoundEffect } from '@/lib/sound-effects'
+import { useWeapon } from '@/weapon'
+
+const PARTS_OF_SPEECH: [PartOfSpeech, string][] = [
+  ["NOUN", "Nouns"],
+  ["VERB", "Verbs"],
+  ["ADJ", "Adjectives"],
+  ["ADV", "Adverbs"],
+]
+
+const STATES: [string, string][] = [
+  ["new", "New"],
+  ["learning", "Learning"],
+  ["review", "Review"],
+  ["relearning", "Relearning"],
+]
+
+interface CustomStudyProps {
+  deck: Deck
+  accessToken: string | undefined
+  targetLanguage: Language
+  onExit: () => void
+}
+
+function toggle<T>(values: T[], value: T): T[] {
+  return values.includes(value) ? values.filter(v => v !== value) : [...values, value]
+}
+
+export function CustomStudy({ deck, accessToken, targetLanguage, onExit }: CustomStudyProps) {
+  const weapon = useWeapon()
+  const [partsOfSpeech, setPartsOfSpeech] = useState<PartOfSpeech[]>([])
+  const [states, setStates] = useState<string[]>([])
+  const [failedThisWeek, setFailedThisWeek] = useState(false)
+  const [multiwordOnly, setMultiwordOnly] = useState(false)
+  const [minLapses, setMinLapses] = useState('')
+  // Cramming leaves the cards' schedules alone, for a last look before a trip or a test
+  const [cram, setCram] = useState(true)
+  const [session, setSession] = useState<{ cards: CardIndicator<string>[], position: number } | null>(null)
+  const [showAnswer, setShowAnswer] = useState(false)
+
+  const filter: CustomStudyFilter = useMemo(() => ({
+    parts_of_speech: partsOfSpeech,
+    states,
+    min_lapses: minLapses === '' ? undefined : parseInt(minLapses),
+    failed_since: failedThisWeek ? new Date(Date.now() - 7 * 24 * 60 * 60 * 1000).toISOString() : undefined,
+    multiword_only: multiwordOnly,
+  }), [partsOfSpeech, states, minLapses, failedThisWeek, multiwordOnly])
+
+  const matching = useMemo(() => deck.get_custom_study_cards(filter), [deck, filter])
+
+  const card = session ? session.cards[session.position] : undefined
+  const challenge = useMemo(() => card ? deck.get_custom_study_challenge(card) : undefined, [deck, card])
+
+  const handleRating = (rating: 'again' | 'hard' | 'good' | 'easy') => {
+    if (!session || !card) return
+    playSoundEffect(rating === 'again' ? 'fail' : 'success')
+    const event = cram ? deck.cram_card(card, rating) : deck.review_card(card, rating)
+    if (event) {
+      weapon.add_deck_event(event)
+    }
+    setShowAnswer(false)
+    setSession({ ...session, position: session.position + 1 })
+  }
+
+  if (session && card && challenge && 'FlashCardReview' in challenge) {
+    return (
+      <div className="flex flex-col flex-1">
+        <Flashcard
+          audioRequest={challenge.FlashCardReview.audio}
+          content={challenge.FlashCardReview.content}
+          isNew={challenge.FlashCardReview.is_new}
+          showAnswer={showAnswer}
+          onToggle={() => setShowAnswer(!showAnswer)}
+          dueCount={session.cards.length - session.position}
+          totalCount={session.cards.length}
+          onRating={handleRating}
+          accessToken={accessToken}
+          key={session.position}
+          targetLanguage={targetLanguage}
+          listeningPrefix={challenge.FlashCardReview.listening_prefix}
+          isProduction={challenge.FlashCardReview.is_production}
+        />
+        <Button onClick={onExit} variant="outline" className="w-full h-12 mt-2">
+          Stop {cram ? "cramming" : "studying"}
+        </Button>
+      </div>
+    )
+  }
+
+  if (session) {
+    return (
+      <AnimatedCard className="bg-card text-card-foreground rounded-lg p-12 gap-6 flex flex-col text-center border">
+        <p className="text-lg">Done! You went through {session.position} cards.</p>
+        <Button onClick={onExit}>Back</Button>
+      </AnimatedCard>
+    )
+  }
+
+  return (
+    <AnimatedCard className="bg-card text-card-foreground rounded-lg p-6 flex flex-col gap-6 border">
+      <p className="text-lg text-center">Custom study</p>
+
+      <div className="space-y-2">
+        <Label>Kinds of word</Label>
+        <div className="flex flex-wrap gap-2">
+          {PARTS_OF_SPEECH.map(([pos, name]) => (
+            <Button key={pos} size="sm" variant={partsOfSpeech.includes(pos) ? "default" : "outline"} onClick={() => setPartsOfSpeech(toggle(partsOfSpeech, pos))}>
+              {name}
+            </Button>
+          ))}
+        </div>
+      </div>
+
+      <div className="space-y-2">
+        <Label>Cards that are</Label>
+        <div className="flex flex-wrap gap-2">
+          {STATES.map(([state, name]) => (
+            <Button key={state} size="sm" variant={states.includes(state) ? "default" : "outline"} onClick={() => setStates(toggle(states, state))}>
+              {name}
+            </Button>
+          ))}
+        </div>
+      </div>
+
+      <div className="flex items-center justify-between">
+        <Label htmlFor="failed-this-week">Failed in the last week</Label>
+        <Switch id="failed-this-week" checked={failedThisWeek} onCheckedChange={setFailedThisWeek} />
+      </div>
+      <div className="flex items-center justify-between">
+        <Label htmlFor="multiword-only">Only expressions of more than one word</Label>
+        <Switch id="multiword-only" checked={multiwordOnly} onCheckedChange={setMultiwordOnly} />
+      </div>
+      <div className="flex items-center justify-between gap-4">
+        <Label htmlFor="min-lapses">Forgotten at least this many times</Label>
+        <Input id="min-lapses" type="number" min={0} value={minLapses} onChange={(e) => setMinLapses(e.target.value)} className="w-20" />
+      </div>
+      <div className="flex items-center justify-between">
+        <Label htmlFor="cram">Cram (don't change when cards are next due)</Label>
+        <Switch id="cram" checked={cram} onCheckedChange={setCram} />
+      </div>
+
+      <div className="flex flex-col gap-2">
+        <Button onClick={() => setSession({ cards: matching, position: 0 })} disabled={matching.length === 0} size="lg">
+          {cram ? "Cram" : "Study"} {matching.length} cards
+        </Button>
+        <Button onClick={onExit} variant="outline">Back</Button>
+      </div>
+    </AnimatedCard>
+  )
+}
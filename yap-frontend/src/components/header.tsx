@@ -8,7 +8,7 @@ import {
   DropdownMenuTrigger,
 } from "@/components/ui/dropdown-menu"
 import { NotificationSettings } from '@/components/notification-settings'
-import { LogOut, AlertTriangle, Languages } from 'lucide-react'
+import { LogOut, AlertTriangle, Languages, Gauge, Mic, History } from 'lucide-react'
 import { SyncStatusDialog } from '@/components/sync-status-dialog'
 import type { UserInfo } from '@/App'
 import { AuthDialog } from '@/components/auth-dialog'
@@ -18,6 +18,15 @@ interface HeaderProps {
   onSignOut: () => void
   onChangeLanguage?: () => void
   showSignupNag?: boolean
+  /** Whether the recommended daily new card and review limits are on, if there's a deck to set them for */
+  dailyLimitsEnabled?: boolean
+  onToggleDailyLimits?: () => void
+  /** Whether reviews of listening cards ask the user to read a sentence aloud */
+  speakingPracticeEnabled?: boolean
+  onToggleSpeakingPractice?: () => void
+  /** Whether a big backlog of overdue reviews is put in order and spread over several days */
+  catchUpEnabled?: boolean
+  onToggleCatchUp?: () => void
 }
 
 export function Header({
@@ -25,6 +34,12 @@ export function Header({
   onSignOut,
   onChangeLanguage,
   showSignupNag = false,
+  dailyLimitsEnabled,
+  onToggleDailyLimits,
+  speakingPracticeEnabled,
+  onToggleSpeakingPractice,
+  catchUpEnabled,
+  onToggleCatchUp,
 }: HeaderProps) {
   const [authOpen, setAuthOpen] = useState(false)
   const [defaultView, setDefaultView] = useState<'signin' | 'signup'>('signin')
@@ -60,6 +75,24 @@ export function Header({
                     Language
                   </DropdownMenuItem>
                 )}
+                {onToggleDailyLimits && (
+                  <DropdownMenuItem onClick={onToggleDailyLimits}>
+                    <Gauge className="mr-2 h-4 w-4" />
+                    {dailyLimitsEnabled ? "Turn off daily limits" : "Turn on daily limits"}
+                  </DropdownMenuItem>
+                )}
+                {onToggleSpeakingPractice && (
+                  <DropdownMenuItem onClick={onToggleSpeakingPractice}>
+                    <Mic className="mr-2 h-4 w-4" />
+                    {speakingPracticeEnabled ? "Turn off speaking practice" : "Turn on speaking practice"}
+                  </DropdownMenuItem>
+                )}
+                {onToggleCatchUp && (
+                  <DropdownMenuItem onClick={onToggleCatchUp}>
+                    <History className="mr-2 h-4 w-4" />
+                    {catchUpEnabled ? "Turn off catch-up mode" : "Turn on catch-up mode"}
+                  </DropdownMenuItem>
+                )}
                 <DropdownMenuItem onClick={onSignOut}>
                   <LogOut className="mr-2 h-4 w-4" />
                   Sign Out
@@ -17,11 +17,16 @@ interface NoCardsDueProps {
   addNextCards: (card_type: CardType | undefined, count: number) => void
   addCardOptions: AddCardOptions
   targetLanguage: Language
+  /** Set if the user knows some words that sound nearly alike */
+  onDrillMinimalPairs?: () => void
+  onCustomStudy?: () => void
 }
 
-export function NoCardsDue({ nextDueCard, showEngagementPrompts, addNextCards, addCardOptions, targetLanguage }: NoCardsDueProps) {
+export function NoCardsDue({ nextDueCard, showEngagementPrompts, addNextCards, addCardOptions, targetLanguage, onDrillMinimalPairs, onCustomStudy }: NoCardsDueProps) {
   const numCanAddTargetLanguage = addCardOptions.manual_add.find(([, card_type]) => card_type === 'TargetLanguage')?.[0] || 0
   const numCanAddListening = addCardOptions.manual_add.find(([, card_type]) => card_type === 'Listening')?.[0] || 0
+  const numCanAddProduction = addCardOptions.manual_add.find(([, card_type]) => card_type === 'NativeToTarget')?.[0] || 0
+  const canAddManually = numCanAddTargetLanguage > 0 || numCanAddListening > 0 || numCanAddProduction > 0
   const numCanSmartAdd = addCardOptions.smart_add
 
   return (
@@ -40,11 +45,11 @@ export function NoCardsDue({ nextDueCard, showEngagementPrompts, addNextCards, a
               <Button
                 onClick={() => addNextCards(undefined, numCanSmartAdd)}
                 variant="default"
-                className={numCanAddTargetLanguage > 0 || numCanAddListening > 0 ? "rounded-r-none" : ""}
+                className={canAddManually ? "rounded-r-none" : ""}
               >
                 Add {numCanSmartAdd} new cards to my deck
               </Button>
-              {(numCanAddTargetLanguage > 0 || numCanAddListening > 0) && (
+              {canAddManually && (
                 <DropdownMenu>
                   <DropdownMenuTrigger asChild>
                     <Button
@@ -65,6 +70,11 @@ export function NoCardsDue({ nextDueCard, showEngagementPrompts, addNextCards, a
                         Add {numCanAddListening} <span style={{ fontWeight: "bold" }}>{targetLanguage} listening</span> cards
                       </DropdownMenuItem>
                     )}
+                    {numCanAddProduction > 0 && (
+                      <DropdownMenuItem onClick={() => addNextCards("NativeToTarget", numCanAddProduction)}>
+                        Add {numCanAddProduction} <span style={{ fontWeight: "bold" }}>English → {targetLanguage}</span> cards
+                      </DropdownMenuItem>
+                    )}
                   </DropdownMenuContent>
                 </DropdownMenu>
               )}
@@ -74,6 +84,18 @@ export function NoCardsDue({ nextDueCard, showEngagementPrompts, addNextCards, a
               You've learned all available words! Keep practicing to master them.
             </p>
           )}
+          <div className="flex flex-wrap justify-center gap-2">
+            {onDrillMinimalPairs && (
+              <Button onClick={onDrillMinimalPairs} variant="outline">
+                Practice hearing similar sounds
+              </Button>
+            )}
+            {onCustomStudy && (
+              <Button onClick={onCustomStudy} variant="outline">
+                Custom study
+              </Button>
+            )}
+          </div>
         </div>
       </AnimatedCard>
 
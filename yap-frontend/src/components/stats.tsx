@@ -73,12 +73,19 @@ export function Stats({ deck }: StatsProps) {
           </thead>
           <tbody>
             {visibleCards.map((card, index) => {
-              const shortDescription =
+              const lexeme =
                 "TargetLanguage" in card.card_indicator
-                  ? "Heteronym" in card.card_indicator.TargetLanguage.lexeme
-                    ? card.card_indicator.TargetLanguage.lexeme.Heteronym.word
-                    : card.card_indicator.TargetLanguage.lexeme.Multiword
-                  : `/${card.card_indicator.ListeningHomophonous.pronunciation}/`;
+                  ? card.card_indicator.TargetLanguage.lexeme
+                  : "NativeToTarget" in card.card_indicator
+                    ? card.card_indicator.NativeToTarget.lexeme
+                    : null;
+              const shortDescription = lexeme
+                ? "Heteronym" in lexeme
+                  ? lexeme.Heteronym.word
+                  : lexeme.Multiword
+                : "ListeningHomophonous" in card.card_indicator
+                  ? `/${card.card_indicator.ListeningHomophonous.pronunciation}/`
+                  : "";
 
               const isDue = card.due_timestamp_ms <= now;
               return (
@@ -0,0 +1,15 @@
+import { useState, useEffect } from 'react'
+
+// Phones and tablets, where typing an answer is a chore
+export function useIsTouchDevice() {
+  const [isTouchDevice, setIsTouchDevice] = useState(() => window.matchMedia('(pointer: coarse)').matches)
+
+  useEffect(() => {
+    const mediaQuery = window.matchMedia('(pointer: coarse)')
+    const handleChange = () => setIsTouchDevice(mediaQuery.matches)
+    mediaQuery.addEventListener('change', handleChange)
+    return () => mediaQuery.removeEventListener('change', handleChange)
+  }, [])
+
+  return isTouchDevice
+}